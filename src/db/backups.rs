@@ -0,0 +1,217 @@
+// Rotating snapshots of data.db on native builds, kept in dx_app/backups next to the database.
+// Snapshots are taken with the SQLite online backup API on startup, once a day while the app runs, on demand,
+// and before anything that replaces data wholesale (import, merge, reset, restore, schema migrations).
+// Startup and daily snapshots are skipped when data.db hasn't changed since the newest snapshot.
+// Retention (backups/settings.json) keeps the newest `keep_count` snapshots and drops any older than
+// `keep_days`; the newest snapshot is never dropped.
+// SQLCipher builds encrypt snapshots with the data key and copy data.key next to each one
+// (data-….key); a passphrase change or recovery re-keys them along with the database.
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use chrono::{Local, NaiveDateTime};
+use rusqlite::backup::Backup as SqliteBackup;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use super::native;
+
+const NAME_FMT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reason { Startup, Daily, Manual, Import, Reset, Restore, Migration }
+
+const REASONS: [Reason; 7] = [Reason::Startup, Reason::Daily, Reason::Manual, Reason::Import, Reason::Reset, Reason::Restore, Reason::Migration];
+
+impl Reason {
+    fn as_str(self) -> &'static str {
+        match self {
+            Reason::Startup => "startup",
+            Reason::Daily => "daily",
+            Reason::Manual => "manual",
+            Reason::Import => "import",
+            Reason::Reset => "reset",
+            Reason::Restore => "restore",
+            Reason::Migration => "migration",
+        }
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Reason::Startup => "backups.reason_startup",
+            Reason::Daily => "backups.reason_daily",
+            Reason::Manual => "backups.reason_manual",
+            Reason::Import => "backups.reason_import",
+            Reason::Reset => "backups.reason_reset",
+            Reason::Restore => "backups.reason_restore",
+            Reason::Migration => "backups.reason_migration",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub taken_at: NaiveDateTime, // local time, from the file name
+    pub reason: Reason,
+    pub size: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retention {
+    pub keep_count: usize,
+    pub keep_days: u32, // 0: no age limit
+}
+
+impl Default for Retention {
+    fn default() -> Self { Retention { keep_count: 10, keep_days: 30 } }
+}
+
+pub fn dir() -> PathBuf { native::db_file_path().with_file_name("backups") }
+
+fn settings_path() -> PathBuf { dir().join("settings.json") }
+
+pub fn retention() -> Retention {
+    std::fs::read_to_string(settings_path()).ok().and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+pub fn set_retention(r: Retention) -> Result<(), String> {
+    let _ = std::fs::create_dir_all(dir());
+    let json = serde_json::to_string_pretty(&r).map_err(|e| e.to_string())?;
+    let tmp = settings_path().with_extension("tmp");
+    std::fs::write(&tmp, json).and_then(|_| std::fs::rename(&tmp, settings_path())).map_err(|e| e.to_string())?;
+    prune(r);
+    Ok(())
+}
+
+// "data-20250102-030405-startup.db"
+fn parse_name(path: &Path) -> Option<(NaiveDateTime, Reason)> {
+    let stem = path.file_name()?.to_str()?.strip_prefix("data-")?.strip_suffix(".db")?;
+    let (stamp, reason) = stem.split_at_checked(15)?;
+    let reason = REASONS.into_iter().find(|r| reason.strip_prefix('-') == Some(r.as_str()))?;
+    Some((NaiveDateTime::parse_from_str(stamp, NAME_FMT).ok()?, reason))
+}
+
+// Newest first
+pub fn list() -> Vec<Backup> {
+    let Ok(entries) = std::fs::read_dir(dir()) else { return Vec::new() };
+    let mut backups: Vec<Backup> = entries.flatten().filter_map(|e| {
+        let path = e.path();
+        let (taken_at, reason) = parse_name(&path)?;
+        Some(Backup { size: e.metadata().map(|m| m.len()).unwrap_or(0), path, taken_at, reason })
+    }).collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.taken_at));
+    backups
+}
+
+fn prune(r: Retention) {
+    let cutoff = Local::now().naive_local() - chrono::Duration::days(r.keep_days.into());
+    for (i, b) in list().iter().enumerate().skip(1) {
+        if i >= r.keep_count.max(1) || (r.keep_days > 0 && b.taken_at < cutoff) {
+            #[cfg(feature = "encryption")]
+            let _ = std::fs::remove_file(b.path.with_extension("key"));
+            match std::fs::remove_file(&b.path) {
+                Ok(()) => native::log_note(&format!("Removed backup {}", b.path.display())),
+                Err(e) => native::log_note(&format!("Could not remove backup {}: {}", b.path.display(), e)),
+            }
+        }
+    }
+}
+
+// A snapshot file; encrypted builds key it with the open database's data key
+fn open_file(path: &Path, flags: OpenFlags) -> Result<Connection, String> {
+    let conn = Connection::open_with_flags(path, flags).map_err(|e| e.to_string())?;
+    #[cfg(feature = "encryption")]
+    {
+        let key = native::data_key().ok_or("the database is locked")?;
+        native::use_key(&conn, &key).map_err(|e| e.to_string())?;
+    }
+    Ok(conn)
+}
+
+fn copy(from: &Connection, to: &mut Connection) -> Result<(), String> {
+    SqliteBackup::new(from, to)
+        .and_then(|b| b.run_to_completion(100, Duration::from_millis(250), None))
+        .map_err(|e| e.to_string())
+}
+
+// In-memory databases (the fallback when data.db can't be opened) have nothing to save
+fn write(conn: &Connection, reason: Reason) -> Result<Option<PathBuf>, String> {
+    if conn.path().is_none_or(str::is_empty) { return Ok(None); }
+    let dir = dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("data-{}-{}.db", Local::now().format(NAME_FMT), reason.as_str()));
+    let part = path.with_extension("db.part");
+    copy(conn, &mut open_file(&part, OpenFlags::default())?)?;
+    #[cfg(feature = "encryption")]
+    if let Err(e) = super::cipher::copy_key_file(&path.with_extension("key")) {
+        let _ = std::fs::remove_file(&part);
+        return Err(e.to_string());
+    }
+    std::fs::rename(&part, &path).map_err(|e| e.to_string())?;
+    native::log_note(&format!("Backup {} written", path.display()));
+    Ok(Some(path))
+}
+
+// Snapshot an open connection; migrations call this before the shared connection exists
+pub(crate) fn take_from(conn: &Connection, reason: Reason) -> Result<Option<PathBuf>, String> {
+    let path = write(conn, reason)?;
+    prune(retention());
+    Ok(path)
+}
+
+pub fn take(reason: Reason) -> Result<Option<PathBuf>, String> { take_from(&native::connection(), reason) }
+
+// True when data.db was written after the newest snapshot
+fn changed_since_last() -> bool {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+    list().first().is_none_or(|b| modified(&native::db_file_path()) > modified(&b.path))
+}
+
+// Startup snapshot, then a daily one while the app stays open; runs on its own thread
+pub fn start() {
+    std::thread::spawn(|| {
+        // Encrypted builds wait for the passphrase
+        #[cfg(feature = "encryption")]
+        while !native::is_unlocked() { std::thread::sleep(Duration::from_secs(1)); }
+        drop(native::connection()); // open (and migrate) the database before comparing timestamps
+        if changed_since_last() && let Err(e) = take(Reason::Startup) { native::log_note(&format!("Startup backup failed: {}", e)); }
+        loop {
+            std::thread::sleep(Duration::from_secs(60 * 60));
+            let last = list().into_iter().find(|b| matches!(b.reason, Reason::Startup | Reason::Daily));
+            let due = last.is_none_or(|b| Local::now().naive_local() - b.taken_at >= chrono::Duration::days(1));
+            if due && changed_since_last() && let Err(e) = take(Reason::Daily) { native::log_note(&format!("Daily backup failed: {}", e)); }
+        }
+    });
+}
+
+// Replace the live database with a snapshot. The current state is snapshotted first so the
+// restore can be undone, and an older snapshot is migrated to the current schema afterwards.
+pub fn restore(path: &Path) -> Result<(), String> {
+    if path.parent() != Some(dir().as_path()) || parse_name(path).is_none() { return Err("not a backup file".into()); }
+    let src = open_file(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let check = src.query_row("PRAGMA quick_check", [], |r| r.get::<_, String>(0)).map_err(|e| e.to_string())?;
+    if check != "ok" { return Err(format!("backup is damaged: {}", check)); }
+    {
+        let mut conn = native::connection();
+        // Not pruned until the restore is done, so the new snapshot can't push out the one being restored
+        write(&conn, Reason::Restore)?;
+        copy(&src, &mut conn)?;
+        native::prepare(&conn)?;
+    }
+    native::log_note(&format!("Restored backup {}", path.display()));
+    prune(retention());
+    Ok(())
+}
+
+// Move every snapshot from the old data key to the new one after a re-key, with a fresh copy of data.key;
+// a snapshot that fails keeps its old key and key file
+#[cfg(feature = "encryption")]
+pub(crate) fn rekey_all(old: &[u8], new: &[u8]) {
+    for b in list() {
+        let res = native::open_keyed(&b.path, old)
+            .and_then(|c| native::apply_key(&c, "rekey", new))
+            .map_err(|e| e.to_string())
+            .and_then(|_| super::cipher::copy_key_file(&b.path.with_extension("key")).map_err(|e| e.to_string()));
+        if let Err(e) = res { native::log_note(&format!("Could not re-key backup {}: {}", b.path.display(), e)); }
+    }
+}
@@ -0,0 +1,322 @@
+// SQLCipher unlock for native builds with the `encryption` feature.
+// The database is encrypted with a random data key. data.key (next to data.db) holds that
+// key wrapped under a key derived from the passphrase with Argon2id, and optionally a second
+// copy wrapped under a one-time recovery code. Changing the passphrase re-keys the database and its backups.
+// A data.db created by a build without encryption is migrated in place on setup.
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use super::native;
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const RECOVERY_LEN: usize = 20;
+// Minimum passphrase length accepted when creating a new database
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+type RawKey = Zeroizing<[u8; KEY_LEN]>;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct KdfParams {
+    salt: String, // hex
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Recovery {
+    #[serde(flatten)]
+    kdf: KdfParams,
+    wrapped_key: String, // data key under the recovery code key
+    wrapped_kek: String, // recovery code key under the data key, so a re-key can carry the code over
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    kdf: String, // 'argon2id'
+    #[serde(flatten)]
+    params: KdfParams,
+    wrapped_key: String, // data key under the passphrase key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recovery: Option<Recovery>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CipherError {
+    WrongPassphrase,
+    WrongRecoveryCode,
+    NoRecoveryCode,
+    TooShort,
+    Io(String),
+    Kdf(String),
+    Db(String),
+}
+
+impl std::fmt::Display for CipherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CipherError::WrongPassphrase => write!(f, "wrong passphrase"),
+            CipherError::WrongRecoveryCode => write!(f, "wrong recovery code"),
+            CipherError::NoRecoveryCode => write!(f, "no recovery code has been created"),
+            CipherError::TooShort => write!(f, "passphrase must be at least {} characters", MIN_PASSPHRASE_LEN),
+            CipherError::Io(e) => write!(f, "file error: {}", e),
+            CipherError::Kdf(e) => write!(f, "key derivation failed: {}", e),
+            CipherError::Db(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CipherError {}
+
+fn key_file_path() -> PathBuf { native::db_file_path().with_file_name("data.key") }
+// Written before a re-key and renamed over data.key once it succeeded
+fn pending_key_file_path() -> PathBuf { native::db_file_path().with_file_name("data.key.next") }
+// Plaintext original kept while setup encrypts it, put back if setup fails
+fn plaintext_copy_path() -> PathBuf { native::db_file_path().with_extension("db.plaintext") }
+
+// Snapshots keep a copy of data.key next to them, so a backup folder copied elsewhere still opens
+pub(crate) fn copy_key_file(to: &Path) -> std::io::Result<()> { std::fs::copy(key_file_path(), to).map(|_| ()) }
+
+fn read_key_file_at(path: &Path) -> Option<KeyFile> {
+    std::fs::read_to_string(path).ok().and_then(|s| serde_json::from_str(&s).ok())
+}
+
+fn read_key_file() -> Option<KeyFile> { read_key_file_at(&key_file_path()) }
+
+fn write_key_file(path: &Path, kf: &KeyFile) -> Result<(), CipherError> {
+    let json = serde_json::to_string_pretty(kf).map_err(|e| CipherError::Io(e.to_string()))?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json).and_then(|_| std::fs::rename(&tmp, path)).map_err(|e| CipherError::Io(e.to_string()))
+}
+
+// True until a passphrase has been chosen and the database encrypted with it
+pub fn needs_setup() -> bool { read_key_file().is_none() || is_plaintext(&native::db_file_path()) }
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut buf = [0u8; N];
+    rand::rng().fill_bytes(&mut buf);
+    buf
+}
+
+fn new_params() -> KdfParams {
+    let defaults = Params::default();
+    KdfParams { salt: hex::encode(random::<SALT_LEN>()), m_cost: defaults.m_cost(), t_cost: defaults.t_cost(), p_cost: defaults.p_cost() }
+}
+
+fn derive_key(secret: &str, kdf: &KdfParams) -> Result<RawKey, CipherError> {
+    let salt = hex::decode(&kdf.salt).map_err(|e| CipherError::Kdf(e.to_string()))?;
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(KEY_LEN)).map_err(|e| CipherError::Kdf(e.to_string()))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(secret.as_bytes(), &salt, key.as_mut())
+        .map_err(|e| CipherError::Kdf(e.to_string()))?;
+    Ok(key)
+}
+
+// hex(nonce || ciphertext) of `key` sealed under `kek`
+fn wrap(kek: &[u8; KEY_LEN], key: &[u8; KEY_LEN]) -> String {
+    let nonce = random::<NONCE_LEN>();
+    let sealed = ChaCha20Poly1305::new(Key::from_slice(kek)).encrypt(Nonce::from_slice(&nonce), key.as_slice()).expect("in-memory encryption");
+    hex::encode([nonce.as_slice(), &sealed].concat())
+}
+
+// None when `kek` is not the key the value was wrapped with
+fn unwrap(kek: &[u8; KEY_LEN], wrapped: &str) -> Option<RawKey> {
+    let bytes = hex::decode(wrapped).ok()?;
+    if bytes.len() <= NONCE_LEN { return None; }
+    let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+    let plain = Zeroizing::new(ChaCha20Poly1305::new(Key::from_slice(kek)).decrypt(Nonce::from_slice(nonce), sealed).ok()?);
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    if plain.len() != KEY_LEN { return None; }
+    key.copy_from_slice(&plain);
+    Some(key)
+}
+
+// Data key for a passphrase; a wrong passphrase fails the unwrap
+fn data_key(passphrase: &str, kf: &KeyFile) -> Result<RawKey, CipherError> {
+    let kek = derive_key(passphrase, &kf.params)?;
+    unwrap(&kek, &kf.wrapped_key).ok_or(CipherError::WrongPassphrase)
+}
+
+fn new_key_file(passphrase: &str, key: &[u8; KEY_LEN], recovery: Option<Recovery>) -> Result<KeyFile, CipherError> {
+    let params = new_params();
+    let kek = derive_key(passphrase, &params)?;
+    Ok(KeyFile { kdf: "argon2id".into(), params, wrapped_key: wrap(&kek, key), recovery })
+}
+
+// Recovery codes are typed back by hand: ignore case, spaces and dashes
+fn normalize_code(code: &str) -> String {
+    code.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+// Unencrypted SQLite files start with a fixed header; SQLCipher files start with random salt
+fn is_plaintext(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)).is_ok() && &header == b"SQLite format 3\0"
+}
+
+fn db_err(e: rusqlite::Error) -> CipherError { CipherError::Db(e.to_string()) }
+
+// Row count of every user table, used to check the encrypted copy against the original
+fn table_counts(conn: &Connection) -> Result<Vec<(String, i64)>, CipherError> {
+    let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name").map_err(db_err)?;
+    let names = stmt.query_map([], |r| r.get::<_, String>(0)).map_err(db_err)?.collect::<Result<Vec<_>, _>>().map_err(db_err)?;
+    names.into_iter().map(|n| {
+        let c = conn.query_row(&format!("SELECT count(*) FROM \"{}\"", n.replace('"', "\"\"")), [], |r| r.get(0)).map_err(db_err)?;
+        Ok((n, c))
+    }).collect()
+}
+
+// Copy a plaintext database into an encrypted sibling with sqlcipher_export, check that
+// every table kept its row count, then swap the copy over the original (copied to `original` first)
+fn encrypt_in_place(path: &Path, key: &[u8], original: &Path) -> Result<(), CipherError> {
+    let tmp = path.with_extension("db.encrypting");
+    let _ = std::fs::remove_file(&tmp);
+    let expected = {
+        let plain = Connection::open(path).map_err(db_err)?;
+        let expected = table_counts(&plain)?;
+        let mut hex_key = Zeroizing::new(format!("x'{}'", hex::encode(key)));
+        let res = plain.execute("ATTACH DATABASE ?1 AS encrypted KEY ?2", rusqlite::params![tmp.to_string_lossy(), hex_key.as_str()]);
+        hex_key.clear();
+        res.map_err(db_err)?;
+        plain.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(())).map_err(db_err)?;
+        plain.execute_batch("DETACH DATABASE encrypted;").map_err(db_err)?;
+        expected
+    };
+    let copied = table_counts(&native::open_keyed(&tmp, key).map_err(db_err)?)?;
+    if copied != expected {
+        let _ = std::fs::remove_file(&tmp);
+        native::log_note(&format!("Encryption aborted, row counts differ: {:?} vs {:?}", expected, copied));
+        return Err(CipherError::Db("encrypted copy does not match the original database".into()));
+    }
+    std::fs::copy(path, original).map_err(|e| CipherError::Io(e.to_string()))?;
+    std::fs::rename(&tmp, path).map_err(|e| CipherError::Io(e.to_string()))?;
+    native::log_note(&format!("Encrypted existing database ({} tables)", expected.len()));
+    Ok(())
+}
+
+// First run: pick a data key, create the encrypted database (or encrypt the existing one) and unlock it
+pub fn setup(passphrase: &str) -> Result<(), CipherError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN { return Err(CipherError::TooShort); }
+    let key: RawKey = Zeroizing::new(random());
+    let kf = new_key_file(passphrase, &key, None)?;
+    let path = native::db_file_path();
+    let existed = path.exists();
+    let plaintext = is_plaintext(&path);
+    let _ = std::fs::remove_file(plaintext_copy_path());
+    // Key file goes first; if the migration is interrupted the database stays plaintext and setup runs again
+    write_key_file(&key_file_path(), &kf)?;
+    let res = if plaintext { encrypt_in_place(&path, key.as_ref(), &plaintext_copy_path()) } else { Ok(()) }
+        .and_then(|_| native::open_keyed(&path, key.as_ref()).map_err(db_err))
+        .and_then(|conn| native::install(conn, key.as_ref()).map_err(CipherError::Db));
+    if let Err(e) = res {
+        // Back to how setup found things: a key file that never matched the database (one already
+        // encrypted, or a failed migration) would fail every later unlock
+        let _ = std::fs::remove_file(key_file_path());
+        if plaintext && plaintext_copy_path().exists() {
+            let _ = std::fs::rename(plaintext_copy_path(), &path);
+        } else if !existed {
+            let _ = std::fs::remove_file(&path);
+        }
+        native::log_note(&format!("Setup failed: {}", e));
+        return Err(e);
+    }
+    let _ = std::fs::remove_file(plaintext_copy_path());
+    // key.bin from earlier builds held an unused random key in clear; drop it
+    let _ = std::fs::remove_file(native::db_file_path().with_file_name("key.bin"));
+    Ok(())
+}
+
+// Open and install the database with a data key; NotADatabase means the key does not match
+fn open_with(key: &[u8; KEY_LEN], wrong: CipherError) -> Result<(), CipherError> {
+    match native::open_keyed(&native::db_file_path(), key) {
+        Ok(conn) => native::install(conn, key).map_err(CipherError::Db),
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == rusqlite::ErrorCode::NotADatabase => Err(wrong),
+        Err(e) => {
+            native::log_note(&format!("Unlock failed: {}", e));
+            Err(CipherError::Db(e.to_string()))
+        }
+    }
+}
+
+// Later runs: unwrap the data key with the passphrase and open the existing database
+pub fn unlock(passphrase: &str) -> Result<(), CipherError> {
+    let kf = read_key_file().ok_or_else(|| CipherError::Io("data.key is missing".into()))?;
+    let res = data_key(passphrase, &kf).and_then(|key| open_with(&key, CipherError::WrongPassphrase));
+    // A setup interrupted after the swap leaves the plaintext original behind
+    if res.is_ok() { let _ = std::fs::remove_file(plaintext_copy_path()); }
+    if res != Err(CipherError::WrongPassphrase) { return res; }
+    // A re-key may have completed without data.key being replaced; try the pending key file
+    let pending = read_key_file_at(&pending_key_file_path()).ok_or(CipherError::WrongPassphrase)?;
+    data_key(passphrase, &pending).and_then(|key| open_with(&key, CipherError::WrongPassphrase))?;
+    std::fs::rename(pending_key_file_path(), key_file_path()).map_err(|e| CipherError::Io(e.to_string()))?;
+    native::log_note("Completed an interrupted re-key");
+    Ok(())
+}
+
+// Re-key the open database under a fresh data key wrapped for `passphrase`. The recovery
+// code (if any) is re-wrapped when `old_key` still unwraps its key.
+fn rotate(passphrase: &str, old_key: &[u8; KEY_LEN], recovery: Option<Recovery>) -> Result<(), CipherError> {
+    let key: RawKey = Zeroizing::new(random());
+    let recovery = recovery.and_then(|r| {
+        let rkek = unwrap(old_key, &r.wrapped_kek)?;
+        Some(Recovery { wrapped_key: wrap(&rkek, &key), wrapped_kek: wrap(&key, &rkek), kdf: r.kdf })
+    });
+    let kf = new_key_file(passphrase, &key, recovery)?;
+    write_key_file(&pending_key_file_path(), &kf)?;
+    if let Err(e) = native::rekey(key.as_ref()) {
+        let _ = std::fs::remove_file(pending_key_file_path());
+        return Err(db_err(e));
+    }
+    std::fs::rename(pending_key_file_path(), key_file_path()).map_err(|e| CipherError::Io(e.to_string()))?;
+    super::backups::rekey_all(old_key, key.as_ref());
+    Ok(())
+}
+
+// Configuration screen: check the current passphrase, then re-key under the new one
+pub fn change_passphrase(current: &str, new: &str) -> Result<(), CipherError> {
+    if new.chars().count() < MIN_PASSPHRASE_LEN { return Err(CipherError::TooShort); }
+    let kf = read_key_file().ok_or_else(|| CipherError::Io("data.key is missing".into()))?;
+    let old_key = data_key(current, &kf)?;
+    rotate(new, &old_key, kf.recovery)?;
+    native::log_note("Passphrase changed and database re-keyed");
+    Ok(())
+}
+
+// Create (or replace) the recovery code; returned once for the user to print or write down
+pub fn create_recovery_code(passphrase: &str) -> Result<String, CipherError> {
+    let mut kf = read_key_file().ok_or_else(|| CipherError::Io("data.key is missing".into()))?;
+    let key = data_key(passphrase, &kf)?;
+    let code = hex::encode_upper(random::<RECOVERY_LEN>());
+    let kdf = new_params();
+    let rkek = derive_key(&code, &kdf)?;
+    kf.recovery = Some(Recovery { wrapped_key: wrap(&rkek, &key), wrapped_kek: wrap(&key, &rkek), kdf });
+    write_key_file(&key_file_path(), &kf)?;
+    let groups: Vec<&str> = (0..code.len()).step_by(5).map(|i| &code[i..(i + 5).min(code.len())]).collect();
+    Ok(groups.join("-"))
+}
+
+pub fn has_recovery_code() -> bool { read_key_file().is_some_and(|kf| kf.recovery.is_some()) }
+
+// Unlock screen: open the database with the recovery code and set a new passphrase.
+// The code is used up; the database is re-keyed and a new code has to be created.
+pub fn recover(code: &str, new_passphrase: &str) -> Result<(), CipherError> {
+    if new_passphrase.chars().count() < MIN_PASSPHRASE_LEN { return Err(CipherError::TooShort); }
+    let kf = read_key_file().ok_or_else(|| CipherError::Io("data.key is missing".into()))?;
+    let r = kf.recovery.ok_or(CipherError::NoRecoveryCode)?;
+    let rkek = derive_key(&normalize_code(code), &r.kdf)?;
+    let key = unwrap(&rkek, &r.wrapped_key).ok_or(CipherError::WrongRecoveryCode)?;
+    open_with(&key, CipherError::WrongRecoveryCode)?;
+    rotate(new_passphrase, &key, None)?;
+    native::log_note("Database recovered with the recovery code");
+    Ok(())
+}
@@ -1,19 +1,11 @@
-#![cfg(feature = "native-db")]
+#![cfg(any(feature = "native-db", feature = "opfs-db"))]
 use crate::db::connection;
+use crate::db::store::{MergeBatch, Snapshot, Store, StoreError, StoreResult};
 use chrono::{NaiveDate, NaiveDateTime};
 use rusqlite::{params, Result, Row};
-use serde::{Deserialize, Serialize};
 use serde_json;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Publisher {
-    pub id: i64,
-    pub first_name: String,
-    pub last_name: String,
-    pub gender: String,
-    pub is_shift_manager: bool,
-    pub priority: i64,
-}
+pub use crate::db::store::{Absence, Blackout, Configuration, GenerationBatch, Publisher, RelationshipKind, Schedule, Shift};
 
 impl Publisher {
     fn from_row(row: &Row) -> Result<Self> {
@@ -44,6 +36,21 @@ pub fn create_publisher(first: &str, last: &str, gender: &str, is_shift_manager:
     Ok(conn.last_insert_rowid())
 }
 
+pub fn create_publishers(rows: &[Publisher]) -> Result<Vec<i64>> {
+    let conn = connection();
+    let tx = conn.unchecked_transaction()?;
+    let mut ids = Vec::with_capacity(rows.len());
+    {
+        let mut stmt = tx.prepare("INSERT INTO Publishers (first_name, last_name, gender, is_shift_manager, priority) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for p in rows {
+            stmt.execute(params![p.first_name, p.last_name, p.gender, if p.is_shift_manager {1} else {0}, p.priority])?;
+            ids.push(tx.last_insert_rowid());
+        }
+    }
+    tx.commit()?;
+    Ok(ids)
+}
+
 pub fn update_publisher(id: i64, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> Result<()> {
     let conn = connection();
     conn.execute(
@@ -66,20 +73,10 @@ pub fn cleanup_expired_absences(today: NaiveDate) -> Result<usize> {
 }
 
 // ================= Configuration =================
-#[derive(Debug, Clone)]
-pub struct Configuration {
-    pub congregation_name: String,
-    pub theme: String,
-    pub name_order: String, // 'first_last' or 'last_first'
-    pub week_start: String, // 'monday' or 'sunday'
-    pub language: String,   // 'system' | 'en' | 'es' | 'fr' | 'de'
-    pub date_format: String, // 'YYYY-MM-DD' | 'DD/MM/YYYY' | 'MM/DD/YYYY' | 'DD MMM YYYY'
-}
-
 pub fn get_configuration() -> Result<Configuration> {
     let conn = connection();
     conn.query_row(
-    "SELECT congregation_name, theme, name_order, week_start, language, date_format FROM Configuration WHERE id = 1",
+    "SELECT congregation_name, theme, name_order, week_start, language, date_format, generator_weights FROM Configuration WHERE id = 1",
         [],
         |r| {
             Ok(Configuration {
@@ -89,44 +86,22 @@ pub fn get_configuration() -> Result<Configuration> {
                 week_start: r.get(3).unwrap_or_else(|_| "monday".to_string()),
                 language: r.get(4).unwrap_or_else(|_| "system".to_string()),
                 date_format: r.get(5).unwrap_or_else(|_| "YYYY-MM-DD".to_string()),
+                weights: r.get::<_, Option<String>>(6)?.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
             })
         },
     )
 }
 
-pub fn update_configuration(name: &str, theme: &str, name_order: &str, week_start: &str, language: &str, date_format: &str) -> Result<()> {
+pub fn update_configuration(cfg: &Configuration) -> Result<()> {
     let conn = connection();
     conn.execute(
-        "UPDATE Configuration SET congregation_name=?1, theme=?2, name_order=?3, week_start=?4, language=?5, date_format=?6 WHERE id=1",
-        params![name, theme, name_order, week_start, language, date_format],
+        "UPDATE Configuration SET congregation_name=?1, theme=?2, name_order=?3, week_start=?4, language=?5, date_format=?6, generator_weights=?7 WHERE id=1",
+        params![cfg.congregation_name, cfg.theme, cfg.name_order, cfg.week_start, cfg.language, cfg.date_format, serde_json::to_string(&cfg.weights).ok()],
     )?;
     Ok(())
 }
 
-#[allow(dead_code)]
-pub fn configuration_is_set() -> bool {
-    if let Ok(cfg) = get_configuration() {
-        let name_ok = !cfg.congregation_name.trim().is_empty() && cfg.congregation_name != "Congregation";
-        let theme_ok = !cfg.theme.trim().is_empty() && cfg.theme != "System";
-        name_ok && theme_ok
-    } else { false }
-}
-
 // ================= Schedules =================
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Schedule {
-    pub id: i64,
-    pub location: String,
-    pub start_hour: String,
-    pub end_hour: String,
-    pub weekday: String,
-    pub description: Option<String>,
-    pub num_publishers: i64,
-    pub num_shift_managers: i64,
-    pub num_brothers: i64,
-    pub num_sisters: i64,
-}
-
 impl Schedule {
     fn from_row(r: &Row) -> Result<Self> {
         Ok(Self {
@@ -140,13 +115,22 @@ impl Schedule {
             num_shift_managers: r.get(7)?,
             num_brothers: r.get(8)?,
             num_sisters: r.get(9)?,
+            recurrence: match r.get::<_, Option<String>>(10)? {
+                Some(json) => serde_json::from_str(&json).map_err(|e| rusqlite::Error::FromSqlConversionFailure(10, rusqlite::types::Type::Text, Box::new(e)))?,
+                None => Default::default(),
+            },
         })
     }
+
+    // NULL for the weekly default, so rows written before rules existed and new weekly ones look the same
+    fn recurrence_json(&self) -> Option<String> {
+        if self.recurrence.is_weekly() { None } else { serde_json::to_string(&self.recurrence).ok() }
+    }
 }
 
 pub fn list_schedules() -> Result<Vec<Schedule>> {
     let conn = connection();
-    let mut stmt = conn.prepare("SELECT id, location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters FROM Schedules ORDER BY weekday, start_hour")?;
+    let mut stmt = conn.prepare("SELECT id, location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters, recurrence FROM Schedules ORDER BY weekday, start_hour")?;
     let rows = stmt.query_map([], |r| Schedule::from_row(r))?;
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
@@ -154,16 +138,16 @@ pub fn list_schedules() -> Result<Vec<Schedule>> {
 pub fn create_schedule(s: &Schedule) -> Result<i64> {
     validate_schedule_counts(s)?;
     let conn = connection();
-    conn.execute("INSERT INTO Schedules (location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters])?;
+    conn.execute("INSERT INTO Schedules (location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters, recurrence) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters, s.recurrence_json()])?;
     Ok(conn.last_insert_rowid())
 }
 
 pub fn update_schedule(s: &Schedule) -> Result<()> {
     validate_schedule_counts(s)?;
     let conn = connection();
-    conn.execute("UPDATE Schedules SET location=?1, start_hour=?2, end_hour=?3, weekday=?4, description=?5, num_publishers=?6, num_shift_managers=?7, num_brothers=?8, num_sisters=?9 WHERE id=?10",
-        params![s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters, s.id])?;
+    conn.execute("UPDATE Schedules SET location=?1, start_hour=?2, end_hour=?3, weekday=?4, description=?5, num_publishers=?6, num_shift_managers=?7, num_brothers=?8, num_sisters=?9, recurrence=?10 WHERE id=?11",
+        params![s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters, s.recurrence_json(), s.id])?;
     Ok(())
 }
 
@@ -174,16 +158,10 @@ pub fn delete_schedule(id: i64) -> Result<()> {
 }
 
 fn validate_schedule_counts(s: &Schedule) -> Result<()> {
-    if s.num_shift_managers + s.num_brothers + s.num_sisters > s.num_publishers { 
-        return Err(rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Integer, Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "slot counts exceed total publishers"))));
-    }
-    Ok(())
+    s.validate().map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Integer, Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))))
 }
 
 // ================= Absences =================
-#[derive(Debug, Clone)]
-pub struct Absence { pub id: i64, pub publisher_id: i64, pub start_date: NaiveDate, pub end_date: NaiveDate, pub description: Option<String> }
-
 impl Absence { fn from_row(r: &Row) -> Result<Self> { Ok(Self { id: r.get(0)?, publisher_id: r.get(1)?, start_date: NaiveDate::parse_from_str(&r.get::<_, String>(2)?, "%Y-%m-%d").unwrap(), end_date: NaiveDate::parse_from_str(&r.get::<_, String>(3)?, "%Y-%m-%d").unwrap(), description: r.get(4)? }) } }
 
 pub fn list_future_absences(today: NaiveDate) -> Result<Vec<Absence>> {
@@ -214,28 +192,51 @@ pub fn is_absent_on(publisher_id: i64, day: NaiveDate) -> Result<bool> {
     Ok(count > 0)
 }
 
-// ================= Shifts =================
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Shift {
-    pub id: i64,
-    pub start: NaiveDateTime,
-    pub end: NaiveDateTime,
-    pub location: String,
-    pub publishers: Vec<i64>,
-    pub warning: Option<String>,
+// ================= Blackouts =================
+impl Blackout { fn from_row(r: &Row) -> Result<Self> { Ok(Self { id: r.get(0)?, start_date: r.get(1)?, end_date: r.get(2)?, location: r.get(3)?, reason: r.get(4)? }) } }
+
+pub fn list_blackouts() -> Result<Vec<Blackout>> {
+    let conn = connection();
+    let mut stmt = conn.prepare("SELECT id, start_date, end_date, location, reason FROM Blackouts ORDER BY start_date, end_date")?;
+    let rows = stmt.query_map([], Blackout::from_row)?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+pub fn create_blackouts(rows: &[Blackout]) -> Result<Vec<i64>> {
+    let conn = connection();
+    let tx = conn.unchecked_transaction()?;
+    let mut ids = Vec::with_capacity(rows.len());
+    {
+        let mut stmt = tx.prepare("INSERT INTO Blackouts (start_date, end_date, location, reason) VALUES (?1, ?2, ?3, ?4)")?;
+        for b in rows {
+            stmt.execute(params![b.start_date, b.end_date, b.location, b.reason])?;
+            ids.push(tx.last_insert_rowid());
+        }
+    }
+    tx.commit()?;
+    Ok(ids)
+}
+
+pub fn update_blackout(b: &Blackout) -> Result<()> {
+    let conn = connection();
+    conn.execute("UPDATE Blackouts SET start_date=?1, end_date=?2, location=?3, reason=?4 WHERE id=?5", params![b.start_date, b.end_date, b.location, b.reason, b.id])?;
+    Ok(())
 }
 
+pub fn delete_blackout(id: i64) -> Result<()> { let conn = connection(); conn.execute("DELETE FROM Blackouts WHERE id=?1", params![id])?; Ok(()) }
+
+// ================= Shifts =================
 impl Shift {
     fn from_row(r: &Row) -> Result<Self> {
         let publishers_json: String = r.get(4)?;
         let publishers: Vec<i64> = serde_json::from_str(&publishers_json).unwrap_or_default();
-        Ok(Self { id: r.get(0)?, start: NaiveDateTime::parse_from_str(&r.get::<_, String>(1)?, "%Y-%m-%d %H:%M:%S").unwrap(), end: NaiveDateTime::parse_from_str(&r.get::<_, String>(2)?, "%Y-%m-%d %H:%M:%S").unwrap(), location: r.get(3)?, publishers, warning: r.get(5)? })
+        Ok(Self { id: r.get(0)?, start: NaiveDateTime::parse_from_str(&r.get::<_, String>(1)?, "%Y-%m-%d %H:%M:%S").unwrap(), end: NaiveDateTime::parse_from_str(&r.get::<_, String>(2)?, "%Y-%m-%d %H:%M:%S").unwrap(), location: r.get(3)?, publishers, warning: r.get(5)?, batch_id: r.get(6)?, edited: r.get(7)? })
     }
 }
 
 pub fn list_shifts_between(start: NaiveDateTime, end: NaiveDateTime) -> Result<Vec<Shift>> {
     let conn = connection();
-    let mut stmt = conn.prepare("SELECT id, start_datetime, end_datetime, location, publishers, warning FROM Shifts WHERE start_datetime >= ?1 AND end_datetime <= ?2 ORDER BY start_datetime")?;
+    let mut stmt = conn.prepare("SELECT id, start_datetime, end_datetime, location, publishers, warning, batch_id, edited FROM Shifts WHERE start_datetime >= ?1 AND end_datetime <= ?2 ORDER BY start_datetime")?;
     let rows = stmt.query_map(params![start.format("%Y-%m-%d %H:%M:%S").to_string(), end.format("%Y-%m-%d %H:%M:%S").to_string()], |r| Shift::from_row(r))?;
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
@@ -250,7 +251,8 @@ pub fn create_shift(start: NaiveDateTime, end: NaiveDateTime, location: &str, pu
 pub fn update_shift_publishers(id: i64, publishers: &[i64], warning: Option<&str>) -> Result<()> {
     let conn = connection();
     let pubs_json = serde_json::to_string(publishers).unwrap();
-    conn.execute("UPDATE Shifts SET publishers=?1, warning=?2 WHERE id=?3", params![pubs_json, warning, id])?;
+    // Only a real change marks the shift edited (SET expressions see the row as it was)
+    conn.execute("UPDATE Shifts SET edited = (edited OR publishers <> ?1), publishers=?1, warning=?2 WHERE id=?3", params![pubs_json, warning, id])?;
     Ok(())
 }
 
@@ -258,7 +260,7 @@ pub fn update_shift_publishers(id: i64, publishers: &[i64], warning: Option<&str
 pub fn update_shift_datetime(id: i64, start: NaiveDateTime, end: NaiveDateTime, warning: Option<&str>) -> Result<()> {
     let conn = connection();
     conn.execute(
-        "UPDATE Shifts SET start_datetime=?1, end_datetime=?2, warning=?3 WHERE id=?4",
+        "UPDATE Shifts SET edited = (edited OR start_datetime <> ?1 OR end_datetime <> ?2), start_datetime=?1, end_datetime=?2, warning=?3 WHERE id=?4",
         params![
             start.format("%Y-%m-%d %H:%M:%S").to_string(),
             end.format("%Y-%m-%d %H:%M:%S").to_string(),
@@ -272,7 +274,7 @@ pub fn update_shift_datetime(id: i64, start: NaiveDateTime, end: NaiveDateTime,
 pub fn update_shift_datetime_location(id: i64, start: NaiveDateTime, end: NaiveDateTime, location: &str, warning: Option<&str>) -> Result<()> {
     let conn = connection();
     conn.execute(
-        "UPDATE Shifts SET start_datetime=?1, end_datetime=?2, location=?3, warning=?4 WHERE id=?5",
+        "UPDATE Shifts SET edited = (edited OR start_datetime <> ?1 OR end_datetime <> ?2 OR location <> ?3), start_datetime=?1, end_datetime=?2, location=?3, warning=?4 WHERE id=?5",
         params![
             start.format("%Y-%m-%d %H:%M:%S").to_string(),
             end.format("%Y-%m-%d %H:%M:%S").to_string(),
@@ -289,6 +291,62 @@ pub fn delete_shift(id: i64) -> Result<()> { let conn = connection(); conn.execu
 #[allow(dead_code)]
 pub fn delete_shifts_in_range(start: NaiveDateTime, end: NaiveDateTime) -> Result<usize> { let conn = connection(); let n = conn.execute("DELETE FROM Shifts WHERE start_datetime >= ?1 AND end_datetime <= ?2", params![start.format("%Y-%m-%d %H:%M:%S").to_string(), end.format("%Y-%m-%d %H:%M:%S").to_string()])?; Ok(n) }
 
+// ================= Generation batches =================
+impl GenerationBatch {
+    fn from_row(r: &Row) -> Result<Self> {
+        let weights: String = r.get(6)?;
+        Ok(Self {
+            id: r.get(0)?,
+            created_at: NaiveDateTime::parse_from_str(&r.get::<_, String>(1)?, "%Y-%m-%d %H:%M:%S").unwrap_or_default(),
+            start: r.get(2)?,
+            end: r.get(3)?,
+            seed: r.get::<_, i64>(4)? as u64,
+            mode: r.get(5)?,
+            weights: serde_json::from_str(&weights).unwrap_or_default(),
+        })
+    }
+}
+
+pub fn list_generation_batches() -> Result<Vec<GenerationBatch>> {
+    let conn = connection();
+    let mut stmt = conn.prepare("SELECT id, created_at, start_date, end_date, seed, mode, weights FROM GenerationBatches ORDER BY id DESC")?;
+    let rows = stmt.query_map([], GenerationBatch::from_row)?;
+    rows.collect()
+}
+
+pub fn save_generation(batch: &GenerationBatch, rows: &[Shift]) -> Result<i64> {
+    let conn = connection();
+    let tx = conn.unchecked_transaction()?;
+    let id = if batch.id > 0 {
+        batch.id
+    } else {
+        tx.execute(
+            "INSERT INTO GenerationBatches (created_at, start_date, end_date, seed, mode, weights) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![batch.created_at.format("%Y-%m-%d %H:%M:%S").to_string(), batch.start, batch.end, batch.seed as i64, batch.mode, serde_json::to_string(&batch.weights).unwrap()],
+        )?;
+        tx.last_insert_rowid()
+    };
+    {
+        let mut stmt = tx.prepare("INSERT INTO Shifts (start_datetime, end_datetime, location, publishers, warning, batch_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+        for s in rows {
+            let pubs_json = serde_json::to_string(&s.publishers).unwrap();
+            stmt.execute(params![s.start.format("%Y-%m-%d %H:%M:%S").to_string(), s.end.format("%Y-%m-%d %H:%M:%S").to_string(), s.location, pubs_json, s.warning, id])?;
+        }
+    }
+    tx.commit()?;
+    Ok(id)
+}
+
+pub fn revert_generation(id: i64) -> Result<(usize, usize)> {
+    let conn = connection();
+    let tx = conn.unchecked_transaction()?;
+    let removed = tx.execute("DELETE FROM Shifts WHERE batch_id=?1 AND edited=0", params![id])?;
+    let kept = tx.execute("UPDATE Shifts SET batch_id=NULL WHERE batch_id=?1", params![id])?;
+    tx.execute("DELETE FROM GenerationBatches WHERE id=?1", params![id])?;
+    tx.commit()?;
+    Ok((removed, kept))
+}
+
 // ================= Availability =================
 pub fn set_publisher_availability(publisher_id: i64, schedule_ids: &[i64]) -> Result<()> {
     let conn = connection();
@@ -317,14 +375,6 @@ pub fn list_publishers_for_schedule(schedule_id: i64) -> Result<Vec<i64>> {
 }
 
 // ================= Relationships =================
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum RelationshipKind { Mandatory, Recommended }
-
-impl RelationshipKind {
-    fn as_str(&self) -> &'static str { match self { RelationshipKind::Mandatory => "mandatory", RelationshipKind::Recommended => "recommended" } }
-    fn from_db(s: String) -> Self { match s.as_str() { "mandatory" => RelationshipKind::Mandatory, _ => RelationshipKind::Recommended } }
-}
-
 pub fn add_relationship(a: i64, b: i64, kind: RelationshipKind) -> Result<()> {
     if a==b { return Ok(()); }
     let (x,y) = if a<b {(a,b)} else {(b,a)};
@@ -339,124 +389,176 @@ pub fn remove_relationship(a: i64, b: i64) -> Result<()> { if a==b { return Ok((
 pub fn list_relationships_for_publisher(p: i64) -> Result<Vec<(i64, RelationshipKind)>> {
     let conn = connection();
     let mut stmt = conn.prepare("SELECT CASE WHEN publisher_a_id = ?1 THEN publisher_b_id ELSE publisher_a_id END AS other, kind FROM Relationships WHERE publisher_a_id = ?1 OR publisher_b_id = ?1")?;
-    let rows = stmt.query_map(params![p], |r| Ok((r.get(0)?, RelationshipKind::from_db(r.get::<_, String>(1)?))))?;
+    let rows = stmt.query_map(params![p], |r| Ok((r.get(0)?, RelationshipKind::from_db(&r.get::<_, String>(1)?))))?;
     Ok(rows.filter_map(|r| r.ok()).collect())
 }
 
 // ================= Export/Import (excluding Configuration) =================
-#[derive(Serialize, Deserialize)]
-pub struct AbsenceExport { pub id: i64, pub publisher_id: i64, pub start_date: String, pub end_date: String, pub description: Option<String> }
-
-#[derive(Serialize, Deserialize)]
-pub struct AvailabilityExport(pub i64, pub i64);
-
-#[derive(Serialize, Deserialize)]
-pub struct RelationshipExport(pub i64, pub i64, pub String);
-
-#[derive(Serialize, Deserialize)]
-pub struct ExportPayload {
-    pub publishers: Vec<Publisher>,
-    pub schedules: Vec<Schedule>,
-    pub absences: Vec<AbsenceExport>,
-    pub shifts: Vec<Shift>,
-    pub availability: Vec<AvailabilityExport>,
-    pub relationships: Vec<RelationshipExport>,
-}
-
-pub fn export_data() -> Result<String> {
+pub fn snapshot() -> Result<Snapshot> {
     let conn = connection();
-    // publishers
     let publishers = {
         let mut stmt = conn.prepare("SELECT id, first_name, last_name, gender, is_shift_manager, priority FROM Publishers ORDER BY id")?;
-        let rows = stmt.query_map([], |r| Publisher::from_row(r))?;
-        rows.filter_map(|r| r.ok()).collect::<Vec<_>>()
-    };
-    // schedules
-    let schedules = list_schedules()?;
-    // absences (all)
-    let absences = {
-        let mut stmt = conn.prepare("SELECT id, publisher_id, start_date, end_date, description FROM Absences ORDER BY id")?;
-        let rows = stmt.query_map([], |r| Ok(AbsenceExport { id: r.get(0)?, publisher_id: r.get(1)?, start_date: r.get::<_, String>(2)?, end_date: r.get::<_, String>(3)?, description: r.get(4)? }))?;
-        rows.filter_map(|r| r.ok()).collect::<Vec<_>>()
+        let rows = stmt.query_map([], Publisher::from_row)?;
+        rows.collect::<Result<Vec<_>>>()?
     };
-    // shifts (all)
-    let shifts = {
-        let mut stmt = conn.prepare("SELECT id, start_datetime, end_datetime, location, publishers, warning FROM Shifts ORDER BY id")?;
-        let rows = stmt.query_map([], |r| Shift::from_row(r))?;
-        rows.filter_map(|r| r.ok()).collect::<Vec<_>>()
+    let schedules = {
+        let mut stmt = conn.prepare("SELECT id, location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters, recurrence FROM Schedules ORDER BY id")?;
+        let rows = stmt.query_map([], Schedule::from_row)?;
+        rows.collect::<Result<Vec<_>>>()?
     };
-    // availability
     let availability = {
         let mut stmt = conn.prepare("SELECT publisher_id, schedule_id FROM Availability ORDER BY publisher_id, schedule_id")?;
-        let rows = stmt.query_map([], |r| Ok(AvailabilityExport(r.get(0)?, r.get(1)?)))?;
-        rows.filter_map(|r| r.ok()).collect::<Vec<_>>()
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+        rows.collect::<Result<Vec<_>>>()?
     };
-    // relationships
     let relationships = {
         let mut stmt = conn.prepare("SELECT publisher_a_id, publisher_b_id, kind FROM Relationships ORDER BY publisher_a_id, publisher_b_id")?;
-        let rows = stmt.query_map([], |r| Ok(RelationshipExport(r.get(0)?, r.get(1)?, r.get(2)?)))?;
-        rows.filter_map(|r| r.ok()).collect::<Vec<_>>()
+        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, RelationshipKind::from_db(&r.get::<_, String>(2)?))))?;
+        rows.collect::<Result<Vec<_>>>()?
     };
-
-    let payload = ExportPayload { publishers, schedules, absences, shifts, availability, relationships };
-    Ok(serde_json::to_string_pretty(&payload).unwrap())
+    let absences = {
+        let mut stmt = conn.prepare("SELECT id, publisher_id, start_date, end_date, description FROM Absences ORDER BY id")?;
+        let rows = stmt.query_map([], Absence::from_row)?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    let shifts = {
+        let mut stmt = conn.prepare("SELECT id, start_datetime, end_datetime, location, publishers, warning, batch_id, edited FROM Shifts ORDER BY id")?;
+        let rows = stmt.query_map([], Shift::from_row)?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    let blackouts = {
+        let mut stmt = conn.prepare("SELECT id, start_date, end_date, location, reason FROM Blackouts ORDER BY id")?;
+        let rows = stmt.query_map([], Blackout::from_row)?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    let generation_batches = {
+        let mut stmt = conn.prepare("SELECT id, created_at, start_date, end_date, seed, mode, weights FROM GenerationBatches ORDER BY id")?;
+        let rows = stmt.query_map([], GenerationBatch::from_row)?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    Ok(Snapshot { publishers, schedules, availability, relationships, absences, shifts, blackouts, generation_batches })
 }
 
-pub fn import_data(json: &str) -> Result<()> {
-    let payload: ExportPayload = serde_json::from_str(json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+// Destructive: replace every row with the snapshot, keeping its ids (checked by db::envelope beforehand)
+pub fn restore(data: &Snapshot) -> Result<()> {
     let conn = connection();
     let tx = conn.unchecked_transaction()?;
     // Clear all (respect FK constraints)
     tx.execute("DELETE FROM Availability", [])?;
     tx.execute("DELETE FROM Relationships", [])?;
     tx.execute("DELETE FROM Shifts", [])?;
+    tx.execute("DELETE FROM GenerationBatches", [])?;
     tx.execute("DELETE FROM Absences", [])?;
     tx.execute("DELETE FROM Schedules", [])?;
     tx.execute("DELETE FROM Publishers", [])?;
+    tx.execute("DELETE FROM Blackouts", [])?;
     // Publishers
     {
         let mut stmt = tx.prepare("INSERT INTO Publishers (id, first_name, last_name, gender, is_shift_manager, priority) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
-        for p in &payload.publishers {
+        for p in &data.publishers {
             stmt.execute(params![p.id, p.first_name, p.last_name, p.gender, if p.is_shift_manager {1} else {0}, p.priority])?;
         }
     }
     // Schedules
     {
-        let mut stmt = tx.prepare("INSERT INTO Schedules (id, location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?;
-        for s in &payload.schedules {
-            stmt.execute(params![s.id, s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters])?;
+        let mut stmt = tx.prepare("INSERT INTO Schedules (id, location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters, recurrence) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)")?;
+        for s in &data.schedules {
+            stmt.execute(params![s.id, s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters, s.recurrence_json()])?;
         }
     }
     // Absences
     {
         let mut stmt = tx.prepare("INSERT INTO Absences (id, publisher_id, start_date, end_date, description) VALUES (?1, ?2, ?3, ?4, ?5)")?;
-        for a in &payload.absences {
-            stmt.execute(params![a.id, a.publisher_id, a.start_date, a.end_date, a.description])?;
+        for a in &data.absences {
+            stmt.execute(params![a.id, a.publisher_id, a.start_date.to_string(), a.end_date.to_string(), a.description])?;
+        }
+    }
+    // Generation batches, before the shifts that point at them
+    {
+        let mut stmt = tx.prepare("INSERT INTO GenerationBatches (id, created_at, start_date, end_date, seed, mode, weights) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+        for b in &data.generation_batches {
+            stmt.execute(params![b.id, b.created_at.format("%Y-%m-%d %H:%M:%S").to_string(), b.start, b.end, b.seed as i64, b.mode, serde_json::to_string(&b.weights).unwrap()])?;
         }
     }
     // Shifts
     {
-        let mut stmt = tx.prepare("INSERT INTO Shifts (id, start_datetime, end_datetime, location, publishers, warning) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
-        for sh in &payload.shifts {
+        let mut stmt = tx.prepare("INSERT INTO Shifts (id, start_datetime, end_datetime, location, publishers, warning, batch_id, edited) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")?;
+        for sh in &data.shifts {
             let pubs_json = serde_json::to_string(&sh.publishers).unwrap_or_else(|_| "[]".to_string());
-            stmt.execute(params![sh.id, sh.start.format("%Y-%m-%d %H:%M:%S").to_string(), sh.end.format("%Y-%m-%d %H:%M:%S").to_string(), sh.location, pubs_json, sh.warning])?;
+            stmt.execute(params![sh.id, sh.start.format("%Y-%m-%d %H:%M:%S").to_string(), sh.end.format("%Y-%m-%d %H:%M:%S").to_string(), sh.location, pubs_json, sh.warning, sh.batch_id, sh.edited])?;
         }
     }
+    // Blackouts
+    {
+        let mut stmt = tx.prepare("INSERT INTO Blackouts (id, start_date, end_date, location, reason) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for b in &data.blackouts { stmt.execute(params![b.id, b.start_date, b.end_date, b.location, b.reason])?; }
+    }
     // Availability
     {
         let mut stmt = tx.prepare("INSERT INTO Availability (publisher_id, schedule_id) VALUES (?1, ?2)")?;
-        for AvailabilityExport(p, s) in &payload.availability { stmt.execute(params![p, s])?; }
+        for (p, s) in &data.availability { stmt.execute(params![p, s])?; }
     }
-    // Relationships
+    // Relationships (stored with the smaller id first)
     {
         let mut stmt = tx.prepare("INSERT INTO Relationships (publisher_a_id, publisher_b_id, kind) VALUES (?1, ?2, ?3)")?;
-        for RelationshipExport(a, b, k) in &payload.relationships { stmt.execute(params![a, b, k])?; }
+        for (a, b, k) in &data.relationships { stmt.execute(params![a.min(b), a.max(b), k.as_str()])?; }
     }
 
     tx.commit()?;
     Ok(())
 }
 
+// Non-destructive import: add a merge batch in one transaction (existing rows are kept as they are)
+pub fn merge_data(batch: &MergeBatch) -> Result<()> {
+    let conn = connection();
+    let tx = conn.unchecked_transaction()?;
+    let mut pids = Vec::with_capacity(batch.publishers.len());
+    {
+        let mut stmt = tx.prepare("INSERT INTO Publishers (first_name, last_name, gender, is_shift_manager, priority) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for p in &batch.publishers {
+            stmt.execute(params![p.first_name, p.last_name, p.gender, if p.is_shift_manager {1} else {0}, p.priority])?;
+            pids.push(tx.last_insert_rowid());
+        }
+    }
+    let mut sids = Vec::with_capacity(batch.schedules.len());
+    {
+        let mut stmt = tx.prepare("INSERT INTO Schedules (location, start_hour, end_hour, weekday, description, num_publishers, num_shift_managers, num_brothers, num_sisters, recurrence) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?;
+        for s in &batch.schedules {
+            stmt.execute(params![s.location, s.start_hour, s.end_hour, s.weekday, s.description, s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters, s.recurrence_json()])?;
+            sids.push(tx.last_insert_rowid());
+        }
+    }
+    {
+        let mut stmt = tx.prepare("INSERT OR IGNORE INTO Availability (publisher_id, schedule_id) VALUES (?1, ?2)")?;
+        for (p, s) in &batch.availability { stmt.execute(params![p.resolve(&pids), s.resolve(&sids)])?; }
+    }
+    {
+        let mut stmt = tx.prepare("INSERT OR IGNORE INTO Relationships (publisher_a_id, publisher_b_id, kind) VALUES (?1, ?2, ?3)")?;
+        for (a, b, kind) in &batch.relationships {
+            let (a, b) = (a.resolve(&pids), b.resolve(&pids));
+            if a != b { stmt.execute(params![a.min(b), a.max(b), kind.as_str()])?; }
+        }
+    }
+    {
+        let mut stmt = tx.prepare("INSERT INTO Absences (publisher_id, start_date, end_date, description) VALUES (?1, ?2, ?3, ?4)")?;
+        for a in &batch.absences { stmt.execute(params![a.publisher.resolve(&pids), a.start_date.to_string(), a.end_date.to_string(), a.description])?; }
+    }
+    {
+        let mut stmt = tx.prepare("INSERT INTO Shifts (start_datetime, end_datetime, location, publishers, warning) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for sh in &batch.shifts {
+            let pubs: Vec<i64> = sh.publishers.iter().map(|r| r.resolve(&pids)).collect();
+            let pubs_json = serde_json::to_string(&pubs).unwrap_or_else(|_| "[]".to_string());
+            stmt.execute(params![sh.start.format("%Y-%m-%d %H:%M:%S").to_string(), sh.end.format("%Y-%m-%d %H:%M:%S").to_string(), sh.location, pubs_json, sh.warning])?;
+        }
+    }
+    {
+        let mut stmt = tx.prepare("INSERT INTO Blackouts (start_date, end_date, location, reason) VALUES (?1, ?2, ?3, ?4)")?;
+        for b in &batch.blackouts { stmt.execute(params![b.start_date, b.end_date, b.location, b.reason])?; }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
 // Destructive: remove all data from database (keeps Configuration row)
 pub fn reset_data() -> Result<()> {
     let conn = connection();
@@ -464,12 +566,88 @@ pub fn reset_data() -> Result<()> {
     tx.execute("DELETE FROM Availability", [])?;
     tx.execute("DELETE FROM Relationships", [])?;
     tx.execute("DELETE FROM Shifts", [])?;
+    tx.execute("DELETE FROM GenerationBatches", [])?;
     tx.execute("DELETE FROM Absences", [])?;
     tx.execute("DELETE FROM Schedules", [])?;
     tx.execute("DELETE FROM Publishers", [])?;
+    tx.execute("DELETE FROM Blackouts", [])?;
     // Reset configuration to defaults/unset so landing page shows
-    tx.execute("UPDATE Configuration SET congregation_name='Congregation', theme='System', name_order='first_last', week_start='monday', language='system', date_format='YYYY-MM-DD' WHERE id=1", [])?;
+    tx.execute("UPDATE Configuration SET congregation_name='Congregation', theme='System', name_order='first_last', week_start='monday', language='system', date_format='YYYY-MM-DD', generator_weights=NULL WHERE id=1", [])?;
     tx.commit()?;
     Ok(())
 }
 
+
+// ================= Store implementation =================
+pub struct SqliteStore;
+
+impl Store for SqliteStore {
+    fn get_configuration(&self) -> StoreResult<Configuration> { Ok(get_configuration()?) }
+    fn update_configuration(&self, cfg: &Configuration) -> StoreResult<()> { Ok(update_configuration(cfg)?) }
+
+    fn list_publishers(&self) -> StoreResult<Vec<Publisher>> { Ok(list_publishers()?) }
+    fn create_publisher(&self, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<i64> { Ok(create_publisher(first, last, gender, is_shift_manager, priority)?) }
+    fn create_publishers(&self, rows: &[Publisher]) -> StoreResult<Vec<i64>> { Ok(create_publishers(rows)?) }
+    fn update_publisher(&self, id: i64, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<()> { Ok(update_publisher(id, first, last, gender, is_shift_manager, priority)?) }
+    fn delete_publisher(&self, id: i64) -> StoreResult<()> { Ok(delete_publisher(id)?) }
+
+    fn list_schedules(&self) -> StoreResult<Vec<Schedule>> { Ok(list_schedules()?) }
+    fn create_schedule(&self, s: &Schedule) -> StoreResult<i64> { s.validate()?; Ok(create_schedule(s)?) }
+    fn update_schedule(&self, s: &Schedule) -> StoreResult<()> { s.validate()?; Ok(update_schedule(s)?) }
+    fn delete_schedule(&self, id: i64) -> StoreResult<()> { Ok(delete_schedule(id)?) }
+
+    fn set_publisher_availability(&self, publisher_id: i64, schedule_ids: &[i64]) -> StoreResult<()> { Ok(set_publisher_availability(publisher_id, schedule_ids)?) }
+    fn list_availability_for_publisher(&self, publisher_id: i64) -> StoreResult<Vec<i64>> { Ok(list_availability_for_publisher(publisher_id)?) }
+    fn list_publishers_for_schedule(&self, schedule_id: i64) -> StoreResult<Vec<i64>> { Ok(list_publishers_for_schedule(schedule_id)?) }
+
+    fn add_relationship(&self, a: i64, b: i64, kind: RelationshipKind) -> StoreResult<()> {
+        if a == b { return Err(StoreError::Invalid("a publisher cannot be related to themselves".into())); }
+        Ok(add_relationship(a, b, kind)?)
+    }
+    fn remove_relationship(&self, a: i64, b: i64) -> StoreResult<()> { Ok(remove_relationship(a, b)?) }
+    fn list_relationships_for_publisher(&self, p: i64) -> StoreResult<Vec<(i64, RelationshipKind)>> { Ok(list_relationships_for_publisher(p)?) }
+
+    fn list_future_absences(&self, today: NaiveDate) -> StoreResult<Vec<Absence>> { Ok(list_future_absences(today)?) }
+    fn create_absence(&self, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<i64> { Ok(create_absence(publisher_id, start, end, desc)?) }
+    fn update_absence(&self, id: i64, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<()> { Ok(update_absence(id, publisher_id, start, end, desc)?) }
+    fn delete_absence(&self, id: i64) -> StoreResult<()> { Ok(delete_absence(id)?) }
+    fn is_absent_on(&self, publisher_id: i64, day: NaiveDate) -> StoreResult<bool> { Ok(is_absent_on(publisher_id, day)?) }
+    fn cleanup_expired_absences(&self, today: NaiveDate) -> StoreResult<usize> { Ok(cleanup_expired_absences(today)?) }
+
+    fn list_blackouts(&self) -> StoreResult<Vec<Blackout>> { Ok(list_blackouts()?) }
+    fn create_blackout(&self, b: &Blackout) -> StoreResult<i64> { self.create_blackouts(std::slice::from_ref(b)).map(|ids| ids[0]) }
+    fn create_blackouts(&self, rows: &[Blackout]) -> StoreResult<Vec<i64>> { for b in rows { b.validate()?; } Ok(create_blackouts(rows)?) }
+    fn update_blackout(&self, b: &Blackout) -> StoreResult<()> { b.validate()?; Ok(update_blackout(b)?) }
+    fn delete_blackout(&self, id: i64) -> StoreResult<()> { Ok(delete_blackout(id)?) }
+
+    fn list_shifts_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<Vec<Shift>> { Ok(list_shifts_between(start, end)?) }
+    fn create_shift(&self, start: NaiveDateTime, end: NaiveDateTime, location: &str, publishers: &[i64], warning: Option<&str>) -> StoreResult<i64> { Ok(create_shift(start, end, location, publishers, warning)?) }
+    fn update_shift_publishers(&self, id: i64, publishers: &[i64], warning: Option<&str>) -> StoreResult<()> { Ok(update_shift_publishers(id, publishers, warning)?) }
+    fn update_shift_datetime_location(&self, id: i64, start: NaiveDateTime, end: NaiveDateTime, location: &str, warning: Option<&str>) -> StoreResult<()> { Ok(update_shift_datetime_location(id, start, end, location, warning)?) }
+    fn delete_shift(&self, id: i64) -> StoreResult<()> { Ok(delete_shift(id)?) }
+    fn delete_shifts_in_range(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<usize> { Ok(delete_shifts_in_range(start, end)?) }
+
+    fn list_generation_batches(&self) -> StoreResult<Vec<GenerationBatch>> { Ok(list_generation_batches()?) }
+    fn save_generation(&self, batch: &GenerationBatch, rows: &[Shift]) -> StoreResult<i64> { Ok(save_generation(batch, rows)?) }
+    fn revert_generation(&self, id: i64) -> StoreResult<(usize, usize)> { Ok(revert_generation(id)?) }
+
+    fn snapshot(&self) -> StoreResult<Snapshot> { Ok(snapshot()?) }
+    // Imports, merges and resets snapshot the database first; a failed snapshot stops them
+    fn restore(&self, data: &Snapshot) -> StoreResult<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        super::backups::take(super::backups::Reason::Import).map_err(super::store::StoreError::Backend)?;
+        Ok(restore(data)?)
+    }
+    fn merge_data(&self, batch: &MergeBatch) -> StoreResult<()> {
+        for s in &batch.schedules { s.validate()?; }
+        for b in &batch.blackouts { b.validate()?; }
+        #[cfg(not(target_arch = "wasm32"))]
+        super::backups::take(super::backups::Reason::Import).map_err(super::store::StoreError::Backend)?;
+        Ok(merge_data(batch)?)
+    }
+    fn reset_data(&self) -> StoreResult<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        super::backups::take(super::backups::Reason::Reset).map_err(super::store::StoreError::Backend)?;
+        Ok(reset_data()?)
+    }
+}
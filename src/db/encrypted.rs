@@ -0,0 +1,178 @@
+// Passphrase-encrypted export files, readable by every build (native and wasm).
+// The export JSON is sealed with XChaCha20-Poly1305 under a key derived from the passphrase with Argon2id:
+//   { "format": "ppoc-gen-encrypted", "version": 1, "kdf": "argon2id", "salt", "m_cost", "t_cost", "p_cost",
+//     "cipher": "xchacha20poly1305", "nonce", "ciphertext" }   (binary fields hex)
+// Every header field is authenticated along with the ciphertext, so editing any of them fails like a wrong passphrase.
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+pub const FORMAT: &str = "ppoc-gen-encrypted";
+const VERSION: u32 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+// Minimum passphrase length accepted when encrypting
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+// Highest multiple of argon2's default costs a file may ask for when decrypting
+const MAX_COST_FACTOR: u32 = 4;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    kdf: String, // 'argon2id'
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    cipher: String, // 'xchacha20poly1305'
+    nonce: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct File {
+    #[serde(flatten)]
+    header: Header,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncryptedError {
+    WrongPassphrase, // or a modified file: the two can't be told apart
+    TooShort,
+    Unsupported(String),
+    Damaged(String),
+}
+
+impl std::fmt::Display for EncryptedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptedError::WrongPassphrase => write!(f, "wrong passphrase or modified file"),
+            EncryptedError::TooShort => write!(f, "passphrase must be at least {} characters", MIN_PASSPHRASE_LEN),
+            EncryptedError::Unsupported(e) => write!(f, "unsupported encrypted file: {}", e),
+            EncryptedError::Damaged(e) => write!(f, "damaged encrypted file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EncryptedError {}
+
+// True when `text` is an encrypted export rather than a plain one
+pub fn is_encrypted(text: &str) -> bool {
+    #[derive(Deserialize)]
+    struct Probe { format: String }
+    serde_json::from_str::<Probe>(text).is_ok_and(|p| p.format == FORMAT)
+}
+
+fn derive_key(passphrase: &str, h: &Header) -> Result<Zeroizing<[u8; KEY_LEN]>, EncryptedError> {
+    let salt = hex::decode(&h.salt).map_err(|e| EncryptedError::Damaged(e.to_string()))?;
+    let params = Params::new(h.m_cost, h.t_cost, h.p_cost, Some(KEY_LEN)).map_err(|e| EncryptedError::Unsupported(e.to_string()))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+        .map_err(|e| EncryptedError::Unsupported(e.to_string()))?;
+    Ok(key)
+}
+
+// The header as associated data: compact JSON in field order
+fn aad(h: &Header) -> Vec<u8> { serde_json::to_vec(h).unwrap_or_default() }
+
+pub fn encrypt(plain: &str, passphrase: &str) -> Result<String, EncryptedError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN { return Err(EncryptedError::TooShort); }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let defaults = Params::default();
+    let header = Header {
+        format: FORMAT.into(),
+        version: VERSION,
+        kdf: "argon2id".into(),
+        salt: hex::encode(salt),
+        m_cost: defaults.m_cost(),
+        t_cost: defaults.t_cost(),
+        p_cost: defaults.p_cost(),
+        cipher: "xchacha20poly1305".into(),
+        nonce: hex::encode(nonce),
+    };
+    let key = derive_key(passphrase, &header)?;
+    let sealed = XChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+        .encrypt(&nonce, Payload { msg: plain.as_bytes(), aad: &aad(&header) })
+        .map_err(|_| EncryptedError::Damaged("encryption failed".into()))?;
+    serde_json::to_string_pretty(&File { header, ciphertext: hex::encode(sealed) }).map_err(|e| EncryptedError::Damaged(e.to_string()))
+}
+
+// The plain export JSON inside an encrypted file
+pub fn decrypt(text: &str, passphrase: &str) -> Result<String, EncryptedError> {
+    let file: File = serde_json::from_str(text).map_err(|e| EncryptedError::Damaged(e.to_string()))?;
+    let h = &file.header;
+    if h.format != FORMAT || h.version != VERSION { return Err(EncryptedError::Unsupported(format!("{} version {}", h.format, h.version))); }
+    if h.kdf != "argon2id" || h.cipher != "xchacha20poly1305" { return Err(EncryptedError::Unsupported(format!("{} / {}", h.kdf, h.cipher))); }
+    // Exports are written with argon2's defaults; refuse anything above a few times those rather than
+    // allocate and spin for whatever the file asks
+    if h.m_cost > Params::DEFAULT_M_COST * MAX_COST_FACTOR || h.t_cost > Params::DEFAULT_T_COST * MAX_COST_FACTOR || h.p_cost > Params::DEFAULT_P_COST * MAX_COST_FACTOR { return Err(EncryptedError::Unsupported("key derivation cost too high".into())); }
+    let nonce = hex::decode(&h.nonce).map_err(|e| EncryptedError::Damaged(e.to_string()))?;
+    if nonce.len() != 24 { return Err(EncryptedError::Damaged("bad nonce".into())); }
+    let sealed = hex::decode(&file.ciphertext).map_err(|e| EncryptedError::Damaged(e.to_string()))?;
+    let key = derive_key(passphrase, h)?;
+    let plain = XChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &sealed, aad: &aad(h) })
+        .map_err(|_| EncryptedError::WrongPassphrase)?;
+    String::from_utf8(plain).map_err(|e| EncryptedError::Damaged(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    const PLAIN: &str = r#"{"format":"ppoc-gen-export"}"#;
+
+    // Encrypt PLAIN, let `edit` change the file, and decrypt the result
+    fn decrypt_edited(edit: impl FnOnce(&mut Value)) -> Result<String, EncryptedError> {
+        let mut file: Value = serde_json::from_str(&encrypt(PLAIN, "correct horse").unwrap()).unwrap();
+        edit(&mut file);
+        decrypt(&file.to_string(), "correct horse")
+    }
+
+    #[test]
+    fn round_trips() {
+        let text = encrypt(PLAIN, "correct horse").unwrap();
+        assert!(is_encrypted(&text));
+        assert_eq!(decrypt(&text, "correct horse").unwrap(), PLAIN);
+    }
+
+    #[test]
+    fn rejects_short_passphrases() {
+        assert_eq!(encrypt(PLAIN, "short"), Err(EncryptedError::TooShort));
+    }
+
+    #[test]
+    fn wrong_passphrase_fails() {
+        let text = encrypt(PLAIN, "correct horse").unwrap();
+        assert_eq!(decrypt(&text, "wrong horse"), Err(EncryptedError::WrongPassphrase));
+    }
+
+    #[test]
+    fn edited_header_or_ciphertext_fails() {
+        assert_eq!(decrypt_edited(|f| f["t_cost"] = json!(Params::DEFAULT_T_COST + 1)), Err(EncryptedError::WrongPassphrase));
+        assert_eq!(
+            decrypt_edited(|f| {
+                let c = f["ciphertext"].as_str().unwrap();
+                let flipped = if c.starts_with('0') { "1" } else { "0" };
+                f["ciphertext"] = json!(format!("{}{}", flipped, &c[1..]));
+            }),
+            Err(EncryptedError::WrongPassphrase),
+        );
+    }
+
+    #[test]
+    fn refuses_costs_above_the_cap() {
+        let too_high = |key: &str, default: u32| decrypt_edited(|f| f[key] = json!(default * MAX_COST_FACTOR + 1));
+        for (key, default) in [("m_cost", Params::DEFAULT_M_COST), ("t_cost", Params::DEFAULT_T_COST), ("p_cost", Params::DEFAULT_P_COST)] {
+            assert!(matches!(too_high(key, default), Err(EncryptedError::Unsupported(_))), "{}", key);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Export file format. Every backend writes the same envelope around its Snapshot:
+//   { "format": "ppoc-gen-export", "format_version": 5, "app_version", "congregation", "exported_at",
+//     "counts": { "publishers": n, ... }, "sha256": <hex digest of the compact JSON of "data">, "data": Snapshot }
+// Version 1 is the bare payload each backend wrote before the envelope existed; UPGRADES brings older files
+// up to date, then the checksum, counts and references are checked before an import touches any row.
+use std::collections::HashSet;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use super::store::{Snapshot, StoreError, StoreResult};
+
+pub const FORMAT: &str = "ppoc-gen-export";
+pub const FORMAT_VERSION: u64 = 5;
+
+// UPGRADES[n - 1] turns a version-n file into version n+1
+const UPGRADES: &[fn(Value) -> Value] = &[
+    // 1 -> 2: normalize the per-backend payload and wrap it (no checksum to verify in these files)
+    |file| {
+        let mut data = Map::new();
+        for key in ["publishers", "schedules", "availability", "relationships", "absences", "shifts"] {
+            data.insert(key.into(), file.get(key).cloned().unwrap_or_else(|| json!([])));
+        }
+        // Web exports kept shift times as "start_datetime"/"end_datetime" with a space separator
+        if let Some(shifts) = data["shifts"].as_array_mut() {
+            for s in shifts.iter_mut().filter_map(Value::as_object_mut) {
+                for (old, new) in [("start_datetime", "start"), ("end_datetime", "end")] {
+                    if let Some(Value::String(v)) = s.remove(old) { s.insert(new.into(), Value::String(v.replacen(' ', "T", 1))); }
+                }
+            }
+        }
+        // In-memory exports wrote the relationship kind capitalized
+        if let Some(rels) = data["relationships"].as_array_mut() {
+            for r in rels.iter_mut().filter_map(Value::as_array_mut) {
+                if let Some(Value::String(kind)) = r.get_mut(2) { *kind = kind.to_lowercase(); }
+            }
+        }
+        let data = Value::Object(data);
+        let len = |key: &str| data[key].as_array().map_or(0, Vec::len);
+        let counts = Counts { publishers: len("publishers"), schedules: len("schedules"), availability: len("availability"), relationships: len("relationships"), absences: len("absences"), shifts: len("shifts"), blackouts: 0, generation_batches: 0 };
+        json!({
+            "format": FORMAT, "format_version": 2, "app_version": "", "congregation": "", "exported_at": "",
+            "counts": counts, "sha256": digest(&data), "data": data,
+        })
+    },
+    // 2 -> 3: schedules gained `recurrence`; rows without one repeat every week, so the data is unchanged
+    |mut file| {
+        file["format_version"] = json!(3);
+        file
+    },
+    // 3 -> 4: blackout dates added; older files have none, so the data is unchanged
+    |mut file| {
+        file["format_version"] = json!(4);
+        file
+    },
+    // 4 -> 5: generation batches added; shifts in older files have none and count as made by hand
+    |mut file| {
+        file["format_version"] = json!(5);
+        file
+    },
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counts {
+    pub publishers: usize,
+    pub schedules: usize,
+    pub availability: usize,
+    pub relationships: usize,
+    pub absences: usize,
+    pub shifts: usize,
+    #[serde(default)] pub blackouts: usize,
+    #[serde(default)] pub generation_batches: usize,
+}
+
+impl Counts {
+    fn of(data: &Snapshot) -> Self {
+        Counts {
+            publishers: data.publishers.len(),
+            schedules: data.schedules.len(),
+            availability: data.availability.len(),
+            relationships: data.relationships.len(),
+            absences: data.absences.len(),
+            shifts: data.shifts.len(),
+            blackouts: data.blackouts.len(),
+            generation_batches: data.generation_batches.len(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    format_version: u64,
+    app_version: String,
+    congregation: String,
+    exported_at: String, // RFC 3339, UTC
+    counts: Counts,
+    sha256: String,
+    data: Value,
+}
+
+fn digest(data: &Value) -> String { hex::encode(Sha256::digest(data.to_string().as_bytes())) }
+
+pub fn seal(data: &Snapshot, congregation: &str) -> StoreResult<String> {
+    let value = serde_json::to_value(data)?;
+    let envelope = Envelope {
+        format: FORMAT.into(),
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").into(),
+        congregation: congregation.into(),
+        exported_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        counts: Counts::of(data),
+        sha256: digest(&value),
+        data: value,
+    };
+    Ok(serde_json::to_string_pretty(&envelope)?)
+}
+
+// Parse, upgrade and check an export file; Invalid lists every problem found, one per line
+pub fn open(json: &str) -> StoreResult<Snapshot> {
+    let mut file: Value = serde_json::from_str(json)?;
+    let version = match file.get("format") {
+        None => 1,
+        Some(f) if f == FORMAT => file.get("format_version").and_then(Value::as_u64).unwrap_or(0),
+        Some(_) => return Err(StoreError::Parse("not a ppoc-gen export file".into())),
+    };
+    if version == 0 || version > FORMAT_VERSION { return Err(StoreError::Parse(format!("export format {} is not supported by this version", version))); }
+    for step in &UPGRADES[version as usize - 1..] { file = step(file); }
+    let envelope: Envelope = serde_json::from_value(file)?;
+    if digest(&envelope.data) != envelope.sha256 { return Err(StoreError::Invalid("checksum mismatch: the file was modified or is incomplete".into())); }
+    let data: Snapshot = serde_json::from_value(envelope.data)?;
+    if Counts::of(&data) != envelope.counts { return Err(StoreError::Invalid("row counts don't match the file header".into())); }
+    let problems = validate(&data);
+    if !problems.is_empty() { return Err(StoreError::Invalid(problems.join("\n"))); }
+    Ok(data)
+}
+
+fn unique_ids(kind: &str, ids: impl Iterator<Item = i64>, problems: &mut Vec<String>) -> HashSet<i64> {
+    let mut seen = HashSet::new();
+    for id in ids { if !seen.insert(id) { problems.push(format!("{} {}: duplicate id", kind, id)); } }
+    seen
+}
+
+// Every duplicate id, dangling reference and value the schema would reject
+pub fn validate(data: &Snapshot) -> Vec<String> {
+    let mut problems = Vec::new();
+    let publishers = unique_ids("publisher", data.publishers.iter().map(|p| p.id), &mut problems);
+    let schedules = unique_ids("schedule", data.schedules.iter().map(|s| s.id), &mut problems);
+    unique_ids("absence", data.absences.iter().map(|a| a.id), &mut problems);
+    unique_ids("shift", data.shifts.iter().map(|s| s.id), &mut problems);
+    unique_ids("blackout", data.blackouts.iter().map(|b| b.id), &mut problems);
+    let batches = unique_ids("generation batch", data.generation_batches.iter().map(|b| b.id), &mut problems);
+
+    for p in &data.publishers {
+        if p.first_name.trim().is_empty() || p.last_name.trim().is_empty() { problems.push(format!("publisher {}: first and last name required", p.id)); }
+        if p.gender != "Male" && p.gender != "Female" { problems.push(format!("publisher {}: gender must be Male or Female, not {:?}", p.id, p.gender)); }
+    }
+    for s in &data.schedules {
+        if let Err(e) = s.validate() { problems.push(format!("schedule {}: {}", s.id, e)); }
+    }
+    let mut pairs = HashSet::new();
+    for (p, s) in &data.availability {
+        if !publishers.contains(p) { problems.push(format!("availability {}/{}: unknown publisher {}", p, s, p)); }
+        if !schedules.contains(s) { problems.push(format!("availability {}/{}: unknown schedule {}", p, s, s)); }
+        if !pairs.insert((*p, *s)) { problems.push(format!("availability {}/{}: listed twice", p, s)); }
+    }
+    let mut pairs = HashSet::new();
+    for (a, b, _) in &data.relationships {
+        for id in [a, b] { if !publishers.contains(id) { problems.push(format!("relationship {}/{}: unknown publisher {}", a, b, id)); } }
+        if a == b { problems.push(format!("relationship {}/{}: a publisher cannot be related to themselves", a, b)); }
+        if !pairs.insert((*a.min(b), *a.max(b))) { problems.push(format!("relationship {}/{}: listed twice", a, b)); }
+    }
+    for a in &data.absences {
+        if !publishers.contains(&a.publisher_id) { problems.push(format!("absence {}: unknown publisher {}", a.id, a.publisher_id)); }
+        if a.end_date < a.start_date { problems.push(format!("absence {}: ends before it starts", a.id)); }
+    }
+    for s in &data.shifts {
+        for id in s.publishers.iter().filter(|id| !publishers.contains(id)) { problems.push(format!("shift {}: unknown publisher {}", s.id, id)); }
+        if s.end < s.start { problems.push(format!("shift {}: ends before it starts", s.id)); }
+        if let Some(b) = s.batch_id.filter(|b| !batches.contains(b)) { problems.push(format!("shift {}: unknown generation batch {}", s.id, b)); }
+    }
+    for b in &data.generation_batches {
+        if b.end < b.start { problems.push(format!("generation batch {}: ends before it starts", b.id)); }
+        if let Err(e) = b.weights.validate() { problems.push(format!("generation batch {}: {}", b.id, e)); }
+    }
+    for b in &data.blackouts {
+        if let Err(e) = b.validate() { problems.push(format!("blackout {}: {}", b.id, e)); }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::db::store::{Absence, Publisher};
+
+    fn publisher(id: i64) -> Publisher {
+        Publisher { id, first_name: "Ana".into(), last_name: "Lopez".into(), gender: "Female".into(), is_shift_manager: false, priority: 5 }
+    }
+
+    fn absence(id: i64, publisher_id: i64) -> Absence {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        Absence { id, publisher_id, start_date: day, end_date: day, description: None }
+    }
+
+    fn sample() -> Snapshot { Snapshot { publishers: vec![publisher(1), publisher(2)], absences: vec![absence(1, 2)], ..Snapshot::default() } }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let data = sample();
+        assert_eq!(open(&seal(&data, "North").unwrap()).unwrap(), data);
+    }
+
+    #[test]
+    fn edited_data_fails_the_checksum() {
+        let mut file: Value = serde_json::from_str(&seal(&sample(), "North").unwrap()).unwrap();
+        file["data"]["publishers"][0]["first_name"] = json!("Eve");
+        match open(&file.to_string()) {
+            Err(StoreError::Invalid(msg)) => assert!(msg.contains("checksum"), "{}", msg),
+            other => panic!("expected a checksum error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn version_1_payloads_are_upgraded() {
+        let bare = json!({ "publishers": serde_json::to_value(vec![publisher(1)]).unwrap() });
+        let data = open(&bare.to_string()).unwrap();
+        assert_eq!(data.publishers, vec![publisher(1)]);
+        assert!(data.shifts.is_empty());
+    }
+
+    #[test]
+    fn validate_reports_dangling_references_and_duplicate_ids() {
+        let data = Snapshot { publishers: vec![publisher(1), publisher(1)], absences: vec![absence(7, 3)], ..Snapshot::default() };
+        let problems = validate(&data);
+        assert!(problems.contains(&"publisher 1: duplicate id".to_string()), "{:?}", problems);
+        assert!(problems.contains(&"absence 7: unknown publisher 3".to_string()), "{:?}", problems);
+        assert!(validate(&sample()).is_empty());
+    }
+}
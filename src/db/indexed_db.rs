@@ -0,0 +1,153 @@
+// IndexedDB persistence for the web build.
+// One object store per entity plus a `meta` store for id counters; shifts are indexed by start
+// date, absences and availability by their foreign keys. wasm_store keeps the in-memory copy
+// that the synchronous Store API reads from and queues row-level writes here.
+use std::cell::RefCell;
+use js_sys::{Array, Function, Promise, JSON};
+use serde_json::Value;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, IdbDatabase, IdbObjectStoreParameters, IdbRequest, IdbTransaction, IdbTransactionMode};
+
+const DB_NAME: &str = "dx_app";
+const DB_VERSION: u32 = 3; // 2: blackouts, 3: generation batches
+
+pub const PUBLISHERS: &str = "publishers";
+pub const SCHEDULES: &str = "schedules";
+pub const AVAILABILITY: &str = "availability";
+pub const RELATIONSHIPS: &str = "relationships";
+pub const ABSENCES: &str = "absences";
+pub const SHIFTS: &str = "shifts";
+pub const BLACKOUTS: &str = "blackouts";
+pub const GENERATION_BATCHES: &str = "generation_batches";
+pub const META: &str = "meta";
+pub const ALL_STORES: [&str; 9] = [PUBLISHERS, SCHEDULES, AVAILABILITY, RELATIONSHIPS, ABSENCES, SHIFTS, BLACKOUTS, GENERATION_BATCHES, META];
+
+// (store, key path, indexes as (name, key path))
+type StoreLayout = (&'static str, &'static [&'static str], &'static [(&'static str, &'static str)]);
+const LAYOUT: [StoreLayout; 9] = [
+    (PUBLISHERS, &["id"], &[]),
+    (SCHEDULES, &["id"], &[]),
+    (AVAILABILITY, &["publisher_id", "schedule_id"], &[("schedule_id", "schedule_id")]),
+    (RELATIONSHIPS, &["a", "b"], &[("b", "b")]),
+    (ABSENCES, &["id"], &[("publisher_id", "publisher_id"), ("end_date", "end_date")]),
+    (SHIFTS, &["id"], &[("start_datetime", "start_datetime")]),
+    (BLACKOUTS, &["id"], &[]),
+    (GENERATION_BATCHES, &["id"], &[]),
+    (META, &["key"], &[]),
+];
+
+pub enum Write {
+    Put(&'static str, Value),
+    Delete(&'static str, Value), // key: id, or [a, b] for composite keys
+    Clear(&'static str),
+}
+
+thread_local! {
+    static HANDLE: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+pub fn is_open() -> bool { HANDLE.with(|h| h.borrow().is_some()) }
+
+// Stop using IndexedDB (writes fall back to localStorage)
+pub fn close() {
+    if let Some(db) = HANDLE.with(|h| h.borrow_mut().take()) { db.close(); }
+}
+
+fn to_js(v: &Value) -> JsValue { JSON::parse(&v.to_string()).unwrap_or(JsValue::UNDEFINED) }
+
+fn from_js(v: &JsValue) -> Option<Value> { JSON::stringify(v).ok().and_then(|s| s.as_string()).and_then(|s| serde_json::from_str(&s).ok()) }
+
+// Resolve once `on_ok` or `on_err` fires on the target; the handlers are dropped afterwards
+async fn settle(set_ok: impl Fn(Option<&Function>), set_err: impl Fn(Option<&Function>), result: impl Fn() -> JsValue + 'static) -> Result<JsValue, JsValue> {
+    let (mut resolve, mut reject) = (None, None);
+    let promise = Promise::new(&mut |res, rej| { resolve = Some(res); reject = Some(rej); });
+    let (resolve, reject) = (resolve.unwrap(), reject.unwrap());
+    let ok = Closure::<dyn FnMut(Event)>::new(move |_| { let _ = resolve.call1(&JsValue::NULL, &result()); });
+    let err = Closure::<dyn FnMut(Event)>::new(move |_| { let _ = reject.call1(&JsValue::NULL, &JsValue::from_str("IndexedDB operation failed")); });
+    set_ok(Some(ok.as_ref().unchecked_ref()));
+    set_err(Some(err.as_ref().unchecked_ref()));
+    let out = JsFuture::from(promise).await;
+    set_ok(None);
+    set_err(None);
+    out
+}
+
+async fn request(req: &IdbRequest) -> Result<JsValue, JsValue> {
+    let r = req.clone();
+    settle(|f| req.set_onsuccess(f), |f| req.set_onerror(f), move || r.result().unwrap_or(JsValue::UNDEFINED)).await
+}
+
+async fn complete(tx: &IdbTransaction) -> Result<(), JsValue> {
+    settle(|f| tx.set_oncomplete(f), |f| { tx.set_onerror(f); tx.set_onabort(f); }, || JsValue::UNDEFINED).await.map(|_| ())
+}
+
+// Create any object store or index the database does not have yet
+fn upgrade(db: &IdbDatabase, tx: &IdbTransaction) -> Result<(), JsValue> {
+    for (name, key_path, indexes) in LAYOUT {
+        let store = if db.object_store_names().contains(name) {
+            tx.object_store(name)?
+        } else {
+            let params = IdbObjectStoreParameters::new();
+            let path: JsValue = if key_path.len() == 1 { key_path[0].into() } else { key_path.iter().map(|k| JsValue::from_str(k)).collect::<Array>().into() };
+            params.set_key_path(&path);
+            db.create_object_store_with_optional_parameters(name, &params)?
+        };
+        for (index, path) in indexes {
+            if !store.index_names().contains(index) { store.create_index_with_str(index, path)?; }
+        }
+    }
+    Ok(())
+}
+
+// Open (and create or upgrade) the database; later writes go to it
+pub async fn open() -> Result<(), JsValue> {
+    let factory = web_sys::window().ok_or("no window")?.indexed_db()?.ok_or("IndexedDB unavailable")?;
+    let req = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+    let r = req.clone();
+    let on_upgrade = Closure::<dyn FnMut(Event)>::new(move |_| {
+        let res = r.result().and_then(|db| {
+            let tx = r.transaction().ok_or("no upgrade transaction")?;
+            upgrade(&db.unchecked_into(), &tx)
+        });
+        if let Err(e) = res { web_sys::console::error_2(&"IndexedDB upgrade failed".into(), &e); }
+    });
+    req.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+    let db = request(&req).await;
+    req.set_onupgradeneeded(None);
+    let db: IdbDatabase = db?.unchecked_into();
+    HANDLE.with(|h| *h.borrow_mut() = Some(db));
+    Ok(())
+}
+
+fn handle() -> Result<IdbDatabase, JsValue> { HANDLE.with(|h| h.borrow().clone()).ok_or_else(|| "IndexedDB is not open".into()) }
+
+// Every row of one object store
+pub async fn read_all(store: &str) -> Result<Vec<Value>, JsValue> {
+    let tx = handle()?.transaction_with_str(store)?;
+    let rows: Array = request(&tx.object_store(store)?.get_all()?).await?.unchecked_into();
+    Ok(rows.iter().filter_map(|v| from_js(&v)).collect())
+}
+
+// Apply writes in one readwrite transaction and wait for it to commit
+pub async fn apply(writes: Vec<Write>) -> Result<(), JsValue> {
+    if writes.is_empty() { return Ok(()); }
+    let db = handle()?;
+    let names: Array = ALL_STORES.iter().map(|s| JsValue::from_str(s)).collect();
+    let tx = db.transaction_with_str_sequence_and_mode(&names, IdbTransactionMode::Readwrite)?;
+    for w in writes {
+        match w {
+            Write::Put(store, row) => { tx.object_store(store)?.put(&to_js(&row))?; }
+            Write::Delete(store, key) => { tx.object_store(store)?.delete(&to_js(&key))?; }
+            Write::Clear(store) => { tx.object_store(store)?.clear()?; }
+        }
+    }
+    complete(&tx).await
+}
+
+// Fire-and-forget variant used after each mutation; `on_fail` runs once the transaction did not commit
+pub fn queue(writes: Vec<Write>, on_fail: impl FnOnce(JsValue) + 'static) {
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = apply(writes).await { on_fail(e); }
+    });
+}
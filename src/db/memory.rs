@@ -0,0 +1,308 @@
+// In-memory Store. Used on host builds without a persistent backend, so the UI
+// still runs (nothing survives a restart).
+use chrono::{NaiveDate, NaiveDateTime};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::db::store::{Absence, Blackout, Configuration, GenerationBatch, MergeBatch, Publisher, RelationshipKind, Schedule, Shift, Snapshot, Store, StoreError, StoreResult};
+
+#[derive(Default, Clone)]
+struct State {
+    configuration: Option<Configuration>,
+    publishers: Vec<Publisher>,
+    schedules: Vec<Schedule>,
+    availability: Vec<(i64, i64)>,
+    relationships: Vec<(i64, i64, RelationshipKind)>,
+    absences: Vec<Absence>,
+    shifts: Vec<Shift>,
+    blackouts: Vec<Blackout>,
+    generation_batches: Vec<GenerationBatch>,
+    next_id: i64,
+}
+
+impl State {
+    fn next_id(&mut self) -> i64 { self.next_id += 1; self.next_id }
+}
+
+#[derive(Default)]
+pub struct MemoryStore { state: Mutex<State> }
+
+impl MemoryStore {
+    fn lock(&self) -> StoreResult<MutexGuard<'_, State>> { self.state.lock().map_err(|e| StoreError::Backend(e.to_string())) }
+}
+
+fn pair(a: i64, b: i64) -> (i64, i64) { if a < b { (a, b) } else { (b, a) } }
+
+impl Store for MemoryStore {
+    fn get_configuration(&self) -> StoreResult<Configuration> { self.lock()?.configuration.clone().ok_or(StoreError::NotFound) }
+    fn update_configuration(&self, cfg: &Configuration) -> StoreResult<()> { self.lock()?.configuration = Some(cfg.clone()); Ok(()) }
+
+    fn list_publishers(&self) -> StoreResult<Vec<Publisher>> {
+        let mut v = self.lock()?.publishers.clone();
+        v.sort_by(|a, b| a.first_name.to_lowercase().cmp(&b.first_name.to_lowercase()).then(a.last_name.to_lowercase().cmp(&b.last_name.to_lowercase())));
+        Ok(v)
+    }
+    fn create_publisher(&self, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<i64> {
+        let mut st = self.lock()?;
+        let id = st.next_id();
+        st.publishers.push(Publisher { id, first_name: first.into(), last_name: last.into(), gender: gender.into(), is_shift_manager, priority });
+        Ok(id)
+    }
+    fn create_publishers(&self, rows: &[Publisher]) -> StoreResult<Vec<i64>> {
+        let mut st = self.lock()?;
+        let mut ids = Vec::with_capacity(rows.len());
+        for p in rows {
+            let id = st.next_id();
+            st.publishers.push(Publisher { id, ..p.clone() });
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+    fn update_publisher(&self, id: i64, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        let p = st.publishers.iter_mut().find(|p| p.id == id).ok_or(StoreError::NotFound)?;
+        *p = Publisher { id, first_name: first.into(), last_name: last.into(), gender: gender.into(), is_shift_manager, priority };
+        Ok(())
+    }
+    fn delete_publisher(&self, id: i64) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        st.publishers.retain(|p| p.id != id);
+        st.availability.retain(|(p, _)| *p != id);
+        st.absences.retain(|a| a.publisher_id != id);
+        st.relationships.retain(|(a, b, _)| *a != id && *b != id);
+        Ok(())
+    }
+
+    fn list_schedules(&self) -> StoreResult<Vec<Schedule>> {
+        let mut v = self.lock()?.schedules.clone();
+        v.sort_by(|a, b| a.weekday.cmp(&b.weekday).then(a.start_hour.cmp(&b.start_hour)));
+        Ok(v)
+    }
+    fn create_schedule(&self, s: &Schedule) -> StoreResult<i64> {
+        s.validate()?;
+        let mut st = self.lock()?;
+        let id = st.next_id();
+        st.schedules.push(Schedule { id, ..s.clone() });
+        Ok(id)
+    }
+    fn update_schedule(&self, s: &Schedule) -> StoreResult<()> {
+        s.validate()?;
+        let mut st = self.lock()?;
+        let existing = st.schedules.iter_mut().find(|x| x.id == s.id).ok_or(StoreError::NotFound)?;
+        *existing = s.clone();
+        Ok(())
+    }
+    fn delete_schedule(&self, id: i64) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        st.schedules.retain(|x| x.id != id);
+        st.availability.retain(|(_, s)| *s != id);
+        Ok(())
+    }
+
+    fn set_publisher_availability(&self, publisher_id: i64, schedule_ids: &[i64]) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        st.availability.retain(|(p, _)| *p != publisher_id);
+        st.availability.extend(schedule_ids.iter().map(|s| (publisher_id, *s)));
+        Ok(())
+    }
+    fn list_availability_for_publisher(&self, publisher_id: i64) -> StoreResult<Vec<i64>> {
+        Ok(self.lock()?.availability.iter().filter(|(p, _)| *p == publisher_id).map(|(_, s)| *s).collect())
+    }
+    fn list_publishers_for_schedule(&self, schedule_id: i64) -> StoreResult<Vec<i64>> {
+        Ok(self.lock()?.availability.iter().filter(|(_, s)| *s == schedule_id).map(|(p, _)| *p).collect())
+    }
+
+    fn add_relationship(&self, a: i64, b: i64, kind: RelationshipKind) -> StoreResult<()> {
+        if a == b { return Err(StoreError::Invalid("a publisher cannot be related to themselves".into())); }
+        let (x, y) = pair(a, b);
+        let mut st = self.lock()?;
+        match st.relationships.iter_mut().find(|(aa, bb, _)| *aa == x && *bb == y) {
+            Some(row) => row.2 = kind,
+            None => st.relationships.push((x, y, kind)),
+        }
+        Ok(())
+    }
+    fn remove_relationship(&self, a: i64, b: i64) -> StoreResult<()> {
+        let (x, y) = pair(a, b);
+        self.lock()?.relationships.retain(|(aa, bb, _)| !(*aa == x && *bb == y));
+        Ok(())
+    }
+    fn list_relationships_for_publisher(&self, p: i64) -> StoreResult<Vec<(i64, RelationshipKind)>> {
+        Ok(self.lock()?.relationships.iter().filter_map(|(a, b, k)| {
+            if *a == p { Some((*b, *k)) } else if *b == p { Some((*a, *k)) } else { None }
+        }).collect())
+    }
+
+    fn list_future_absences(&self, today: NaiveDate) -> StoreResult<Vec<Absence>> {
+        let mut v: Vec<Absence> = self.lock()?.absences.iter().filter(|a| a.end_date >= today).cloned().collect();
+        v.sort_by_key(|a| a.start_date);
+        Ok(v)
+    }
+    fn create_absence(&self, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<i64> {
+        let mut st = self.lock()?;
+        let id = st.next_id();
+        st.absences.push(Absence { id, publisher_id, start_date: start, end_date: end, description: desc.map(str::to_string) });
+        Ok(id)
+    }
+    fn update_absence(&self, id: i64, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        let a = st.absences.iter_mut().find(|a| a.id == id).ok_or(StoreError::NotFound)?;
+        *a = Absence { id, publisher_id, start_date: start, end_date: end, description: desc.map(str::to_string) };
+        Ok(())
+    }
+    fn delete_absence(&self, id: i64) -> StoreResult<()> { self.lock()?.absences.retain(|a| a.id != id); Ok(()) }
+    fn is_absent_on(&self, publisher_id: i64, day: NaiveDate) -> StoreResult<bool> {
+        Ok(self.lock()?.absences.iter().any(|a| a.publisher_id == publisher_id && a.start_date <= day && day <= a.end_date))
+    }
+    fn cleanup_expired_absences(&self, today: NaiveDate) -> StoreResult<usize> {
+        let mut st = self.lock()?;
+        let before = st.absences.len();
+        st.absences.retain(|a| a.end_date >= today);
+        Ok(before - st.absences.len())
+    }
+
+    fn list_blackouts(&self) -> StoreResult<Vec<Blackout>> {
+        let mut v = self.lock()?.blackouts.clone();
+        v.sort_by_key(|b| (b.start_date, b.end_date));
+        Ok(v)
+    }
+    fn create_blackout(&self, b: &Blackout) -> StoreResult<i64> { self.create_blackouts(std::slice::from_ref(b)).map(|ids| ids[0]) }
+    fn create_blackouts(&self, rows: &[Blackout]) -> StoreResult<Vec<i64>> {
+        for b in rows { b.validate()?; }
+        let mut st = self.lock()?;
+        Ok(rows.iter().map(|b| { let id = st.next_id(); st.blackouts.push(Blackout { id, ..b.clone() }); id }).collect())
+    }
+    fn update_blackout(&self, b: &Blackout) -> StoreResult<()> {
+        b.validate()?;
+        let mut st = self.lock()?;
+        let existing = st.blackouts.iter_mut().find(|x| x.id == b.id).ok_or(StoreError::NotFound)?;
+        *existing = b.clone();
+        Ok(())
+    }
+    fn delete_blackout(&self, id: i64) -> StoreResult<()> { self.lock()?.blackouts.retain(|b| b.id != id); Ok(()) }
+
+    fn list_shifts_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<Vec<Shift>> {
+        let mut v: Vec<Shift> = self.lock()?.shifts.iter().filter(|s| s.start >= start && s.end <= end).cloned().collect();
+        v.sort_by_key(|s| s.start);
+        Ok(v)
+    }
+    fn create_shift(&self, start: NaiveDateTime, end: NaiveDateTime, location: &str, publishers: &[i64], warning: Option<&str>) -> StoreResult<i64> {
+        let mut st = self.lock()?;
+        let id = st.next_id();
+        st.shifts.push(Shift { id, start, end, location: location.into(), publishers: publishers.to_vec(), warning: warning.map(str::to_string), batch_id: None, edited: false });
+        Ok(id)
+    }
+    fn update_shift_publishers(&self, id: i64, publishers: &[i64], warning: Option<&str>) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        let s = st.shifts.iter_mut().find(|s| s.id == id).ok_or(StoreError::NotFound)?;
+        s.edited |= s.publishers != publishers;
+        s.publishers = publishers.to_vec();
+        s.warning = warning.map(str::to_string);
+        Ok(())
+    }
+    fn update_shift_datetime_location(&self, id: i64, start: NaiveDateTime, end: NaiveDateTime, location: &str, warning: Option<&str>) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        let s = st.shifts.iter_mut().find(|s| s.id == id).ok_or(StoreError::NotFound)?;
+        s.edited |= s.start != start || s.end != end || s.location != location;
+        s.start = start;
+        s.end = end;
+        s.location = location.into();
+        s.warning = warning.map(str::to_string);
+        Ok(())
+    }
+    fn delete_shift(&self, id: i64) -> StoreResult<()> { self.lock()?.shifts.retain(|s| s.id != id); Ok(()) }
+    fn delete_shifts_in_range(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<usize> {
+        let mut st = self.lock()?;
+        let before = st.shifts.len();
+        st.shifts.retain(|s| !(s.start >= start && s.end <= end));
+        Ok(before - st.shifts.len())
+    }
+
+    fn list_generation_batches(&self) -> StoreResult<Vec<GenerationBatch>> {
+        let mut v = self.lock()?.generation_batches.clone();
+        v.sort_by_key(|b| std::cmp::Reverse(b.id));
+        Ok(v)
+    }
+    fn save_generation(&self, batch: &GenerationBatch, rows: &[Shift]) -> StoreResult<i64> {
+        let mut st = self.lock()?;
+        let batch_id = if batch.id > 0 { batch.id } else {
+            let id = st.next_id();
+            st.generation_batches.push(GenerationBatch { id, ..batch.clone() });
+            id
+        };
+        for s in rows {
+            let id = st.next_id();
+            st.shifts.push(Shift { id, batch_id: Some(batch_id), edited: false, ..s.clone() });
+        }
+        Ok(batch_id)
+    }
+    fn revert_generation(&self, id: i64) -> StoreResult<(usize, usize)> {
+        let mut st = self.lock()?;
+        let before = st.shifts.len();
+        st.shifts.retain(|s| s.batch_id != Some(id) || s.edited);
+        let removed = before - st.shifts.len();
+        let mut kept = 0;
+        for s in st.shifts.iter_mut().filter(|s| s.batch_id == Some(id)) { s.batch_id = None; kept += 1; }
+        st.generation_batches.retain(|b| b.id != id);
+        Ok((removed, kept))
+    }
+
+    fn snapshot(&self) -> StoreResult<Snapshot> {
+        let st = self.lock()?;
+        Ok(Snapshot {
+            publishers: st.publishers.clone(),
+            schedules: st.schedules.clone(),
+            availability: st.availability.clone(),
+            relationships: st.relationships.clone(),
+            absences: st.absences.clone(),
+            shifts: st.shifts.clone(),
+            blackouts: st.blackouts.clone(),
+            generation_batches: st.generation_batches.clone(),
+        })
+    }
+    fn restore(&self, data: &Snapshot) -> StoreResult<()> {
+        let mut st = self.lock()?;
+        let ids = data.publishers.iter().map(|p| p.id).chain(data.schedules.iter().map(|s| s.id)).chain(data.absences.iter().map(|a| a.id)).chain(data.shifts.iter().map(|s| s.id)).chain(data.blackouts.iter().map(|b| b.id)).chain(data.generation_batches.iter().map(|b| b.id));
+        *st = State {
+            configuration: st.configuration.take(),
+            publishers: data.publishers.clone(),
+            schedules: data.schedules.clone(),
+            availability: data.availability.clone(),
+            relationships: data.relationships.iter().map(|(a, b, k)| { let (x, y) = pair(*a, *b); (x, y, *k) }).collect(),
+            absences: data.absences.clone(),
+            shifts: data.shifts.clone(),
+            blackouts: data.blackouts.clone(),
+            generation_batches: data.generation_batches.clone(),
+            next_id: ids.max().unwrap_or(0),
+        };
+        Ok(())
+    }
+    fn merge_data(&self, batch: &MergeBatch) -> StoreResult<()> {
+        for s in &batch.schedules { s.validate()?; }
+        for b in &batch.blackouts { b.validate()?; }
+        let mut st = self.lock()?;
+        let pids: Vec<i64> = batch.publishers.iter().map(|p| { let id = st.next_id(); st.publishers.push(Publisher { id, ..p.clone() }); id }).collect();
+        let sids: Vec<i64> = batch.schedules.iter().map(|s| { let id = st.next_id(); st.schedules.push(Schedule { id, ..s.clone() }); id }).collect();
+        for (p, s) in &batch.availability {
+            let row = (p.resolve(&pids), s.resolve(&sids));
+            if !st.availability.contains(&row) { st.availability.push(row); }
+        }
+        for (a, b, kind) in &batch.relationships {
+            let (x, y) = pair(a.resolve(&pids), b.resolve(&pids));
+            if x != y && !st.relationships.iter().any(|(aa, bb, _)| *aa == x && *bb == y) { st.relationships.push((x, y, *kind)); }
+        }
+        for a in &batch.absences {
+            let id = st.next_id();
+            st.absences.push(Absence { id, publisher_id: a.publisher.resolve(&pids), start_date: a.start_date, end_date: a.end_date, description: a.description.clone() });
+        }
+        for s in &batch.shifts {
+            let id = st.next_id();
+            st.shifts.push(Shift { id, start: s.start, end: s.end, location: s.location.clone(), publishers: s.publishers.iter().map(|r| r.resolve(&pids)).collect(), warning: s.warning.clone(), batch_id: None, edited: false });
+        }
+        for b in &batch.blackouts {
+            let id = st.next_id();
+            st.blackouts.push(Blackout { id, ..b.clone() });
+        }
+        Ok(())
+    }
+    fn reset_data(&self) -> StoreResult<()> { *self.lock()? = State::default(); Ok(()) }
+}
@@ -0,0 +1,220 @@
+// Numbered schema migrations for the SQLite database (native builds, and the web with `opfs-db`).
+// Each migration runs in its own transaction and is recorded in `_migrations` under its
+// number, so a failure leaves the schema at the last complete version. Before any pending migration
+// runs on an existing database it is snapshotted into backups/ (the web writes a copy of the image to
+// `data.db.v<N>.bak` in OPFS).
+// Append new migrations to MIGRATIONS; never edit or renumber one that has shipped.
+use rusqlite::{Connection, Result};
+use std::path::PathBuf;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: fn(&Connection) -> Result<()>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "0001_init", up: |c| c.execute_batch(INIT) },
+    Migration { version: 2, name: "0002_configuration_columns", up: configuration_columns },
+    Migration { version: 3, name: "0003_schedule_recurrence", up: schedule_recurrence },
+    Migration { version: 4, name: "0004_blackouts", up: |c| c.execute_batch(BLACKOUTS) },
+    Migration { version: 5, name: "0005_generator_weights", up: generator_weights },
+    Migration { version: 6, name: "0006_generation_batches", up: generation_batches },
+];
+
+#[derive(Debug)]
+pub struct MigrationError {
+    pub name: &'static str,
+    pub message: String,
+    pub backup: Option<PathBuf>,
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "migration {} failed: {}", self.name, self.message)?;
+        if let Some(b) = &self.backup { write!(f, " (backup of the previous database: {})", b.display())?; }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+// Bring the schema up to the latest version
+pub fn apply(conn: &Connection) -> std::result::Result<(), MigrationError> {
+    let fail = |name, e: &dyn std::error::Error, backup| MigrationError { name, message: e.to_string(), backup };
+    let current = current_version(conn).map_err(|e| fail("_migrations", &e, None))?;
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > current).collect();
+    if pending.is_empty() { return Ok(()); }
+    let backup = if current > 0 { backup(conn, current).map_err(|e| fail("backup", &e, None))? } else { None };
+    for m in pending {
+        run(conn, m).map_err(|e| fail(m.name, &e, backup.clone()))?;
+        super::log_note(&format!("Applied migration {}", m.name));
+    }
+    Ok(())
+}
+
+fn current_version(conn: &Connection) -> Result<i64> {
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS _migrations (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);")?;
+    conn.query_row("SELECT COALESCE(MAX(id), 0) FROM _migrations", [], |r| r.get(0))
+}
+
+fn run(conn: &Connection, m: &Migration) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    (m.up)(&tx)?;
+    tx.execute("INSERT INTO _migrations (id, name) VALUES (?1, ?2)", rusqlite::params![m.version, m.name])?;
+    tx.commit()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn backup(conn: &Connection, _version: i64) -> std::io::Result<Option<PathBuf>> {
+    super::backups::take_from(conn, super::backups::Reason::Migration).map_err(std::io::Error::other)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn backup(conn: &Connection, version: i64) -> std::io::Result<Option<PathBuf>> {
+    super::opfs::backup(conn, version).map(Some).map_err(std::io::Error::other)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |r| r.get::<_, String>(1))?.collect::<Result<Vec<_>>>()?;
+    Ok(names.iter().any(|n| n == column))
+}
+
+// Databases created before these columns were part of 0001_init
+fn configuration_columns(conn: &Connection) -> Result<()> {
+    let columns = [
+        ("Configuration", "name_order", "TEXT NOT NULL DEFAULT 'first_last'"),
+        ("Configuration", "week_start", "TEXT NOT NULL DEFAULT 'monday'"),
+        ("Configuration", "language", "TEXT NOT NULL DEFAULT 'system'"),
+        ("Configuration", "date_format", "TEXT NOT NULL DEFAULT 'YYYY-MM-DD'"),
+        ("Relationships", "kind", "TEXT NOT NULL DEFAULT 'recommended'"),
+    ];
+    for (table, column, decl) in columns {
+        if !has_column(conn, table, column)? {
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, decl))?;
+        }
+    }
+    Ok(())
+}
+
+// Repeat rule as JSON (see recurrence.rs); NULL repeats every week
+fn schedule_recurrence(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "Schedules", "recurrence")? { conn.execute_batch("ALTER TABLE Schedules ADD COLUMN recurrence TEXT;")?; }
+    Ok(())
+}
+
+// Generator weights as JSON (scheduler::Weights); NULL uses the defaults
+fn generator_weights(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "Configuration", "generator_weights")? { conn.execute_batch("ALTER TABLE Configuration ADD COLUMN generator_weights TEXT;")?; }
+    Ok(())
+}
+
+// Generator runs and the shifts they created. A NULL batch_id is a hand-made shift; `edited` marks
+// generated shifts changed by hand since, which reverting the run leaves in place
+fn generation_batches(conn: &Connection) -> Result<()> {
+    conn.execute_batch(GENERATION_BATCHES)?;
+    if !has_column(conn, "Shifts", "batch_id")? { conn.execute_batch("ALTER TABLE Shifts ADD COLUMN batch_id INTEGER REFERENCES GenerationBatches(id) ON DELETE SET NULL;")?; }
+    if !has_column(conn, "Shifts", "edited")? { conn.execute_batch("ALTER TABLE Shifts ADD COLUMN edited INTEGER NOT NULL DEFAULT 0;")?; }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_shifts_batch ON Shifts(batch_id);")
+}
+
+const GENERATION_BATCHES: &str = r#"
+CREATE TABLE IF NOT EXISTS GenerationBatches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at TEXT NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    mode TEXT NOT NULL,
+    weights TEXT NOT NULL
+);
+"#;
+
+// Days without shifts; a NULL location applies to every location
+const BLACKOUTS: &str = r#"
+CREATE TABLE IF NOT EXISTS Blackouts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    location TEXT,
+    reason TEXT,
+    CHECK (end_date >= start_date)
+);
+CREATE INDEX IF NOT EXISTS idx_blackouts_end ON Blackouts(end_date);
+"#;
+
+const INIT: &str = r#"
+-- Core tables
+CREATE TABLE IF NOT EXISTS Configuration (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    congregation_name TEXT NOT NULL,
+    theme TEXT NOT NULL DEFAULT 'System',
+    name_order TEXT NOT NULL DEFAULT 'first_last',
+    week_start TEXT NOT NULL DEFAULT 'monday',
+    language TEXT NOT NULL DEFAULT 'system',
+    date_format TEXT NOT NULL DEFAULT 'YYYY-MM-DD'
+);
+INSERT OR IGNORE INTO Configuration (id, congregation_name, theme, name_order, week_start, language, date_format) VALUES (1, 'Congregation', 'System', 'first_last', 'monday', 'system', 'YYYY-MM-DD');
+
+CREATE TABLE IF NOT EXISTS Publishers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    gender TEXT NOT NULL CHECK (gender IN ('Male','Female')),
+    is_shift_manager INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER NOT NULL DEFAULT 5
+);
+
+CREATE TABLE IF NOT EXISTS Schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    location TEXT NOT NULL,
+    start_hour TEXT NOT NULL,
+    end_hour TEXT NOT NULL,
+    weekday TEXT NOT NULL,
+    description TEXT,
+    num_publishers INTEGER NOT NULL,
+    num_shift_managers INTEGER NOT NULL,
+    num_brothers INTEGER NOT NULL,
+    num_sisters INTEGER NOT NULL,
+    CHECK (num_shift_managers + num_brothers + num_sisters <= num_publishers)
+);
+
+CREATE TABLE IF NOT EXISTS Absences (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    publisher_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    description TEXT
+);
+
+CREATE TABLE IF NOT EXISTS Shifts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_datetime TEXT NOT NULL,
+    end_datetime TEXT NOT NULL,
+    location TEXT NOT NULL,
+    publishers TEXT NOT NULL DEFAULT '[]',
+    warning TEXT
+);
+
+-- Join tables
+CREATE TABLE IF NOT EXISTS Availability (
+    publisher_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
+    schedule_id INTEGER NOT NULL REFERENCES Schedules(id) ON DELETE CASCADE,
+    PRIMARY KEY (publisher_id, schedule_id)
+);
+
+CREATE TABLE IF NOT EXISTS Relationships (
+    publisher_a_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
+    publisher_b_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL DEFAULT 'recommended',
+    PRIMARY KEY (publisher_a_id, publisher_b_id),
+    CHECK (publisher_a_id != publisher_b_id)
+);
+
+-- Indexes for performance
+CREATE INDEX IF NOT EXISTS idx_absences_publisher ON Absences(publisher_id);
+CREATE INDEX IF NOT EXISTS idx_shifts_start ON Shifts(start_datetime);
+CREATE INDEX IF NOT EXISTS idx_availability_schedule ON Availability(schedule_id);
+
+"#;
@@ -1,13 +1,15 @@
 // Native (sqlite) path
 #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
 pub mod native {
-    use once_cell::sync::Lazy;
-    use rusqlite::{Connection, Result};
+    #[cfg(not(feature = "encryption"))] use once_cell::sync::Lazy;
+    use rusqlite::Connection;
+    #[cfg(feature = "encryption")] use rusqlite::Result;
     use std::path::PathBuf;
     use std::sync::{Mutex, MutexGuard};
     use std::fs::{OpenOptions, create_dir_all};
     use std::io::Write;
-    fn log_note(msg: &str) {
+    #[cfg(feature = "encryption")] use zeroize::{Zeroize, Zeroizing};
+    pub(crate) fn log_note(msg: &str) {
         let mut base = log_base_dir();
         let _ = create_dir_all(&base);
         base.push("app.log");
@@ -23,11 +25,11 @@ pub mod native {
         p.push("dx_app");
         p
     }
-    #[cfg(feature = "encryption")] use rand::RngCore;
-    #[cfg(feature = "encryption")] use zeroize::Zeroize;
     // dao is defined as a sibling module at the crate::db level
 
-    pub static DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
+    // Plain builds open the database on first use
+    #[cfg(not(feature = "encryption"))]
+    static DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
         let path = db_file_path();
         if let Some(parent) = path.parent() { let _ = std::fs::create_dir_all(parent); }
 
@@ -39,29 +41,101 @@ pub mod native {
                 Connection::open_in_memory().unwrap()
             }
         };
-
-        let conn = {
-            let _ = ();
-            // Apply PRAGMA and optional encryption key
-            #[allow(unused_mut)]
-            let mut c = conn;
-            let _ = c.execute("PRAGMA foreign_keys = ON;", []);
-            #[cfg(feature = "encryption")] {
-                if path.as_os_str().len() > 0 && path.as_os_str() != ":memory:" {
-                    let _ = apply_key(&mut c);
-                }
-            }
-            c
-        };
-    if let Err(e) = apply_migrations(&conn) { log_note(&format!("DB migrations error: {}", e)); }
-        let today = chrono::Local::now().date_naive();
-        let _ = conn.execute("DELETE FROM Absences WHERE end_date < ?1", [today.to_string()]);
+        if let Err(e) = prepare(&conn) { *MIGRATION_ERROR.lock().unwrap() = Some(e); }
         Mutex::new(conn)
     });
 
+    // Set when the schema could not be brought up to date; the app shows it instead of running
+    #[cfg(not(feature = "encryption"))]
+    static MIGRATION_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+    #[cfg(not(feature = "encryption"))]
+    pub fn migration_error() -> Option<String> {
+        Lazy::force(&DB);
+        MIGRATION_ERROR.lock().unwrap().clone()
+    }
+
+    // Encrypted builds only get a connection once the passphrase unlocked it (see db::cipher)
+    #[cfg(feature = "encryption")]
+    static DB: once_cell::sync::OnceCell<Mutex<Connection>> = once_cell::sync::OnceCell::new();
+
+    #[cfg(not(feature = "encryption"))]
     pub fn connection() -> MutexGuard<'static, Connection> { DB.lock().unwrap() }
 
-    fn db_file_path() -> PathBuf {
+    #[cfg(feature = "encryption")]
+    pub fn connection() -> MutexGuard<'static, Connection> { DB.get().expect("database accessed before unlock").lock().unwrap() }
+
+    #[cfg(feature = "encryption")]
+    pub fn is_unlocked() -> bool { DB.get().is_some() }
+
+    // Data key of the open database; backups are written and read back with it
+    #[cfg(feature = "encryption")]
+    static KEY: Mutex<Option<Zeroizing<Vec<u8>>>> = Mutex::new(None);
+
+    #[cfg(feature = "encryption")]
+    pub(crate) fn data_key() -> Option<Zeroizing<Vec<u8>>> { KEY.lock().unwrap().clone() }
+
+    // PRAGMA key (or rekey) with a raw key, without leaving its hex form in memory
+    #[cfg(feature = "encryption")]
+    pub(crate) fn apply_key(conn: &Connection, pragma: &str, key: &[u8]) -> Result<()> {
+        let mut hex_key = hex::encode(key);
+        let res = conn.execute_batch(&format!("PRAGMA {} = \"x'{}'\";", pragma, hex_key));
+        hex_key.zeroize();
+        res
+    }
+
+    // Key a freshly opened connection with a raw 32-byte SQLCipher key and check that it decrypts
+    #[cfg(feature = "encryption")]
+    pub(crate) fn use_key(conn: &Connection, key: &[u8]) -> Result<()> {
+        apply_key(conn, "key", key)?;
+        conn.execute_batch("PRAGMA cipher_memory_security = ON;")?;
+        // Reading the schema fails with SQLITE_NOTADB when the key is wrong
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |r| r.get::<_, i64>(0))?;
+        Ok(())
+    }
+
+    // Open the database file with a raw key and check that it decrypts
+    #[cfg(feature = "encryption")]
+    pub(crate) fn open_keyed(path: &std::path::Path, key: &[u8]) -> Result<Connection> {
+        let conn = Connection::open(path)?;
+        use_key(&conn, key)?;
+        Ok(conn)
+    }
+
+    // Re-encrypt the open database under a new raw key
+    #[cfg(feature = "encryption")]
+    pub(crate) fn rekey(key: &[u8]) -> Result<()> {
+        apply_key(&connection(), "rekey", key)?;
+        *KEY.lock().unwrap() = Some(Zeroizing::new(key.to_vec()));
+        Ok(())
+    }
+
+    // Install an unlocked connection as the process-wide database; a failed migration keeps it locked
+    #[cfg(feature = "encryption")]
+    pub(crate) fn install(conn: Connection, key: &[u8]) -> std::result::Result<(), String> {
+        // Set first: the snapshot taken before migrations is encrypted with it
+        *KEY.lock().unwrap() = Some(Zeroizing::new(key.to_vec()));
+        if let Err(e) = prepare(&conn) {
+            *KEY.lock().unwrap() = None;
+            return Err(e);
+        }
+        let _ = DB.set(Mutex::new(conn));
+        Ok(())
+    }
+
+    // Pragmas, migrations and housekeeping run on every freshly opened database
+    pub(super) fn prepare(conn: &Connection) -> Result<(), String> {
+        let _ = conn.execute("PRAGMA foreign_keys = ON;", []);
+        if let Err(e) = super::migrations::apply(conn) {
+            log_note(&format!("DB migrations error: {}", e));
+            return Err(e.to_string());
+        }
+        let today = chrono::Local::now().date_naive();
+        let _ = conn.execute("DELETE FROM Absences WHERE end_date < ?1", [today.to_string()]);
+        Ok(())
+    }
+
+    pub(crate) fn db_file_path() -> PathBuf {
         // Try a series of writable locations across platforms
         let mut candidates: Vec<PathBuf> = Vec::new();
         if let Some(p) = dirs_next::data_local_dir() { candidates.push(p); }
@@ -84,178 +158,152 @@ pub mod native {
         base.push("data.db");
         base
     }
+}
 
-    fn apply_migrations(conn: &Connection) -> Result<()> {
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS _migrations (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);",
-            [],
-        )?;
-        let migrations: &[(&str, &str)] = &[("0001_init", super::MIGRATION_0001_INIT)];
-        for (name, sql) in migrations {
-            let already: Option<i64> = conn
-                .query_row("SELECT id FROM _migrations WHERE name = ?1", [name], |row| row.get(0))
-                .optional()?;
-            if already.is_none() {
-                conn.execute_batch(sql)?;
-                conn.execute("INSERT INTO _migrations (name) VALUES (?1)", [name])?;
-            }
-        }
-        // Idempotent patch: ensure Configuration has name_order column
-        let mut stmt = conn.prepare("PRAGMA table_info(Configuration)")?;
-        let mut has_name_order = false;
-        let mut has_week_start = false;
-        let mut has_language = false;
-        let mut has_date_format = false;
-        let mut rows = stmt.query([])?;
-        while let Some(row) = rows.next()? {
-            let col_name: String = row.get(1)?; // 1 = name
-            if col_name == "name_order" { has_name_order = true; }
-            if col_name == "week_start" { has_week_start = true; }
-            if col_name == "language" { has_language = true; }
-            if col_name == "date_format" { has_date_format = true; }
-        }
-        if !has_name_order {
-            let _ = conn.execute("ALTER TABLE Configuration ADD COLUMN name_order TEXT NOT NULL DEFAULT 'first_last'", []);
-        }
-        if !has_week_start {
-            let _ = conn.execute("ALTER TABLE Configuration ADD COLUMN week_start TEXT NOT NULL DEFAULT 'monday'", []);
-        }
-        if !has_language {
-            let _ = conn.execute("ALTER TABLE Configuration ADD COLUMN language TEXT NOT NULL DEFAULT 'system'", []);
-        }
-        if !has_date_format {
-            let _ = conn.execute("ALTER TABLE Configuration ADD COLUMN date_format TEXT NOT NULL DEFAULT 'YYYY-MM-DD'", []);
-        }
-        // Ensure Relationships.kind column exists
-        {
-            let mut stmt = conn.prepare("PRAGMA table_info(Relationships)")?;
-            let mut has_kind = false;
-            let mut rows = stmt.query([])?;
-            while let Some(row) = rows.next()? {
-                let col_name: String = row.get(1)?; // 1 = name
-                if col_name == "kind" { has_kind = true; }
-            }
-            if !has_kind {
-                let _ = conn.execute("ALTER TABLE Relationships ADD COLUMN kind TEXT NOT NULL DEFAULT 'recommended'", []);
-            }
-        }
-        Ok(())
-    }
-
-    #[cfg(feature = "encryption")]
-    fn apply_key(conn: &mut Connection) -> Result<()> {
-        let key_path = key_file_path();
-        let key_bytes = if key_path.exists() {
-            std::fs::read(&key_path).expect("read key file")
-        } else {
-            let mut rng = rand::rngs::OsRng;
-            let mut secret = [0u8; 32];
-            rng.fill_bytes(&mut secret);
-            std::fs::write(&key_path, &secret).expect("store key file");
-            secret.to_vec()
-        };
-        let hex_key = hex::encode(&key_bytes);
-        let _ = conn.execute(&format!("PRAGMA key = '{}';", hex_key), []);
-        let _ = conn.execute("PRAGMA cipher_memory_security = ON;", []);
-        Ok(())
-    }
-    #[cfg(feature = "encryption")]
-    fn key_file_path() -> PathBuf {
-        let mut base = dirs_next::data_local_dir().unwrap_or(std::env::current_dir().unwrap());
-        base.push("dx_app");
-        base.push("key.bin");
-        base
-    }
+// Backend-agnostic Store trait and domain types
+pub mod store;
+// Versioned export file format shared by every backend
+pub mod envelope;
+// Passphrase-encrypted wrapper around export files
+pub mod encrypted;
 
-    // Helper trait
-    trait OptionalRow { type Output; fn optional(self) -> Result<Option<Self::Output>>; }
-    impl<T> OptionalRow for rusqlite::Result<T> { type Output = T; fn optional(self) -> Result<Option<T>> { match self { Ok(v)=>Ok(Some(v)), Err(rusqlite::Error::QueryReturnedNoRows)=>Ok(None), Err(e)=>Err(e) } } }
-}
+// In-memory store: backend for host builds without native-db, and placeholder while an encrypted database is locked
+#[cfg_attr(all(any(feature = "native-db", target_arch = "wasm32"), not(feature = "encryption")), allow(dead_code))]
+pub mod memory;
 
-// Wasm path store API
+// Wasm path store API. Without `opfs-db` the web build does not run the SQLite schema and
+// wasm_store mirrors its checks and cascades; with it, wasm_store only hands its data over once.
 #[cfg(target_arch = "wasm32")] pub mod wasm_store;
+// IndexedDB persistence behind wasm_store
+#[cfg(target_arch = "wasm32")] mod indexed_db;
+
+// SQLite on the web, saved to OPFS by a dedicated worker
+#[cfg(all(feature = "opfs-db", target_arch = "wasm32"))]
+pub mod opfs;
 
-// Expose dao module at top-level for native targets (file: src/db/dao.rs)
+// Passphrase unlock for the SQLCipher build
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+pub mod cipher;
+
+// Numbered schema migrations for the SQLite database
+#[cfg(any(all(feature = "native-db", not(target_arch = "wasm32")), all(feature = "opfs-db", target_arch = "wasm32")))]
+mod migrations;
+
+// Rotating snapshots of data.db
 #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+pub mod backups;
+
+// Expose dao module at top-level for SQLite targets (file: src/db/dao.rs)
+#[cfg(any(all(feature = "native-db", not(target_arch = "wasm32")), all(feature = "opfs-db", target_arch = "wasm32")))]
 pub mod dao;
 
 
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-const MIGRATION_0001_INIT: &str = r#"
--- Core tables
-CREATE TABLE IF NOT EXISTS Configuration (
-    id INTEGER PRIMARY KEY CHECK (id = 1),
-    congregation_name TEXT NOT NULL,
-    theme TEXT NOT NULL DEFAULT 'System',
-    name_order TEXT NOT NULL DEFAULT 'first_last',
-    week_start TEXT NOT NULL DEFAULT 'monday',
-    language TEXT NOT NULL DEFAULT 'system',
-    date_format TEXT NOT NULL DEFAULT 'YYYY-MM-DD'
-);
-INSERT OR IGNORE INTO Configuration (id, congregation_name, theme, name_order, week_start, language, date_format) VALUES (1, 'Congregation', 'System', 'first_last', 'monday', 'system', 'YYYY-MM-DD');
-
-CREATE TABLE IF NOT EXISTS Publishers (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    first_name TEXT NOT NULL,
-    last_name TEXT NOT NULL,
-    gender TEXT NOT NULL CHECK (gender IN ('Male','Female')),
-    is_shift_manager INTEGER NOT NULL DEFAULT 0,
-    priority INTEGER NOT NULL DEFAULT 5
-);
-
-CREATE TABLE IF NOT EXISTS Schedules (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    location TEXT NOT NULL,
-    start_hour TEXT NOT NULL,
-    end_hour TEXT NOT NULL,
-    weekday TEXT NOT NULL,
-    description TEXT,
-    num_publishers INTEGER NOT NULL,
-    num_shift_managers INTEGER NOT NULL,
-    num_brothers INTEGER NOT NULL,
-    num_sisters INTEGER NOT NULL,
-    CHECK (num_shift_managers + num_brothers + num_sisters <= num_publishers)
-);
-
-CREATE TABLE IF NOT EXISTS Absences (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    publisher_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
-    start_date TEXT NOT NULL,
-    end_date TEXT NOT NULL,
-    description TEXT
-);
-
-CREATE TABLE IF NOT EXISTS Shifts (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    start_datetime TEXT NOT NULL,
-    end_datetime TEXT NOT NULL,
-    location TEXT NOT NULL,
-    publishers TEXT NOT NULL DEFAULT '[]',
-    warning TEXT
-);
-
--- Join tables
-CREATE TABLE IF NOT EXISTS Availability (
-    publisher_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
-    schedule_id INTEGER NOT NULL REFERENCES Schedules(id) ON DELETE CASCADE,
-    PRIMARY KEY (publisher_id, schedule_id)
-);
-
-CREATE TABLE IF NOT EXISTS Relationships (
-    publisher_a_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
-    publisher_b_id INTEGER NOT NULL REFERENCES Publishers(id) ON DELETE CASCADE,
-    kind TEXT NOT NULL DEFAULT 'recommended',
-    PRIMARY KEY (publisher_a_id, publisher_b_id),
-    CHECK (publisher_a_id != publisher_b_id)
-);
-
--- Indexes for performance
-CREATE INDEX IF NOT EXISTS idx_absences_publisher ON Absences(publisher_id);
-CREATE INDEX IF NOT EXISTS idx_shifts_start ON Shifts(start_datetime);
-CREATE INDEX IF NOT EXISTS idx_availability_schedule ON Availability(schedule_id);
-
-"#;
-
-// Native connection re-export for external code
+// Connection re-export for external code
 #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
 pub use native::connection;
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+pub(crate) use native::log_note;
+#[cfg(all(feature = "opfs-db", target_arch = "wasm32"))]
+pub use opfs::connection;
+#[cfg(all(feature = "opfs-db", target_arch = "wasm32"))]
+pub(crate) use opfs::log_note;
+
+// Active store for the current target
+#[cfg(all(feature = "native-db", not(feature = "encryption"), not(target_arch = "wasm32")))]
+pub fn store() -> &'static dyn store::Store { &dao::SqliteStore }
+
+// While the encrypted database is locked, callers see an empty throwaway store
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+pub fn store() -> &'static dyn store::Store {
+    static LOCKED: once_cell::sync::Lazy<memory::MemoryStore> = once_cell::sync::Lazy::new(memory::MemoryStore::default);
+    if native::is_unlocked() { &dao::SqliteStore } else { &*LOCKED }
+}
+
+// Schema upgrade failure that stops the app (encrypted builds report it on the unlock screen instead)
+#[cfg(all(feature = "native-db", not(feature = "encryption"), not(target_arch = "wasm32")))]
+pub fn migration_error() -> Option<String> { native::migration_error() }
+
+#[cfg(all(feature = "opfs-db", target_arch = "wasm32"))]
+pub fn migration_error() -> Option<String> { opfs::migration_error() }
+
+#[cfg(not(any(all(feature = "native-db", not(feature = "encryption"), not(target_arch = "wasm32")), all(feature = "opfs-db", target_arch = "wasm32"))))]
+pub fn migration_error() -> Option<String> { None }
+
+// The web database could not be opened (OPFS builds; most often it is open in another tab)
+#[cfg(all(feature = "opfs-db", target_arch = "wasm32"))]
+pub fn open_error() -> Option<String> { opfs::open_error() }
+
+#[cfg(not(all(feature = "opfs-db", target_arch = "wasm32")))]
+pub fn open_error() -> Option<String> { None }
+
+// True while the encrypted database still waits for its passphrase
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+pub fn is_locked() -> bool { !native::is_unlocked() }
+
+#[cfg(all(target_arch = "wasm32", not(feature = "opfs-db")))]
+pub fn store() -> &'static dyn store::Store { &wasm_store::WasmStore }
+
+#[cfg(all(target_arch = "wasm32", feature = "opfs-db"))]
+pub fn store() -> &'static dyn store::Store { &dao::SqliteStore }
+
+// Web builds load their data before the first render
+#[cfg(target_arch = "wasm32")]
+pub async fn init() {
+    #[cfg(feature = "opfs-db")]
+    opfs::init().await;
+    #[cfg(not(feature = "opfs-db"))]
+    wasm_store::init().await;
+}
+
+// Web writes reach storage after the Store call returned; a failed one is reported to this listener with
+// (the data went to the localStorage blob instead, detail)
+#[cfg(target_arch = "wasm32")]
+type SaveErrorListener = Box<dyn FnMut(bool, String)>;
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static ON_SAVE_ERROR: std::cell::RefCell<Option<SaveErrorListener>> = const { std::cell::RefCell::new(None) };
+}
+
+// The app shows these; the Store call that made the change has already reported success
+#[cfg(target_arch = "wasm32")]
+pub fn on_save_error(f: impl FnMut(bool, String) + 'static) {
+    ON_SAVE_ERROR.with(|cb| *cb.borrow_mut() = Some(Box::new(f)));
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save_error(kept: bool, detail: String) {
+    web_sys::console::error_1(&format!("Saving failed ({}): {}", if kept { "kept in localStorage" } else { "not saved" }, detail).into());
+    ON_SAVE_ERROR.with(|cb| if let Some(f) = cb.borrow_mut().as_mut() { f(kept, detail) });
+}
+
+#[cfg(not(any(feature = "native-db", target_arch = "wasm32")))]
+pub fn store() -> &'static dyn store::Store {
+    static MEMORY: once_cell::sync::Lazy<memory::MemoryStore> = once_cell::sync::Lazy::new(memory::MemoryStore::default);
+    &*MEMORY
+}
+
+// Local calendar date (browser clock on web)
+#[cfg(target_arch = "wasm32")]
+pub fn today() -> chrono::NaiveDate {
+    let d = js_sys::Date::new_0();
+    chrono::NaiveDate::from_ymd_opt(d.get_full_year() as i32, d.get_month() + 1, d.get_date()).unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn today() -> chrono::NaiveDate { chrono::Local::now().date_naive() }
+
+// Local date and time to the second (browser clock on web)
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> chrono::NaiveDateTime {
+    let d = js_sys::Date::new_0();
+    chrono::NaiveDate::from_ymd_opt(d.get_full_year() as i32, d.get_month() + 1, d.get_date())
+        .and_then(|day| day.and_hms_opt(d.get_hours(), d.get_minutes(), d.get_seconds()))
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> chrono::NaiveDateTime {
+    use chrono::Timelike;
+    chrono::Local::now().naive_local().with_nanosecond(0).unwrap_or_default()
+}
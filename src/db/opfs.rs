@@ -0,0 +1,220 @@
+// SQLite for the web build (`opfs-db` feature): the same schema, migrations and dao.rs as native builds,
+// on sqlite-wasm-rs through rusqlite. Store calls are synchronous on the UI thread, but OPFS files can only
+// be written synchronously from a dedicated worker, so the database runs in memory here and the worker owns
+// the file: every commit schedules a copy of the whole image (sqlite3_serialize), posted to the worker
+// once the current call returns. The worker writes data.db.next and then data.db; on start the first of
+// the two that is complete is loaded, so a write cut short never loses the previous state.
+// The worker keeps both files open, which also keeps a second tab from opening the database.
+// On first use the IndexedDB (or localStorage) data of the plain web build is copied in.
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use once_cell::sync::OnceCell;
+use rusqlite::{Connection, MAIN_DB};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, MessageEvent, Url, Worker};
+
+use super::store::Store;
+
+const FILE_NAME: &str = "data.db";
+
+// Requests are handled one after the other, so saves land in the order they were posted
+const WORKER_JS: &str = r#"
+const handles = {};
+async function open(name) {
+  if (!handles[name]) {
+    const root = await navigator.storage.getDirectory();
+    const file = await root.getFileHandle(name, { create: true });
+    handles[name] = await file.createSyncAccessHandle();
+  }
+  return handles[name];
+}
+function read(h) { const buf = new Uint8Array(h.getSize()); h.read(buf, { at: 0 }); return buf; }
+function write(h, bytes) { h.truncate(0); h.write(bytes, { at: 0 }); h.flush(); }
+async function run(msg) {
+  if (msg.op === "load") {
+    const main = read(await open(msg.name)), next = read(await open(msg.name + ".next"));
+    return [{ main, next }, [main.buffer, next.buffer]];
+  }
+  if (msg.op === "save") write(await open(msg.name + ".next"), msg.bytes);
+  write(await open(msg.name), msg.bytes);
+  return [{}, []];
+}
+let queue = Promise.resolve();
+onmessage = ({ data }) => {
+  queue = queue.then(() => run(data)).then(
+    ([reply, transfer]) => postMessage(Object.assign(reply, { id: data.id, ok: true }), transfer),
+    (e) => postMessage({ id: data.id, ok: false, error: String(e) }),
+  );
+};
+"#;
+
+static DB: OnceCell<Mutex<Connection>> = OnceCell::new();
+
+// Set when the file could not be opened or its schema brought up to date; the app shows it instead of running
+static OPEN_ERROR: Mutex<Option<String>> = Mutex::new(None);
+static MIGRATION_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+thread_local! {
+    static WORKER: RefCell<Option<Worker>> = const { RefCell::new(None) };
+    // Replies the worker still owes: request id -> (resolve, reject)
+    static PENDING: RefCell<HashMap<u32, (Function, Function)>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u32> = const { Cell::new(1) };
+    static FLUSH_QUEUED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn connection() -> MutexGuard<'static, Connection> { DB.get().expect("database accessed before init").lock().unwrap() }
+
+pub fn open_error() -> Option<String> { OPEN_ERROR.lock().unwrap().clone() }
+
+pub fn migration_error() -> Option<String> { MIGRATION_ERROR.lock().unwrap().clone() }
+
+pub(crate) fn log_note(msg: &str) { web_sys::console::log_1(&msg.into()); }
+
+fn start_worker() -> Result<Worker, JsValue> {
+    let opts = BlobPropertyBag::new();
+    opts.set_type("text/javascript");
+    let blob = Blob::new_with_str_sequence_and_options(&Array::of1(&WORKER_JS.into()), &opts)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let worker = Worker::new(&url)?;
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(|e: MessageEvent| {
+        let data = e.data();
+        let id = Reflect::get(&data, &"id".into()).ok().and_then(|v| v.as_f64()).unwrap_or_default() as u32;
+        let Some((resolve, reject)) = PENDING.with(|p| p.borrow_mut().remove(&id)) else { return };
+        if Reflect::get(&data, &"ok".into()).ok().and_then(|v| v.as_bool()).unwrap_or(false) {
+            let _ = resolve.call1(&JsValue::NULL, &data);
+        } else {
+            let _ = reject.call1(&JsValue::NULL, &Reflect::get(&data, &"error".into()).unwrap_or(JsValue::UNDEFINED));
+        }
+    });
+    worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+    Ok(worker)
+}
+
+// Post one request to the worker and wait for its reply; `bytes` is handed over, not copied
+async fn call(op: &str, name: &str, bytes: Option<Uint8Array>) -> Result<JsValue, JsValue> {
+    let worker = WORKER.with(|w| w.borrow().clone()).ok_or("OPFS worker is not running")?;
+    let id = NEXT_ID.with(|n| { let id = n.get(); n.set(id + 1); id });
+    let msg = Object::new();
+    Reflect::set(&msg, &"id".into(), &id.into())?;
+    Reflect::set(&msg, &"op".into(), &op.into())?;
+    Reflect::set(&msg, &"name".into(), &name.into())?;
+    let mut slot = None;
+    let promise = Promise::new(&mut |resolve, reject| slot = Some((resolve, reject)));
+    PENDING.with(|p| p.borrow_mut().insert(id, slot.take().unwrap()));
+    match bytes {
+        Some(b) => {
+            Reflect::set(&msg, &"bytes".into(), &b)?;
+            worker.post_message_with_transfer(&msg, &Array::of1(&b.buffer()))?;
+        }
+        None => worker.post_message(&msg)?,
+    }
+    JsFuture::from(promise).await
+}
+
+// A whole SQLite image: the page size and page count in its header match its length
+fn is_complete(image: &[u8]) -> bool {
+    if image.len() < 100 || &image[..16] != b"SQLite format 3\0" { return false; }
+    let page_size = match u16::from_be_bytes([image[16], image[17]]) { 1 => 65536, n => usize::from(n) };
+    let pages = u32::from_be_bytes([image[28], image[29], image[30], image[31]]) as usize;
+    page_size * pages == image.len()
+}
+
+fn image(conn: &Connection) -> rusqlite::Result<Uint8Array> { conn.serialize(MAIN_DB).map(|data| Uint8Array::from(&data[..])) }
+
+// Write the current image once the Store call that committed has returned
+fn queue_flush() {
+    if FLUSH_QUEUED.with(|q| q.replace(true)) { return; }
+    wasm_bindgen_futures::spawn_local(async {
+        FLUSH_QUEUED.with(|q| q.set(false));
+        let Some(db) = DB.get() else { return };
+        let bytes = image(&db.lock().unwrap());
+        let res = match bytes {
+            Ok(bytes) => call("save", FILE_NAME, Some(bytes)).await.map(|_| ()).map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e))),
+            Err(e) => Err(e.to_string()),
+        };
+        // The next commit writes the whole image again, so a later save catches up
+        if let Err(e) = res { super::save_error(false, e); }
+    });
+}
+
+// Copy of the image before migrations change it, next to data.db
+pub(super) fn backup(conn: &Connection, version: i64) -> Result<std::path::PathBuf, String> {
+    let name = format!("{}.v{}.bak", FILE_NAME, version);
+    let bytes = image(conn).map_err(|e| e.to_string())?;
+    let file = name.clone();
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = call("write", &file, Some(bytes)).await { web_sys::console::error_2(&"OPFS backup failed:".into(), &e); }
+    });
+    Ok(name.into())
+}
+
+async fn load() -> Result<Option<Vec<u8>>, JsValue> {
+    let files = call("load", FILE_NAME, None).await?;
+    let file = |key: &str| Reflect::get(&files, &key.into()).map(|v| v.unchecked_into::<Uint8Array>().to_vec());
+    let (main, next) = (file("main")?, file("next")?);
+    Ok([main, next].into_iter().find(|image| is_complete(image)))
+}
+
+fn open(image: Option<Vec<u8>>) -> rusqlite::Result<Connection> {
+    let mut conn = Connection::open_in_memory()?;
+    if let Some(image) = image { conn.deserialize_read_exact(MAIN_DB, image.as_slice(), image.len(), false)?; }
+    Ok(conn)
+}
+
+// Pragmas, migrations and housekeeping, as on native builds
+fn prepare(conn: &Connection) -> Result<(), String> {
+    let _ = conn.execute("PRAGMA foreign_keys = ON;", []);
+    super::migrations::apply(conn).map_err(|e| e.to_string())?;
+    let _ = conn.execute("DELETE FROM Absences WHERE end_date < ?1", [super::today().to_string()]);
+    Ok(())
+}
+
+// The plain web build's data (IndexedDB, or the localStorage blob), copied in on first use
+async fn copy_from_web_store() -> Result<(), String> {
+    super::wasm_store::init().await;
+    let old = super::wasm_store::WasmStore;
+    let sqlite = super::dao::SqliteStore;
+    if old.configuration_is_set() { sqlite.update_configuration(&old.get_configuration().map_err(|e| e.to_string())?).map_err(|e| e.to_string())?; }
+    if old.list_publishers().map_err(|e| e.to_string())?.is_empty() && old.list_schedules().map_err(|e| e.to_string())?.is_empty() { return Ok(()); }
+    sqlite.import_data(&old.export_data().map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+    log_note("Copied the browser database into SQLite");
+    Ok(())
+}
+
+// Run once before launch: start the worker, load data.db and bring its schema up to date
+pub async fn init() {
+    let loaded = match start_worker() {
+        Ok(worker) => {
+            WORKER.with(|w| *w.borrow_mut() = Some(worker));
+            load().await
+        }
+        Err(e) => Err(e),
+    };
+    let image = match loaded {
+        Ok(image) => image,
+        Err(e) => {
+            // Most often the database is open in another tab; running on an empty copy would hide the data
+            *OPEN_ERROR.lock().unwrap() = Some(e.as_string().unwrap_or_else(|| format!("{:?}", e)));
+            return;
+        }
+    };
+    let first_use = image.is_none();
+    let conn = match open(image) {
+        Ok(conn) => conn,
+        Err(e) => { *OPEN_ERROR.lock().unwrap() = Some(e.to_string()); return; }
+    };
+    // Every commit (migrations included) is written out by the worker
+    let _ = conn.commit_hook(Some(|| { queue_flush(); false }));
+    if let Err(e) = prepare(&conn) {
+        log_note(&format!("DB migrations error: {}", e));
+        *MIGRATION_ERROR.lock().unwrap() = Some(e);
+    }
+    let _ = DB.set(Mutex::new(conn));
+    if first_use && migration_error().is_none() && let Err(e) = copy_from_web_store().await {
+        web_sys::console::error_2(&"Could not copy the browser database into SQLite:".into(), &e.into());
+    }
+}
@@ -0,0 +1,313 @@
+// Storage abstraction shared by every backend.
+// Views talk to `crate::db::store()` and never to dao/wasm_store directly, so the
+// same code runs against SQLite (native), localStorage (web) or memory (tests).
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use crate::recurrence::Recurrence;
+use crate::scheduler::Weights;
+
+// ================= Domain types =================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Publisher {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub gender: String,
+    pub is_shift_manager: bool,
+    pub priority: i64,
+}
+
+impl Publisher {
+    // Label honoring Configuration.name_order ('first_last' | 'last_first')
+    pub fn display_name(&self, name_order: &str) -> String {
+        if name_order == "last_first" { format!("{} {}", self.last_name, self.first_name) } else { format!("{} {}", self.first_name, self.last_name) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: i64,
+    pub location: String,
+    pub start_hour: String,
+    pub end_hour: String,
+    pub weekday: String,
+    pub description: Option<String>,
+    pub num_publishers: i64,
+    pub num_shift_managers: i64,
+    pub num_brothers: i64,
+    pub num_sisters: i64,
+    #[serde(default)] pub recurrence: Recurrence, // which weeks on top of `weekday`; default every week
+}
+
+impl Schedule {
+    pub fn validate(&self) -> StoreResult<()> {
+        if self.num_shift_managers + self.num_brothers + self.num_sisters > self.num_publishers {
+            return Err(StoreError::Invalid("slot counts exceed total publishers".into()));
+        }
+        self.recurrence.validate().map_err(StoreError::Invalid)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Absence { pub id: i64, pub publisher_id: i64, pub start_date: NaiveDate, pub end_date: NaiveDate, pub description: Option<String> }
+
+// Days without shifts (holidays, assemblies, conventions): congregation-wide, or only at one location
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blackout { pub id: i64, pub start_date: NaiveDate, pub end_date: NaiveDate, pub location: Option<String>, pub reason: Option<String> }
+
+impl Blackout {
+    pub fn validate(&self) -> StoreResult<()> {
+        if self.end_date < self.start_date { return Err(StoreError::Invalid("blackout ends before it starts".into())); }
+        if self.location.as_deref().is_some_and(|l| l.trim().is_empty()) { return Err(StoreError::Invalid("blackout location is empty".into())); }
+        Ok(())
+    }
+
+    // Whether no shift should be held at `location` on `day`; locations compare like the scheduler does (trimmed, case-insensitive)
+    pub fn covers(&self, day: NaiveDate, location: &str) -> bool {
+        day >= self.start_date && day <= self.end_date
+            && self.location.as_deref().is_none_or(|l| l.trim().eq_ignore_ascii_case(location.trim()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shift {
+    pub id: i64,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub location: String,
+    pub publishers: Vec<i64>,
+    pub warning: Option<String>,
+    #[serde(default)] pub batch_id: Option<i64>, // generation run that created it; None when made by hand
+    #[serde(default)] pub edited: bool,          // changed by hand since it was generated
+}
+
+// One run of the shift generator whose draft was accepted: the range and options it ran with, so the
+// run can be reproduced from its seed or reverted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationBatch {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub seed: u64,
+    pub mode: String, // scheduler::Mode key
+    #[serde(default)] pub weights: Weights,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelationshipKind { Mandatory, Recommended }
+
+impl RelationshipKind {
+    pub fn as_str(&self) -> &'static str { match self { RelationshipKind::Mandatory => "mandatory", RelationshipKind::Recommended => "recommended" } }
+    pub fn from_db(s: &str) -> Self { match s { "mandatory" => RelationshipKind::Mandatory, _ => RelationshipKind::Recommended } }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Configuration {
+    pub congregation_name: String,
+    pub theme: String,
+    #[serde(default = "default_name_order")] pub name_order: String,   // 'first_last' | 'last_first'
+    #[serde(default = "default_week_start")] pub week_start: String,   // 'monday' | 'sunday'
+    #[serde(default = "default_language")] pub language: String,       // 'system' | 'en' | 'es' | 'fr' | 'de'
+    #[serde(default = "default_date_format")] pub date_format: String, // 'YYYY-MM-DD' | 'DD/MM/YYYY' | 'MM/DD/YYYY' | 'DD MMM YYYY'
+    #[serde(default)] pub weights: Weights, // shift generator scoring
+}
+
+pub fn default_name_order() -> String { "first_last".to_string() }
+pub fn default_week_start() -> String { "monday".to_string() }
+pub fn default_language() -> String { "system".to_string() }
+pub fn default_date_format() -> String { "YYYY-MM-DD".to_string() }
+
+impl Default for Configuration {
+    // Same values the native schema seeds the Configuration row with
+    fn default() -> Self {
+        Self {
+            congregation_name: "Congregation".into(),
+            theme: "System".into(),
+            name_order: default_name_order(),
+            week_start: default_week_start(),
+            language: default_language(),
+            date_format: default_date_format(),
+            weights: Weights::default(),
+        }
+    }
+}
+
+// ================= Export/Import =================
+// Every row except Configuration; the data part of an export file (see db::envelope)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(default)] pub publishers: Vec<Publisher>,
+    #[serde(default)] pub schedules: Vec<Schedule>,
+    #[serde(default)] pub availability: Vec<(i64, i64)>, // (publisher, schedule)
+    #[serde(default)] pub relationships: Vec<(i64, i64, RelationshipKind)>,
+    #[serde(default)] pub absences: Vec<Absence>,
+    #[serde(default)] pub shifts: Vec<Shift>,
+    #[serde(default)] pub blackouts: Vec<Blackout>,
+    #[serde(default)] pub generation_batches: Vec<GenerationBatch>,
+}
+
+// ================= Merge import =================
+// Publisher or schedule a merged row points at: one already stored, or one added by the same batch (index)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ref { Existing(i64), New(usize) }
+
+impl Ref {
+    pub fn resolve(self, new_ids: &[i64]) -> i64 { match self { Ref::Existing(id) => id, Ref::New(i) => new_ids[i] } }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeAbsence { pub publisher: Ref, pub start_date: NaiveDate, pub end_date: NaiveDate, pub description: Option<String> }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeShift { pub start: NaiveDateTime, pub end: NaiveDateTime, pub location: String, pub publishers: Vec<Ref>, pub warning: Option<String> }
+
+// Rows a merge import adds on top of the current data (see import::merge); ids of new publishers/schedules are ignored
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeBatch {
+    pub publishers: Vec<Publisher>,
+    pub schedules: Vec<Schedule>,
+    pub availability: Vec<(Ref, Ref)>, // (publisher, schedule)
+    pub relationships: Vec<(Ref, Ref, RelationshipKind)>,
+    pub absences: Vec<MergeAbsence>,
+    pub shifts: Vec<MergeShift>,
+    pub blackouts: Vec<Blackout>, // ids ignored
+}
+
+impl MergeBatch {
+    pub fn is_empty(&self) -> bool {
+        self.publishers.is_empty() && self.schedules.is_empty() && self.availability.is_empty()
+            && self.relationships.is_empty() && self.absences.is_empty() && self.shifts.is_empty() && self.blackouts.is_empty()
+    }
+}
+
+// ================= Errors =================
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreError {
+    Backend(String), // underlying storage failed
+    Invalid(String), // input rejected before reaching storage
+    Parse(String),   // stored or imported data could not be decoded
+    NotFound,
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Backend(e) => write!(f, "storage error: {}", e),
+            StoreError::Invalid(e) => write!(f, "invalid data: {}", e),
+            StoreError::Parse(e) => write!(f, "could not parse data: {}", e),
+            StoreError::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+#[cfg(any(all(feature = "native-db", not(target_arch = "wasm32")), all(feature = "opfs-db", target_arch = "wasm32")))]
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => StoreError::NotFound,
+            other => StoreError::Backend(other.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self { StoreError::Parse(e.to_string()) }
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+// ================= Store trait =================
+pub trait Store {
+    // Configuration
+    fn get_configuration(&self) -> StoreResult<Configuration>;
+    fn update_configuration(&self, cfg: &Configuration) -> StoreResult<()>;
+
+    // Publishers
+    fn list_publishers(&self) -> StoreResult<Vec<Publisher>>;
+    fn create_publisher(&self, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<i64>;
+    // All-or-nothing insert (CSV import); the ids of `rows` are ignored and the new ids returned in order
+    fn create_publishers(&self, rows: &[Publisher]) -> StoreResult<Vec<i64>>;
+    fn update_publisher(&self, id: i64, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<()>;
+    fn delete_publisher(&self, id: i64) -> StoreResult<()>;
+
+    // Schedules
+    fn list_schedules(&self) -> StoreResult<Vec<Schedule>>;
+    fn create_schedule(&self, s: &Schedule) -> StoreResult<i64>;
+    fn update_schedule(&self, s: &Schedule) -> StoreResult<()>;
+    fn delete_schedule(&self, id: i64) -> StoreResult<()>;
+
+    // Availability
+    fn set_publisher_availability(&self, publisher_id: i64, schedule_ids: &[i64]) -> StoreResult<()>;
+    fn list_availability_for_publisher(&self, publisher_id: i64) -> StoreResult<Vec<i64>>;
+    fn list_publishers_for_schedule(&self, schedule_id: i64) -> StoreResult<Vec<i64>>;
+
+    // Relationships
+    fn add_relationship(&self, a: i64, b: i64, kind: RelationshipKind) -> StoreResult<()>;
+    fn remove_relationship(&self, a: i64, b: i64) -> StoreResult<()>;
+    fn list_relationships_for_publisher(&self, p: i64) -> StoreResult<Vec<(i64, RelationshipKind)>>;
+
+    // Absences
+    fn list_future_absences(&self, today: NaiveDate) -> StoreResult<Vec<Absence>>;
+    fn create_absence(&self, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<i64>;
+    fn update_absence(&self, id: i64, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<()>;
+    fn delete_absence(&self, id: i64) -> StoreResult<()>;
+    fn is_absent_on(&self, publisher_id: i64, day: NaiveDate) -> StoreResult<bool>;
+    fn cleanup_expired_absences(&self, today: NaiveDate) -> StoreResult<usize>;
+
+    // Blackout dates
+    fn list_blackouts(&self) -> StoreResult<Vec<Blackout>>;
+    fn create_blackout(&self, b: &Blackout) -> StoreResult<i64>;
+    // All-or-nothing insert (date list import); the ids of `rows` are ignored and the new ids returned in order
+    fn create_blackouts(&self, rows: &[Blackout]) -> StoreResult<Vec<i64>>;
+    fn update_blackout(&self, b: &Blackout) -> StoreResult<()>;
+    fn delete_blackout(&self, id: i64) -> StoreResult<()>;
+
+    // Shifts
+    fn list_shifts_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<Vec<Shift>>;
+    fn create_shift(&self, start: NaiveDateTime, end: NaiveDateTime, location: &str, publishers: &[i64], warning: Option<&str>) -> StoreResult<i64>;
+    fn update_shift_publishers(&self, id: i64, publishers: &[i64], warning: Option<&str>) -> StoreResult<()>;
+    fn update_shift_datetime_location(&self, id: i64, start: NaiveDateTime, end: NaiveDateTime, location: &str, warning: Option<&str>) -> StoreResult<()>;
+    fn delete_shift(&self, id: i64) -> StoreResult<()>;
+    #[allow(dead_code)]
+    fn delete_shifts_in_range(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<usize>;
+
+    // Generation batches (newest first). Editing a shift through the update methods above marks it `edited`
+    fn list_generation_batches(&self) -> StoreResult<Vec<GenerationBatch>>;
+    // Store the run when `batch.id` is 0 and add `rows` linked to it (their ids are ignored), all in one
+    // transaction; returns the batch id, so later parts of the same draft join the same batch
+    fn save_generation(&self, batch: &GenerationBatch, rows: &[Shift]) -> StoreResult<i64>;
+    // Delete the run's shifts nobody edited and the batch itself; edited ones stay as hand-made shifts.
+    // Returns (removed, kept)
+    fn revert_generation(&self, id: i64) -> StoreResult<(usize, usize)>;
+
+    // Export/Import (excluding Configuration) and full reset
+    fn snapshot(&self) -> StoreResult<Snapshot>;
+    // Replace every row with `data` (ids kept) in one transaction
+    #[cfg_attr(not(any(feature = "native-db", target_arch = "wasm32")), allow(dead_code))]
+    fn restore(&self, data: &Snapshot) -> StoreResult<()>;
+    // Add a merge import's rows in one transaction, nothing is updated or deleted
+    #[cfg_attr(not(any(feature = "native-db", target_arch = "wasm32")), allow(dead_code))]
+    fn merge_data(&self, batch: &MergeBatch) -> StoreResult<()>;
+    fn reset_data(&self) -> StoreResult<()>;
+
+    // Derived helpers
+    fn export_data(&self) -> StoreResult<String> {
+        let congregation = self.get_configuration().map(|c| c.congregation_name).unwrap_or_default();
+        super::envelope::seal(&self.snapshot()?, &congregation)
+    }
+    // The file is upgraded, checked and validated before any row is replaced
+    #[cfg_attr(not(any(feature = "native-db", target_arch = "wasm32")), allow(dead_code))]
+    fn import_data(&self, json: &str) -> StoreResult<()> { self.restore(&super::envelope::open(json)?) }
+    fn configuration_is_set(&self) -> bool {
+        self.get_configuration()
+            .map(|cfg| !cfg.congregation_name.trim().is_empty() && cfg.congregation_name != "Congregation")
+            .unwrap_or(false)
+    }
+    fn name_order(&self) -> String { self.get_configuration().map(|c| c.name_order).unwrap_or_else(|_| default_name_order()) }
+    fn week_start(&self) -> String { self.get_configuration().map(|c| c.week_start).unwrap_or_else(|_| default_week_start()) }
+    fn weights(&self) -> Weights { self.get_configuration().map(|c| c.weights).unwrap_or_default() }
+}
@@ -1,40 +1,37 @@
-use serde::{Serialize, Deserialize};
-use serde_json;
+use serde::{de::DeserializeOwned, Serialize, Deserialize};
+use serde_json::json;
+use wasm_bindgen::JsValue;
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
 use web_sys::{window, Storage};
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::db::indexed_db::{self as idb, Write};
+use crate::db::store::{self, Store, StoreError, StoreResult};
+
+pub use crate::db::store::{Configuration, Publisher, RelationshipKind, Schedule};
 
 const KEY_PUBLISHERS: &str = "dx_app_publishers";
 const KEY_CONFIGURATION: &str = "dx_app_configuration";
 
-fn storage() -> Storage { window().and_then(|w| w.local_storage().ok().flatten()).expect("localStorage") }
+// Version of the stored blob; bump it and append an upgrade step whenever WasmDb changes shape
+const SCHEMA_VERSION: u64 = 3;
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub struct Publisher {
-    pub id: i64,
-    pub first_name: String,
-    pub last_name: String,
-    pub gender: String,
-    pub is_shift_manager: bool,
-    pub priority: i64,
-}
+// UPGRADES[n] turns a version-n blob into version n+1
+const UPGRADES: &[fn(&mut serde_json::Value)] = &[
+    // 0 -> 1: blobs written before versioning; fields they lack fall back to serde defaults
+    |_| {},
+    // 1 -> 2: blackout dates added; older blobs have none
+    |_| {},
+    // 2 -> 3: generation batches added; older shifts have no batch and count as made by hand
+    |_| {},
+];
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub struct Schedule {
-    pub id: i64,
-    pub location: String,
-    pub start_hour: String,
-    pub end_hour: String,
-    pub weekday: String,
-    pub description: Option<String>,
-    pub num_publishers: i64,
-    pub num_shift_managers: i64,
-    pub num_brothers: i64,
-    pub num_sisters: i64,
-}
+fn storage() -> Storage { window().and_then(|w| w.local_storage().ok().flatten()).expect("localStorage") }
 
 #[derive(Default, Serialize, Deserialize)]
 struct WasmDb {
+    #[serde(default)]
+    schema_version: u64,
     publishers: Vec<Publisher>,
     next_id: i64,
     // schedules support
@@ -58,25 +55,177 @@ struct WasmDb {
     // relationships (a,b,kind) where a<b
     #[serde(default)]
     relationships: Vec<(i64, i64, String)>,
+    // blackout dates (the domain type; dates serialize as YYYY-MM-DD)
+    #[serde(default)]
+    blackouts: Vec<store::Blackout>,
+    #[serde(default)]
+    next_blackout_id: i64,
+    // generator runs (the domain type)
+    #[serde(default)]
+    generation_batches: Vec<store::GenerationBatch>,
+    #[serde(default)]
+    next_generation_batch_id: i64,
+}
+
+// Keep an unreadable blob under a side key instead of overwriting it with an empty database
+fn quarantine(key: &str, raw: &str, reason: &str) {
+    let side = format!("{}.quarantine.{}", key, js_sys::Date::now() as i64);
+    let _ = storage().set_item(&side, raw);
+    let _ = storage().remove_item(key);
+    web_sys::console::error_1(&format!("{} could not be loaded ({}); kept as {}", key, reason, side).into());
+}
+
+// Parse a stored blob and run the upgrade steps it is missing
+fn upgrade(raw: &str) -> Result<WasmDb, String> {
+    let mut value: serde_json::Value = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    let version = value.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > SCHEMA_VERSION { return Err(format!("written by a newer version (schema {})", version)); }
+    for step in &UPGRADES[version as usize..] { step(&mut value); }
+    value.as_object_mut().ok_or("not a JSON object")?.insert("schema_version".into(), SCHEMA_VERSION.into());
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn load() -> WasmDb {
+    let Some(raw) = storage().get_item(KEY_PUBLISHERS).ok().flatten() else { return WasmDb::default() };
+    upgrade(&raw).unwrap_or_else(|reason| { quarantine(KEY_PUBLISHERS, &raw, &reason); WasmDb::default() })
 }
 
 static DB: Lazy<Mutex<WasmDb>> = Lazy::new(|| {
-    let raw = storage().get_item(KEY_PUBLISHERS).ok().flatten();
-    let mut db: WasmDb = raw.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default();
+    let mut db = load();
+    db.schema_version = SCHEMA_VERSION;
     if db.next_id <= 0 { db.next_id = 1; }
     if db.next_schedule_id <= 0 { db.next_schedule_id = 1; }
     if db.next_absence_id <= 0 { db.next_absence_id = 1; }
     if db.next_shift_id <= 0 { db.next_shift_id = 1; }
+    if db.next_blackout_id <= 0 { db.next_blackout_id = 1; }
+    if db.next_generation_batch_id <= 0 { db.next_generation_batch_id = 1; }
     if db.relationships.is_empty() { /* keep default empty */ }
     let db = db;
     Mutex::new(db)
 });
 
-fn persist() {
-    if let Ok(db) = DB.lock() {
-        if let Ok(json) = serde_json::to_string(&*db) {
-            let _ = storage().set_item(KEY_PUBLISHERS, &json);
+// Counter record in the IndexedDB `meta` store
+fn meta_row(db: &WasmDb) -> Write {
+    Write::Put(idb::META, json!({
+        "key": "counters",
+        "schema_version": SCHEMA_VERSION,
+        "next_id": db.next_id,
+        "next_schedule_id": db.next_schedule_id,
+        "next_absence_id": db.next_absence_id,
+        "next_shift_id": db.next_shift_id,
+        "next_blackout_id": db.next_blackout_id,
+        "next_generation_batch_id": db.next_generation_batch_id,
+    }))
+}
+
+fn put<T: Serialize>(store: &'static str, row: &T) -> Write { Write::Put(store, serde_json::to_value(row).unwrap_or_default()) }
+fn availability_row(p: i64, s: i64) -> Write { Write::Put(idb::AVAILABILITY, json!({ "publisher_id": p, "schedule_id": s })) }
+fn relationship_row(a: i64, b: i64, kind: &str) -> Write { Write::Put(idb::RELATIONSHIPS, json!({ "a": a, "b": b, "kind": kind })) }
+
+// Every row, replacing whatever IndexedDB held (first migration, import)
+fn all_rows(db: &WasmDb) -> Vec<Write> {
+    let mut w: Vec<Write> = idb::ALL_STORES.iter().map(|s| Write::Clear(s)).collect();
+    w.extend(db.publishers.iter().map(|p| put(idb::PUBLISHERS, p)));
+    w.extend(db.schedules.iter().map(|s| put(idb::SCHEDULES, s)));
+    w.extend(db.availability.iter().map(|(p, s)| availability_row(*p, *s)));
+    w.extend(db.relationships.iter().map(|(a, b, k)| relationship_row(*a, *b, k)));
+    w.extend(db.absences.iter().map(|a| put(idb::ABSENCES, a)));
+    w.extend(db.shifts.iter().map(|s| put(idb::SHIFTS, s)));
+    w.extend(db.blackouts.iter().map(|b| put(idb::BLACKOUTS, b)));
+    w.extend(db.generation_batches.iter().map(|b| put(idb::GENERATION_BATCHES, b)));
+    w.push(meta_row(db));
+    w
+}
+
+// Rewrite the whole localStorage blob from memory
+fn write_blob(db: &WasmDb) -> Result<(), String> {
+    let json = serde_json::to_string(db).map_err(|e| e.to_string())?;
+    storage().set_item(KEY_PUBLISHERS, &json).map_err(|e| format!("{:?}", e))
+}
+
+// A failed IndexedDB transaction leaves memory ahead of disk: stop using IndexedDB and write everything
+// to the localStorage blob, which init() copies back into IndexedDB on the next start
+fn indexed_write_failed(e: JsValue) {
+    idb::close();
+    let detail = e.as_string().unwrap_or_else(|| format!("{:?}", e));
+    let res = DB.lock().map_err(|e| e.to_string()).and_then(|db| write_blob(&db));
+    match res {
+        Ok(()) => super::save_error(true, detail),
+        Err(blob) => super::save_error(false, format!("{}; {}", detail, blob)),
+    }
+}
+
+// Queue the changed rows to IndexedDB, or rewrite the localStorage blob when IndexedDB is unavailable
+fn persist(mut writes: Vec<Write>) {
+    let Ok(db) = DB.lock() else { return };
+    if idb::is_open() {
+        writes.push(meta_row(&db));
+        drop(db);
+        idb::queue(writes, indexed_write_failed);
+    } else if let Err(e) = write_blob(&db) {
+        drop(db);
+        super::save_error(false, e);
+    }
+}
+
+fn rows<T: DeserializeOwned>(store: &str, values: Vec<serde_json::Value>) -> Vec<T> {
+    values.into_iter().filter_map(|v| serde_json::from_value(v.clone()).map_err(|e| {
+        web_sys::console::error_1(&format!("Skipping unreadable {} row {}: {}", store, v, e).into());
+    }).ok()).collect()
+}
+
+async fn load_indexed() -> Result<Option<WasmDb>, JsValue> {
+    let meta = idb::read_all(idb::META).await?;
+    let Some(counters) = meta.iter().find(|m| m["key"] == "counters") else { return Ok(None) };
+    let counter = |k: &str| counters[k].as_i64().unwrap_or(1).max(1);
+    let pairs = |v: Vec<serde_json::Value>, a: &str, b: &str| v.iter().filter_map(|r| Some((r[a].as_i64()?, r[b].as_i64()?))).collect::<Vec<_>>();
+    let relationships = idb::read_all(idb::RELATIONSHIPS).await?.iter()
+        .filter_map(|r| Some((r["a"].as_i64()?, r["b"].as_i64()?, r["kind"].as_str()?.to_string()))).collect();
+    Ok(Some(WasmDb {
+        schema_version: SCHEMA_VERSION,
+        publishers: rows(idb::PUBLISHERS, idb::read_all(idb::PUBLISHERS).await?),
+        next_id: counter("next_id"),
+        schedules: rows(idb::SCHEDULES, idb::read_all(idb::SCHEDULES).await?),
+        next_schedule_id: counter("next_schedule_id"),
+        availability: pairs(idb::read_all(idb::AVAILABILITY).await?, "publisher_id", "schedule_id"),
+        absences: rows(idb::ABSENCES, idb::read_all(idb::ABSENCES).await?),
+        next_absence_id: counter("next_absence_id"),
+        shifts: rows(idb::SHIFTS, idb::read_all(idb::SHIFTS).await?),
+        next_shift_id: counter("next_shift_id"),
+        relationships,
+        blackouts: rows(idb::BLACKOUTS, idb::read_all(idb::BLACKOUTS).await?),
+        next_blackout_id: counter("next_blackout_id"),
+        generation_batches: rows(idb::GENERATION_BATCHES, idb::read_all(idb::GENERATION_BATCHES).await?),
+        next_generation_batch_id: counter("next_generation_batch_id"),
+    }))
+}
+
+// Run once before launch: load from IndexedDB, or on its first use copy the localStorage blob into it.
+// The blob is kept under a `.migrated` key. Without IndexedDB the app stays on localStorage.
+pub async fn init() {
+    let res = match idb::open().await {
+        // A blob still in localStorage is newer than IndexedDB: it was never copied over, or an IndexedDB
+        // write failed last session and everything went to the blob instead
+        Ok(()) if storage().get_item(KEY_PUBLISHERS).ok().flatten().is_some() => Ok(None),
+        Ok(()) => load_indexed().await,
+        Err(e) => Err(e),
+    };
+    let res = match res {
+        Ok(Some(db)) => { *DB.lock().unwrap() = db; Ok(()) }
+        Ok(None) => {
+            let writes = all_rows(&DB.lock().unwrap());
+            idb::apply(writes).await.map(|_| {
+                if let Some(raw) = storage().get_item(KEY_PUBLISHERS).ok().flatten() {
+                    let _ = storage().set_item(&format!("{}.migrated", KEY_PUBLISHERS), &raw);
+                    let _ = storage().remove_item(KEY_PUBLISHERS);
+                }
+            })
         }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = res {
+        idb::close();
+        web_sys::console::error_2(&"IndexedDB unavailable, keeping data in localStorage:".into(), &e);
     }
 }
 
@@ -91,34 +240,60 @@ pub fn create_publisher(first: &str, last: &str, gender: &str, is_shift_manager:
     let mut db = DB.lock().unwrap();
     let id = db.next_id;
     db.next_id += 1;
-    db.publishers.push(Publisher { id, first_name: first.into(), last_name: last.into(), gender: gender.into(), is_shift_manager, priority });
+    let p = Publisher { id, first_name: first.into(), last_name: last.into(), gender: gender.into(), is_shift_manager, priority };
+    db.publishers.push(p.clone());
     drop(db);
-    persist();
+    persist(vec![put(idb::PUBLISHERS, &p)]);
     id
 }
 
+// Several publishers in one IndexedDB transaction (CSV import)
+pub fn create_publishers(rows: &[Publisher]) -> Vec<i64> {
+    let mut db = DB.lock().unwrap();
+    let mut ids = Vec::with_capacity(rows.len());
+    let mut writes = Vec::with_capacity(rows.len());
+    for p in rows {
+        let p = Publisher { id: db.next_id, ..p.clone() };
+        db.next_id += 1;
+        ids.push(p.id);
+        writes.push(put(idb::PUBLISHERS, &p));
+        db.publishers.push(p);
+    }
+    drop(db);
+    persist(writes);
+    ids
+}
+
 pub fn delete_publisher(id: i64) {
     let mut db = DB.lock().unwrap();
+    let mut writes = vec![Write::Delete(idb::PUBLISHERS, json!(id))];
     db.publishers.retain(|p| p.id != id);
     // cascade remove availability for this publisher
+    writes.extend(db.availability.iter().filter(|(p, _)| *p == id).map(|(p, s)| Write::Delete(idb::AVAILABILITY, json!([p, s]))));
     db.availability.retain(|(p, _s)| *p != id);
     // cascade remove absences for this publisher
+    writes.extend(db.absences.iter().filter(|a| a.publisher_id == id).map(|a| Write::Delete(idb::ABSENCES, json!(a.id))));
     db.absences.retain(|a| a.publisher_id != id);
+    // cascade remove relationships involving this publisher (ON DELETE CASCADE in the SQLite schema)
+    writes.extend(db.relationships.iter().filter(|(a, b, _)| *a == id || *b == id).map(|(a, b, _)| Write::Delete(idb::RELATIONSHIPS, json!([a, b]))));
+    db.relationships.retain(|(a, b, _)| *a != id && *b != id);
     drop(db);
-    persist();
+    persist(writes);
 }
 
 pub fn update_publisher(id: i64, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) {
     let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
     if let Some(p) = db.publishers.iter_mut().find(|p| p.id == id) {
         p.first_name = first.to_string();
         p.last_name = last.to_string();
         p.gender = gender.to_string();
         p.is_shift_manager = is_shift_manager;
         p.priority = priority;
+        writes.push(put(idb::PUBLISHERS, p));
     }
     drop(db);
-    persist();
+    persist(writes);
 }
 
 // ================= Schedules (web) =================
@@ -128,36 +303,39 @@ pub fn list_schedules() -> Vec<Schedule> {
     v
 }
 
-pub fn get_name_order() -> String { get_configuration().map(|c| c.name_order).unwrap_or_else(|| "first_last".into()) }
-
 pub fn create_schedule(s: &Schedule) -> i64 {
     let mut db = DB.lock().unwrap();
     let id = db.next_schedule_id;
     db.next_schedule_id += 1;
     let mut new_s = s.clone();
     new_s.id = id;
+    let row = put(idb::SCHEDULES, &new_s);
     db.schedules.push(new_s);
     drop(db);
-    persist();
+    persist(vec![row]);
     id
 }
 
 pub fn update_schedule(s: &Schedule) {
     let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
     if let Some(existing) = db.schedules.iter_mut().find(|x| x.id == s.id) {
         *existing = s.clone();
+        writes.push(put(idb::SCHEDULES, s));
     }
     drop(db);
-    persist();
+    persist(writes);
 }
 
 pub fn delete_schedule(id: i64) {
     let mut db = DB.lock().unwrap();
+    let mut writes = vec![Write::Delete(idb::SCHEDULES, json!(id))];
     db.schedules.retain(|x| x.id != id);
     // cascade remove availability entries with this schedule
+    writes.extend(db.availability.iter().filter(|(_, s)| *s == id).map(|(p, s)| Write::Delete(idb::AVAILABILITY, json!([p, s]))));
     db.availability.retain(|(_p, s)| *s != id);
     drop(db);
-    persist();
+    persist(writes);
 }
 
 // ================= Availability =================
@@ -167,10 +345,14 @@ pub fn list_availability_for_publisher(publisher_id: i64) -> Vec<i64> {
 
 pub fn set_publisher_availability(publisher_id: i64, schedule_ids: &[i64]) {
     let mut db = DB.lock().unwrap();
+    let mut writes: Vec<Write> = db.availability.iter().filter(|(p, _)| *p == publisher_id).map(|(p, s)| Write::Delete(idb::AVAILABILITY, json!([p, s]))).collect();
     db.availability.retain(|(p, _)| *p != publisher_id);
-    for sid in schedule_ids { db.availability.push((publisher_id, *sid)); }
+    for sid in schedule_ids {
+        db.availability.push((publisher_id, *sid));
+        writes.push(availability_row(publisher_id, *sid));
+    }
     drop(db);
-    persist();
+    persist(writes);
 }
 
 pub fn list_publishers_for_schedule(schedule_id: i64) -> Vec<i64> {
@@ -178,14 +360,6 @@ pub fn list_publishers_for_schedule(schedule_id: i64) -> Vec<i64> {
 }
 
 // ================= Relationships =================
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub enum RelationshipKind { Mandatory, Recommended }
-
-impl RelationshipKind {
-    fn as_str(&self) -> &'static str { match self { RelationshipKind::Mandatory => "mandatory", RelationshipKind::Recommended => "recommended" } }
-    fn from_str(s: &str) -> Self { match s { "mandatory" => RelationshipKind::Mandatory, _ => RelationshipKind::Recommended } }
-}
-
 pub fn add_relationship(a: i64, b: i64, kind: RelationshipKind) {
     if a == b { return; }
     let (x,y) = if a<b {(a,b)} else {(b,a)};
@@ -193,7 +367,7 @@ pub fn add_relationship(a: i64, b: i64, kind: RelationshipKind) {
     if let Some(row) = db.relationships.iter_mut().find(|(aa,bb,_)| *aa==x && *bb==y) { row.2 = kind.as_str().to_string(); }
     else { db.relationships.push((x,y, kind.as_str().to_string())); }
     drop(db);
-    persist();
+    persist(vec![relationship_row(x, y, kind.as_str())]);
 }
 
 pub fn remove_relationship(a: i64, b: i64) {
@@ -202,19 +376,20 @@ pub fn remove_relationship(a: i64, b: i64) {
     let mut db = DB.lock().unwrap();
     db.relationships.retain(|(aa,bb,_)| !(*aa==x && *bb==y));
     drop(db);
-    persist();
+    persist(vec![Write::Delete(idb::RELATIONSHIPS, json!([x, y]))]);
 }
 
 pub fn list_relationships_for_publisher(p: i64) -> Vec<(i64, RelationshipKind)> {
     let db = DB.lock().unwrap();
     db.relationships.iter().filter_map(|(a,b,k)| {
-        if *a==p { Some((*b, RelationshipKind::from_str(k))) }
-        else if *b==p { Some((*a, RelationshipKind::from_str(k))) }
+        if *a==p { Some((*b, RelationshipKind::from_db(k))) }
+        else if *b==p { Some((*a, RelationshipKind::from_db(k))) }
         else { None }
     }).collect()
 }
 
 // ================= Absences =================
+// Stored row: dates kept as YYYY-MM-DD strings
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Absence { pub id: i64, pub publisher_id: i64, pub start_date: String, pub end_date: String, pub description: Option<String> }
 
@@ -230,38 +405,42 @@ pub fn list_future_absences(today: &str) -> Vec<Absence> {
 pub fn cleanup_expired_absences(today: &str) {
     let mut db = DB.lock().unwrap();
     let t = today.to_string();
+    let writes: Vec<Write> = db.absences.iter().filter(|a| a.end_date < t).map(|a| Write::Delete(idb::ABSENCES, json!(a.id))).collect();
     db.absences.retain(|a| a.end_date >= t);
     drop(db);
-    persist();
+    persist(writes);
 }
 
 pub fn create_absence(publisher_id: i64, start_date: &str, end_date: &str, description: Option<&str>) -> i64 {
     let mut db = DB.lock().unwrap();
     let id = db.next_absence_id;
     db.next_absence_id += 1;
-    db.absences.push(Absence { id, publisher_id, start_date: start_date.to_string(), end_date: end_date.to_string(), description: description.map(|s| s.to_string()) });
+    let a = Absence { id, publisher_id, start_date: start_date.to_string(), end_date: end_date.to_string(), description: description.map(|s| s.to_string()) };
+    db.absences.push(a.clone());
     drop(db);
-    persist();
+    persist(vec![put(idb::ABSENCES, &a)]);
     id
 }
 
 pub fn update_absence(id: i64, publisher_id: i64, start_date: &str, end_date: &str, description: Option<&str>) {
     let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
     if let Some(a) = db.absences.iter_mut().find(|x| x.id == id) {
         a.publisher_id = publisher_id;
         a.start_date = start_date.to_string();
         a.end_date = end_date.to_string();
         a.description = description.map(|s| s.to_string());
+        writes.push(put(idb::ABSENCES, a));
     }
     drop(db);
-    persist();
+    persist(writes);
 }
 
 pub fn delete_absence(id: i64) {
     let mut db = DB.lock().unwrap();
     db.absences.retain(|a| a.id != id);
     drop(db);
-    persist();
+    persist(vec![Write::Delete(idb::ABSENCES, json!(id))]);
 }
 
 // ================= Shifts (web) =================
@@ -273,6 +452,10 @@ pub struct Shift {
     pub location: String,
     pub publishers: Vec<i64>,
     pub warning: Option<String>,
+    #[serde(default)]
+    pub batch_id: Option<i64>,
+    #[serde(default)]
+    pub edited: bool,
 }
 
 pub fn list_shifts_between(start: &str, end: &str) -> Vec<Shift> {
@@ -288,52 +471,59 @@ pub fn create_shift(start: &str, end: &str, location: &str, publishers: &[i64],
     let mut db = DB.lock().unwrap();
     let id = db.next_shift_id;
     db.next_shift_id += 1;
-    db.shifts.push(Shift { id, start_datetime: start.to_string(), end_datetime: end.to_string(), location: location.to_string(), publishers: publishers.to_vec(), warning: warning.map(|s| s.to_string()) });
+    let sh = Shift { id, start_datetime: start.to_string(), end_datetime: end.to_string(), location: location.to_string(), publishers: publishers.to_vec(), warning: warning.map(|s| s.to_string()), batch_id: None, edited: false };
+    db.shifts.push(sh.clone());
     drop(db);
-    persist();
+    persist(vec![put(idb::SHIFTS, &sh)]);
     id
 }
 
-pub fn update_shift_publishers(id: i64, publishers: &[i64], warning: Option<&str>) {
+// Apply `f` to one shift and queue the updated row
+fn modify_shift(id: i64, f: impl FnOnce(&mut Shift)) {
     let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
     if let Some(sh) = db.shifts.iter_mut().find(|s| s.id == id) {
-        sh.publishers = publishers.to_vec();
-        sh.warning = warning.map(|s| s.to_string());
+        f(sh);
+        writes.push(put(idb::SHIFTS, sh));
     }
     drop(db);
-    persist();
+    persist(writes);
+}
+
+pub fn update_shift_publishers(id: i64, publishers: &[i64], warning: Option<&str>) {
+    modify_shift(id, |sh| {
+        sh.edited |= sh.publishers != publishers;
+        sh.publishers = publishers.to_vec();
+        sh.warning = warning.map(|s| s.to_string());
+    });
 }
 
 // Update shift start/end datetimes (web)
 #[allow(dead_code)]
 pub fn update_shift_datetime(id: i64, start: &str, end: &str, warning: Option<&str>) {
-    let mut db = DB.lock().unwrap();
-    if let Some(sh) = db.shifts.iter_mut().find(|s| s.id == id) {
+    modify_shift(id, |sh| {
+        sh.edited |= sh.start_datetime != start || sh.end_datetime != end;
         sh.start_datetime = start.to_string();
         sh.end_datetime = end.to_string();
         sh.warning = warning.map(|s| s.to_string());
-    }
-    drop(db);
-    persist();
+    });
 }
 
 pub fn update_shift_datetime_location(id: i64, start: &str, end: &str, location: &str, warning: Option<&str>) {
-    let mut db = DB.lock().unwrap();
-    if let Some(sh) = db.shifts.iter_mut().find(|s| s.id == id) {
+    modify_shift(id, |sh| {
+        sh.edited |= sh.start_datetime != start || sh.end_datetime != end || sh.location != location;
         sh.start_datetime = start.to_string();
         sh.end_datetime = end.to_string();
         sh.location = location.to_string();
         sh.warning = warning.map(|s| s.to_string());
-    }
-    drop(db);
-    persist();
+    });
 }
 
 pub fn delete_shift(id: i64) {
     let mut db = DB.lock().unwrap();
     db.shifts.retain(|s| s.id != id);
     drop(db);
-    persist();
+    persist(vec![Write::Delete(idb::SHIFTS, json!(id))]);
 }
 
 #[allow(dead_code)]
@@ -341,118 +531,237 @@ pub fn delete_shifts_in_range(start: &str, end: &str) -> usize {
     let mut db = DB.lock().unwrap();
     let s = start.to_string();
     let e = end.to_string();
-    let before = db.shifts.len();
-    db.shifts.retain(|sh| !(sh.start_datetime >= s && sh.end_datetime <= e));
-    let removed = before - db.shifts.len();
+    let in_range = |sh: &Shift| sh.start_datetime >= s && sh.end_datetime <= e;
+    let writes: Vec<Write> = db.shifts.iter().filter(|sh| in_range(sh)).map(|sh| Write::Delete(idb::SHIFTS, json!(sh.id))).collect();
+    db.shifts.retain(|sh| !in_range(sh));
+    let removed = writes.len();
     drop(db);
-    persist();
+    persist(writes);
     removed
 }
 
+// ================= Generation batches (web) =================
+pub fn list_generation_batches() -> Vec<store::GenerationBatch> {
+    let mut v = DB.lock().unwrap().generation_batches.clone();
+    v.sort_by_key(|b| std::cmp::Reverse(b.id));
+    v
+}
+
+// The run (unless already stored) and its shifts in one IndexedDB transaction
+pub fn save_generation(batch: &store::GenerationBatch, rows: &[store::Shift]) -> i64 {
+    let mut db = DB.lock().unwrap();
+    let mut writes = Vec::with_capacity(rows.len() + 1);
+    let batch_id = if batch.id > 0 { batch.id } else {
+        let id = db.next_generation_batch_id;
+        db.next_generation_batch_id += 1;
+        let b = store::GenerationBatch { id, ..batch.clone() };
+        writes.push(put(idb::GENERATION_BATCHES, &b));
+        db.generation_batches.push(b);
+        id
+    };
+    for s in rows {
+        let sh = Shift { id: db.next_shift_id, start_datetime: fmt_dt(s.start), end_datetime: fmt_dt(s.end), location: s.location.clone(), publishers: s.publishers.clone(), warning: s.warning.clone(), batch_id: Some(batch_id), edited: false };
+        db.next_shift_id += 1;
+        writes.push(put(idb::SHIFTS, &sh));
+        db.shifts.push(sh);
+    }
+    drop(db);
+    persist(writes);
+    batch_id
+}
+
+pub fn revert_generation(id: i64) -> (usize, usize) {
+    let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
+    let (mut removed, mut kept) = (0, 0);
+    for sh in db.shifts.iter_mut().filter(|s| s.batch_id == Some(id)) {
+        if sh.edited {
+            sh.batch_id = None;
+            writes.push(put(idb::SHIFTS, sh));
+            kept += 1;
+        } else {
+            writes.push(Write::Delete(idb::SHIFTS, json!(sh.id)));
+            removed += 1;
+        }
+    }
+    db.shifts.retain(|s| s.batch_id != Some(id));
+    db.generation_batches.retain(|b| b.id != id);
+    writes.push(Write::Delete(idb::GENERATION_BATCHES, json!(id)));
+    drop(db);
+    persist(writes);
+    (removed, kept)
+}
+
+// ================= Blackouts (web) =================
+pub fn list_blackouts() -> Vec<store::Blackout> {
+    let mut v = DB.lock().unwrap().blackouts.clone();
+    v.sort_by_key(|b| (b.start_date, b.end_date));
+    v
+}
+
+// Several blackouts in one IndexedDB transaction (date list import)
+pub fn create_blackouts(rows: &[store::Blackout]) -> Vec<i64> {
+    let mut db = DB.lock().unwrap();
+    let mut ids = Vec::with_capacity(rows.len());
+    let mut writes = Vec::with_capacity(rows.len());
+    for b in rows {
+        let b = store::Blackout { id: db.next_blackout_id, ..b.clone() };
+        db.next_blackout_id += 1;
+        ids.push(b.id);
+        writes.push(put(idb::BLACKOUTS, &b));
+        db.blackouts.push(b);
+    }
+    drop(db);
+    persist(writes);
+    ids
+}
+
+pub fn update_blackout(b: &store::Blackout) {
+    let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
+    if let Some(existing) = db.blackouts.iter_mut().find(|x| x.id == b.id) {
+        *existing = b.clone();
+        writes.push(put(idb::BLACKOUTS, b));
+    }
+    drop(db);
+    persist(writes);
+}
+
+pub fn delete_blackout(id: i64) {
+    let mut db = DB.lock().unwrap();
+    db.blackouts.retain(|b| b.id != id);
+    drop(db);
+    persist(vec![Write::Delete(idb::BLACKOUTS, json!(id))]);
+}
+
 pub fn is_absent_on(publisher_id: i64, ymd: &str) -> bool {
     let d = ymd.to_string();
     DB.lock().unwrap().absences.iter().any(|a| a.publisher_id == publisher_id && a.start_date <= d && a.end_date >= d)
 }
 
 // Configuration stored as separate JSON object to keep compatibility
-#[derive(Serialize, Deserialize, Clone)]
-pub struct Configuration {
-    pub congregation_name: String,
-    pub theme: String,
-    #[serde(default="default_name_order")] pub name_order: String,
-    #[serde(default="default_week_start")] pub week_start: String,
-    #[serde(default="default_language")] pub language: String,
-    #[serde(default="default_date_format")] pub date_format: String,
-}
-
-fn default_name_order() -> String { "first_last".to_string() }
-fn default_week_start() -> String { "monday".to_string() }
-fn default_language() -> String { "system".to_string() }
-fn default_date_format() -> String { "YYYY-MM-DD".to_string() }
-
 pub fn get_configuration() -> Option<Configuration> {
-    storage().get_item(KEY_CONFIGURATION).ok().flatten().and_then(|s| serde_json::from_str(&s).ok())
+    let raw = storage().get_item(KEY_CONFIGURATION).ok().flatten()?;
+    serde_json::from_str(&raw).map_err(|e| quarantine(KEY_CONFIGURATION, &raw, &e.to_string())).ok()
 }
 
-pub fn update_configuration(name: &str, theme: &str, name_order: &str, week_start: &str, language: &str, date_format: &str) {
+pub fn update_configuration(cfg: &Configuration) {
+    let or_default = |v: &str, d: fn() -> String| if v.is_empty() { d() } else { v.to_string() };
     let cfg = Configuration {
-        congregation_name: name.to_string(),
-        theme: theme.to_string(),
-        name_order: if name_order.is_empty() { default_name_order() } else { name_order.to_string() },
-    week_start: if week_start.is_empty() { default_week_start() } else { week_start.to_string() },
-    language: if language.is_empty() { default_language() } else { language.to_string() },
-    date_format: if date_format.is_empty() { default_date_format() } else { date_format.to_string() },
+        congregation_name: cfg.congregation_name.clone(),
+        theme: cfg.theme.clone(),
+        name_order: or_default(&cfg.name_order, store::default_name_order),
+        week_start: or_default(&cfg.week_start, store::default_week_start),
+        language: or_default(&cfg.language, store::default_language),
+        date_format: or_default(&cfg.date_format, store::default_date_format),
+        weights: cfg.weights,
     };
     if let Ok(json) = serde_json::to_string(&cfg) { let _ = storage().set_item(KEY_CONFIGURATION, &json); }
 }
 
-pub fn configuration_is_set() -> bool {
-    if let Some(cfg) = get_configuration() {
-    let name_ok = !cfg.congregation_name.trim().is_empty() && cfg.congregation_name != "Congregation";
-    // Accept any theme value, including "System"
-    return name_ok;
-    }
-    false
-}
-
 // Export/Import (excluding Configuration)
-#[derive(Serialize, Deserialize)]
-pub struct ExportPayload {
-    pub publishers: Vec<Publisher>,
-    pub next_id: i64,
-    #[serde(default)]
-    pub schedules: Vec<Schedule>,
-    #[serde(default)]
-    pub next_schedule_id: i64,
-    #[serde(default)]
-    pub availability: Vec<(i64, i64)>,
-    #[serde(default)]
-    pub absences: Vec<Absence>,
-    #[serde(default)]
-    pub next_absence_id: i64,
-    #[serde(default)]
-    pub shifts: Vec<Shift>,
-    #[serde(default)]
-    pub next_shift_id: i64,
-    #[serde(default)]
-    pub relationships: Vec<(i64, i64, String)>,
-}
-
-pub fn export_data() -> String {
-    let db = DB.lock().unwrap();
-    serde_json::to_string_pretty(&ExportPayload {
+pub fn snapshot() -> StoreResult<store::Snapshot> {
+    let db = DB.lock().map_err(|e| StoreError::Backend(e.to_string()))?;
+    Ok(store::Snapshot {
         publishers: db.publishers.clone(),
-        next_id: db.next_id,
         schedules: db.schedules.clone(),
-        next_schedule_id: db.next_schedule_id,
-    availability: db.availability.clone(),
-    absences: db.absences.clone(),
-    next_absence_id: db.next_absence_id,
-    shifts: db.shifts.clone(),
-    next_shift_id: db.next_shift_id,
-    relationships: db.relationships.clone(),
-    }).unwrap()
-}
-
-pub fn import_data(json: &str) -> bool {
-    if let Ok(payload) = serde_json::from_str::<ExportPayload>(json) {
-        if let Ok(mut db) = DB.lock() {
-            db.publishers = payload.publishers;
-            db.next_id = payload.next_id.max(1);
-            db.schedules = payload.schedules;
-            db.next_schedule_id = payload.next_schedule_id.max(1);
-            db.availability = payload.availability;
-            db.absences = payload.absences;
-            db.next_absence_id = payload.next_absence_id.max(1);
-            db.shifts = payload.shifts;
-            db.next_shift_id = payload.next_shift_id.max(1);
-            db.relationships = payload.relationships;
-            drop(db);
-            persist();
-            return true;
+        availability: db.availability.clone(),
+        relationships: db.relationships.iter().map(|(a, b, k)| (*a, *b, RelationshipKind::from_db(k))).collect(),
+        absences: db.absences.iter().cloned().map(absence_to_domain).collect::<StoreResult<_>>()?,
+        shifts: db.shifts.iter().cloned().map(shift_to_domain).collect::<StoreResult<_>>()?,
+        blackouts: db.blackouts.clone(),
+        generation_batches: db.generation_batches.clone(),
+    })
+}
+
+// Replace every row with the snapshot, keeping its ids; counters continue after the largest id
+pub fn restore(data: &store::Snapshot) -> bool {
+    let Ok(mut db) = DB.lock() else { return false };
+    let next = |ids: &mut dyn Iterator<Item = i64>| ids.max().unwrap_or(0) + 1;
+    db.publishers = data.publishers.clone();
+    db.next_id = next(&mut data.publishers.iter().map(|p| p.id));
+    db.schedules = data.schedules.clone();
+    db.next_schedule_id = next(&mut data.schedules.iter().map(|s| s.id));
+    db.availability = data.availability.clone();
+    db.relationships = data.relationships.iter().map(|(a, b, k)| (*a.min(b), *a.max(b), k.as_str().to_string())).collect();
+    db.absences = data.absences.iter().map(|a| Absence { id: a.id, publisher_id: a.publisher_id, start_date: a.start_date.to_string(), end_date: a.end_date.to_string(), description: a.description.clone() }).collect();
+    db.next_absence_id = next(&mut data.absences.iter().map(|a| a.id));
+    db.shifts = data.shifts.iter().map(|s| Shift { id: s.id, start_datetime: s.start.format(DATETIME_FMT).to_string(), end_datetime: s.end.format(DATETIME_FMT).to_string(), location: s.location.clone(), publishers: s.publishers.clone(), warning: s.warning.clone(), batch_id: s.batch_id, edited: s.edited }).collect();
+    db.next_shift_id = next(&mut data.shifts.iter().map(|s| s.id));
+    db.blackouts = data.blackouts.clone();
+    db.next_blackout_id = next(&mut data.blackouts.iter().map(|b| b.id));
+    db.generation_batches = data.generation_batches.clone();
+    db.next_generation_batch_id = next(&mut data.generation_batches.iter().map(|b| b.id));
+    let writes = all_rows(&db);
+    drop(db);
+    persist(writes);
+    true
+}
+
+// Add a merge batch and queue its rows in one IndexedDB transaction (existing rows are kept as they are)
+pub fn merge_data(batch: &store::MergeBatch) {
+    let mut db = DB.lock().unwrap();
+    let mut writes = Vec::new();
+    let mut pids = Vec::with_capacity(batch.publishers.len());
+    for p in &batch.publishers {
+        let p = Publisher { id: db.next_id, ..p.clone() };
+        db.next_id += 1;
+        pids.push(p.id);
+        writes.push(put(idb::PUBLISHERS, &p));
+        db.publishers.push(p);
+    }
+    let mut sids = Vec::with_capacity(batch.schedules.len());
+    for s in &batch.schedules {
+        let s = Schedule { id: db.next_schedule_id, ..s.clone() };
+        db.next_schedule_id += 1;
+        sids.push(s.id);
+        writes.push(put(idb::SCHEDULES, &s));
+        db.schedules.push(s);
+    }
+    for (p, s) in &batch.availability {
+        let row = (p.resolve(&pids), s.resolve(&sids));
+        if !db.availability.contains(&row) {
+            db.availability.push(row);
+            writes.push(availability_row(row.0, row.1));
+        }
+    }
+    for (a, b, kind) in &batch.relationships {
+        let (a, b) = (a.resolve(&pids), b.resolve(&pids));
+        let (x, y) = (a.min(b), a.max(b));
+        if x != y && !db.relationships.iter().any(|(aa, bb, _)| *aa == x && *bb == y) {
+            db.relationships.push((x, y, kind.as_str().to_string()));
+            writes.push(relationship_row(x, y, kind.as_str()));
         }
     }
-    false
+    for a in &batch.absences {
+        let a = Absence { id: db.next_absence_id, publisher_id: a.publisher.resolve(&pids), start_date: a.start_date.to_string(), end_date: a.end_date.to_string(), description: a.description.clone() };
+        db.next_absence_id += 1;
+        writes.push(put(idb::ABSENCES, &a));
+        db.absences.push(a);
+    }
+    for s in &batch.shifts {
+        let sh = Shift {
+            id: db.next_shift_id,
+            start_datetime: s.start.format(DATETIME_FMT).to_string(),
+            end_datetime: s.end.format(DATETIME_FMT).to_string(),
+            location: s.location.clone(),
+            publishers: s.publishers.iter().map(|r| r.resolve(&pids)).collect(),
+            warning: s.warning.clone(),
+            batch_id: None,
+            edited: false,
+        };
+        db.next_shift_id += 1;
+        writes.push(put(idb::SHIFTS, &sh));
+        db.shifts.push(sh);
+    }
+    for b in &batch.blackouts {
+        let b = store::Blackout { id: db.next_blackout_id, ..b.clone() };
+        db.next_blackout_id += 1;
+        writes.push(put(idb::BLACKOUTS, &b));
+        db.blackouts.push(b);
+    }
+    drop(db);
+    persist(writes);
 }
 
 // Wipe all data (except configuration)
@@ -468,10 +777,103 @@ pub fn reset_data() -> bool {
         db.shifts.clear();
         db.next_shift_id = 1;
         db.relationships.clear();
+        db.blackouts.clear();
+        db.next_blackout_id = 1;
+        db.generation_batches.clear();
+        db.next_generation_batch_id = 1;
+        let writes = all_rows(&db);
         drop(db);
-        persist();
+        persist(writes);
     // also clear configuration entry so app shows landing again
     let _ = storage().remove_item(KEY_CONFIGURATION);
         true
     } else { false }
 }
+
+// ================= Store implementation =================
+// Converts between the string-dated storage rows above and the shared domain types.
+const DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn fmt_dt(dt: NaiveDateTime) -> String { dt.format(DATETIME_FMT).to_string() }
+
+fn absence_to_domain(a: Absence) -> StoreResult<store::Absence> {
+    let parse = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| StoreError::Parse(e.to_string()));
+    Ok(store::Absence { id: a.id, publisher_id: a.publisher_id, start_date: parse(&a.start_date)?, end_date: parse(&a.end_date)?, description: a.description })
+}
+
+fn shift_to_domain(s: Shift) -> StoreResult<store::Shift> {
+    let parse = |v: &str| NaiveDateTime::parse_from_str(v, DATETIME_FMT).map_err(|e| StoreError::Parse(e.to_string()));
+    Ok(store::Shift { id: s.id, start: parse(&s.start_datetime)?, end: parse(&s.end_datetime)?, location: s.location, publishers: s.publishers, warning: s.warning, batch_id: s.batch_id, edited: s.edited })
+}
+
+pub struct WasmStore;
+
+impl Store for WasmStore {
+    fn get_configuration(&self) -> StoreResult<Configuration> { get_configuration().ok_or(StoreError::NotFound) }
+    fn update_configuration(&self, cfg: &Configuration) -> StoreResult<()> { update_configuration(cfg); Ok(()) }
+
+    fn list_publishers(&self) -> StoreResult<Vec<Publisher>> { Ok(list_publishers()) }
+    fn create_publisher(&self, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<i64> { Ok(create_publisher(first, last, gender, is_shift_manager, priority)) }
+    fn create_publishers(&self, rows: &[Publisher]) -> StoreResult<Vec<i64>> { Ok(create_publishers(rows)) }
+    fn update_publisher(&self, id: i64, first: &str, last: &str, gender: &str, is_shift_manager: bool, priority: i64) -> StoreResult<()> { update_publisher(id, first, last, gender, is_shift_manager, priority); Ok(()) }
+    fn delete_publisher(&self, id: i64) -> StoreResult<()> { delete_publisher(id); Ok(()) }
+
+    fn list_schedules(&self) -> StoreResult<Vec<Schedule>> { Ok(list_schedules()) }
+    fn create_schedule(&self, s: &Schedule) -> StoreResult<i64> { s.validate()?; Ok(create_schedule(s)) }
+    fn update_schedule(&self, s: &Schedule) -> StoreResult<()> { s.validate()?; update_schedule(s); Ok(()) }
+    fn delete_schedule(&self, id: i64) -> StoreResult<()> { delete_schedule(id); Ok(()) }
+
+    fn set_publisher_availability(&self, publisher_id: i64, schedule_ids: &[i64]) -> StoreResult<()> { set_publisher_availability(publisher_id, schedule_ids); Ok(()) }
+    fn list_availability_for_publisher(&self, publisher_id: i64) -> StoreResult<Vec<i64>> { Ok(list_availability_for_publisher(publisher_id)) }
+    fn list_publishers_for_schedule(&self, schedule_id: i64) -> StoreResult<Vec<i64>> { Ok(list_publishers_for_schedule(schedule_id)) }
+
+    fn add_relationship(&self, a: i64, b: i64, kind: RelationshipKind) -> StoreResult<()> {
+        if a == b { return Err(StoreError::Invalid("a publisher cannot be related to themselves".into())); }
+        add_relationship(a, b, kind);
+        Ok(())
+    }
+    fn remove_relationship(&self, a: i64, b: i64) -> StoreResult<()> { remove_relationship(a, b); Ok(()) }
+    fn list_relationships_for_publisher(&self, p: i64) -> StoreResult<Vec<(i64, RelationshipKind)>> { Ok(list_relationships_for_publisher(p)) }
+
+    fn list_future_absences(&self, today: NaiveDate) -> StoreResult<Vec<store::Absence>> {
+        let mut v = list_future_absences(&today.to_string()).into_iter().map(absence_to_domain).collect::<StoreResult<Vec<_>>>()?;
+        v.sort_by_key(|a| a.start_date);
+        Ok(v)
+    }
+    fn create_absence(&self, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<i64> { Ok(create_absence(publisher_id, &start.to_string(), &end.to_string(), desc)) }
+    fn update_absence(&self, id: i64, publisher_id: i64, start: NaiveDate, end: NaiveDate, desc: Option<&str>) -> StoreResult<()> { update_absence(id, publisher_id, &start.to_string(), &end.to_string(), desc); Ok(()) }
+    fn delete_absence(&self, id: i64) -> StoreResult<()> { delete_absence(id); Ok(()) }
+    fn is_absent_on(&self, publisher_id: i64, day: NaiveDate) -> StoreResult<bool> { Ok(is_absent_on(publisher_id, &day.to_string())) }
+    fn cleanup_expired_absences(&self, today: NaiveDate) -> StoreResult<usize> {
+        let before = DB.lock().unwrap().absences.len();
+        cleanup_expired_absences(&today.to_string());
+        Ok(before - DB.lock().unwrap().absences.len())
+    }
+
+    fn list_blackouts(&self) -> StoreResult<Vec<store::Blackout>> { Ok(list_blackouts()) }
+    fn create_blackout(&self, b: &store::Blackout) -> StoreResult<i64> { self.create_blackouts(std::slice::from_ref(b)).map(|ids| ids[0]) }
+    fn create_blackouts(&self, rows: &[store::Blackout]) -> StoreResult<Vec<i64>> { for b in rows { b.validate()?; } Ok(create_blackouts(rows)) }
+    fn update_blackout(&self, b: &store::Blackout) -> StoreResult<()> { b.validate()?; update_blackout(b); Ok(()) }
+    fn delete_blackout(&self, id: i64) -> StoreResult<()> { delete_blackout(id); Ok(()) }
+
+    fn list_shifts_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<Vec<store::Shift>> { list_shifts_between(&fmt_dt(start), &fmt_dt(end)).into_iter().map(shift_to_domain).collect() }
+    fn create_shift(&self, start: NaiveDateTime, end: NaiveDateTime, location: &str, publishers: &[i64], warning: Option<&str>) -> StoreResult<i64> { Ok(create_shift(&fmt_dt(start), &fmt_dt(end), location, publishers, warning)) }
+    fn update_shift_publishers(&self, id: i64, publishers: &[i64], warning: Option<&str>) -> StoreResult<()> { update_shift_publishers(id, publishers, warning); Ok(()) }
+    fn update_shift_datetime_location(&self, id: i64, start: NaiveDateTime, end: NaiveDateTime, location: &str, warning: Option<&str>) -> StoreResult<()> { update_shift_datetime_location(id, &fmt_dt(start), &fmt_dt(end), location, warning); Ok(()) }
+    fn delete_shift(&self, id: i64) -> StoreResult<()> { delete_shift(id); Ok(()) }
+    fn delete_shifts_in_range(&self, start: NaiveDateTime, end: NaiveDateTime) -> StoreResult<usize> { Ok(delete_shifts_in_range(&fmt_dt(start), &fmt_dt(end))) }
+
+    fn list_generation_batches(&self) -> StoreResult<Vec<store::GenerationBatch>> { Ok(list_generation_batches()) }
+    fn save_generation(&self, batch: &store::GenerationBatch, rows: &[store::Shift]) -> StoreResult<i64> { Ok(save_generation(batch, rows)) }
+    fn revert_generation(&self, id: i64) -> StoreResult<(usize, usize)> { Ok(revert_generation(id)) }
+
+    fn snapshot(&self) -> StoreResult<store::Snapshot> { snapshot() }
+    fn restore(&self, data: &store::Snapshot) -> StoreResult<()> { if restore(data) { Ok(()) } else { Err(StoreError::Backend("storage is locked".into())) } }
+    fn merge_data(&self, batch: &store::MergeBatch) -> StoreResult<()> {
+        for s in &batch.schedules { s.validate()?; }
+        for b in &batch.blackouts { b.validate()?; }
+        merge_data(batch);
+        Ok(())
+    }
+    fn reset_data(&self) -> StoreResult<()> { if reset_data() { Ok(()) } else { Err(StoreError::Backend("storage is locked".into())) } }
+}
@@ -0,0 +1,60 @@
+// Where native exports go. Desktop builds ask with the system save dialog; mobile builds have
+// no dialogs, so files are written to the app's export folder and handed to the share sheet
+// (the webview's Web Share API) for the user to send or store. Other native builds write to the
+// suggested path. Imports need nothing here: the webview's file input already opens the system
+// open dialog on desktop and the document picker on mobile.
+use std::path::{Path, PathBuf};
+
+#[cfg_attr(not(any(feature = "desktop", feature = "mobile")), allow(dead_code))]
+pub struct Filter {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub mime: &'static str,
+}
+
+pub const JSON: Filter = Filter { name: "JSON", extensions: &["json"], mime: "application/json" };
+pub const PDF: Filter = Filter { name: "PDF", extensions: &["pdf"], mime: "application/pdf" };
+pub const XLSX: Filter = Filter { name: "Excel", extensions: &["xlsx"], mime: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" };
+pub const ICS: Filter = Filter { name: "iCalendar", extensions: &["ics"], mime: "text/calendar" };
+
+// Documents folder where there is one; mobile platforms fall back to dx_app/exports next to the database
+pub fn export_dir() -> PathBuf {
+    dirs_next::document_dir().or_else(dirs_next::home_dir)
+        .unwrap_or_else(|| crate::db::native::db_file_path().with_file_name("exports"))
+}
+
+// Path to write an export to; None when the user cancelled the dialog
+#[cfg(feature = "desktop")]
+pub async fn save_path(suggested: &Path, filter: &Filter) -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new().add_filter(filter.name, filter.extensions);
+    if let Some(dir) = suggested.parent() { dialog = dialog.set_directory(dir); }
+    if let Some(name) = suggested.file_name() { dialog = dialog.set_file_name(name.to_string_lossy()); }
+    dialog.save_file().await.map(|f| f.path().to_path_buf())
+}
+
+#[cfg(not(feature = "desktop"))]
+pub async fn save_path(suggested: &Path, _filter: &Filter) -> Option<PathBuf> {
+    if let Some(dir) = suggested.parent() { let _ = std::fs::create_dir_all(dir); }
+    Some(suggested.to_path_buf())
+}
+
+// Offer written files to the share sheet; false when the webview can't share files (they stay in export_dir)
+#[cfg(feature = "mobile")]
+pub async fn share(paths: &[PathBuf], filter: &Filter) -> bool {
+    use base64::Engine;
+    let files: Vec<serde_json::Value> = paths.iter().filter_map(|p| {
+        let bytes = std::fs::read(p).ok()?;
+        let name = p.file_name()?.to_string_lossy().into_owned();
+        Some(serde_json::json!({ "name": name, "data": base64::engine::general_purpose::STANDARD.encode(bytes) }))
+    }).collect();
+    let js = format!(
+        r#"const files = {}.map(f => new File([Uint8Array.from(atob(f.data), c => c.charCodeAt(0))], f.name, {{ type: "{}" }}));
+        if (!navigator.canShare || !navigator.canShare({{ files }})) return false;
+        try {{ await navigator.share({{ files }}); return true; }} catch (e) {{ return e.name === "AbortError"; }}"#,
+        serde_json::Value::Array(files), filter.mime,
+    );
+    dioxus::prelude::document::eval(&js).await.ok().and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+#[cfg(not(feature = "mobile"))]
+pub async fn share(_paths: &[PathBuf], _filter: &Filter) -> bool { true }
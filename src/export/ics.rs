@@ -0,0 +1,115 @@
+// iCalendar (RFC 5545) export: the whole congregation calendar, one calendar per publisher or one per location.
+// Event UIDs are derived from the shift id, so importing a newer file updates events instead of duplicating them.
+// Times are floating (no time zone): a shift at 09:00 shows at 09:00 wherever the calendar is opened.
+use std::collections::{BTreeMap, HashMap};
+use chrono::{NaiveDateTime, Utc};
+use crate::db::store::Shift;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Split { All, Publisher, Location }
+
+impl Split {
+    pub fn from_key(key: &str) -> Split {
+        match key {
+            "publisher" => Split::Publisher,
+            "location" => Split::Location,
+            _ => Split::All,
+        }
+    }
+}
+
+pub struct Calendar {
+    pub suffix: Option<String>, // file name part for split calendars
+    pub body: String,
+}
+
+// Calendars for the shifts; split calendars are sorted by publisher or location name
+pub fn calendars(shifts: &[Shift], name_for: &HashMap<i64, String>, split: Split, title: &str) -> Vec<Calendar> {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let build = |label: Option<&str>, shifts: &[&Shift]| {
+        let name = label.map_or_else(|| title.to_string(), |l| format!("{} – {}", title, l));
+        Calendar { suffix: label.map(slug), body: calendar(&name, shifts, name_for, &stamp) }
+    };
+    match split {
+        Split::All => vec![build(None, &shifts.iter().collect::<Vec<_>>())],
+        Split::Publisher => {
+            let mut by_name: BTreeMap<&str, Vec<&Shift>> = BTreeMap::new();
+            for s in shifts {
+                for pid in &s.publishers {
+                    if let Some(name) = name_for.get(pid) { by_name.entry(name.as_str()).or_default().push(s); }
+                }
+            }
+            by_name.into_iter().map(|(name, list)| build(Some(name), &list)).collect()
+        }
+        Split::Location => {
+            let mut by_location: BTreeMap<&str, Vec<&Shift>> = BTreeMap::new();
+            for s in shifts { by_location.entry(s.location.as_str()).or_default().push(s); }
+            by_location.into_iter().map(|(loc, list)| build(Some(loc), &list)).collect()
+        }
+    }
+}
+
+// "<stem>.ics", or "<stem>_<suffix>.ics" for split calendars
+pub fn file_name(stem: &str, cal: &Calendar) -> String {
+    match &cal.suffix {
+        Some(suffix) if !suffix.is_empty() => format!("{}_{}.ics", stem, suffix),
+        _ => format!("{}.ics", stem),
+    }
+}
+
+fn calendar(name: &str, shifts: &[&Shift], name_for: &HashMap<i64, String>, stamp: &str) -> String {
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//ppoc-gen//Shifts//EN", "CALSCALE:GREGORIAN", "METHOD:PUBLISH"] { push(&mut out, line); }
+    push(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+    for s in shifts {
+        push(&mut out, "BEGIN:VEVENT");
+        push(&mut out, &format!("UID:shift-{}@ppoc-gen", s.id));
+        push(&mut out, &format!("DTSTAMP:{}", stamp));
+        push(&mut out, &format!("DTSTART:{}", local(s.start)));
+        push(&mut out, &format!("DTEND:{}", local(s.end)));
+        push(&mut out, &format!("SUMMARY:{}", escape(&s.location)));
+        push(&mut out, &format!("LOCATION:{}", escape(&s.location)));
+        push(&mut out, &format!("DESCRIPTION:{}", escape(&super::names(s, name_for).join(", "))));
+        push(&mut out, "END:VEVENT");
+    }
+    push(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn local(dt: NaiveDateTime) -> String { dt.format("%Y%m%dT%H%M%S").to_string() }
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n").replace('\r', "")
+}
+
+// Content lines end in CRLF and are folded at 75 octets (continuations start with a space)
+fn push(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+// File-name-safe form of a publisher or location name
+fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() { out.extend(c.to_lowercase()); } else if !out.ends_with('_') { out.push('_'); }
+    }
+    out.trim_matches('_').to_string()
+}
+
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+pub fn save(dir: &std::path::Path, stem: &str, calendars: &[Calendar]) -> std::io::Result<Vec<std::path::PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    calendars.iter().map(|cal| {
+        let path = dir.join(file_name(stem, cal));
+        std::fs::write(&path, &cal.body).map(|_| path)
+    }).collect()
+}
@@ -0,0 +1,89 @@
+// Shift exports shared by the web print window and the native file writers
+#![cfg_attr(not(any(feature = "native-db", target_arch = "wasm32")), allow(dead_code))]
+use std::collections::{BTreeMap, HashMap};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use crate::db::store::Shift;
+use crate::i18n::{format_date_ymd, t, weekday_name_for_date};
+
+// Native PDF renderer
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+pub mod pdf;
+// Excel workbook, written to a file on native and downloaded on web
+pub mod xlsx;
+// iCalendar files for calendar apps
+pub mod ics;
+
+// One shift as exported: "HH:MM–HH:MM", location, assigned names (sorted), generator warning
+pub struct ExportShift {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub time: String,
+    pub location: String,
+    pub names: Vec<String>,
+    pub warning: Option<String>,
+}
+
+pub struct ExportDay {
+    pub date: NaiveDate,
+    pub shifts: Vec<ExportShift>,
+}
+
+// Shifts between two dates (inclusive) and publisher display names in the configured name order
+pub fn shifts_between(start: NaiveDate, end: NaiveDate) -> (Vec<Shift>, HashMap<i64, String>) {
+    let store = crate::db::store();
+    let shifts = store.list_shifts_between(
+        NaiveDateTime::new(start, NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+        NaiveDateTime::new(end, NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+    ).unwrap_or_default();
+    let name_order = store.name_order();
+    let name_for = store.list_publishers().unwrap_or_default().iter().map(|p| (p.id, p.display_name(&name_order))).collect();
+    (shifts, name_for)
+}
+
+// Assigned names of a shift, sorted; unknown ids show as "#id"
+pub fn names(shift: &Shift, name_for: &HashMap<i64, String>) -> Vec<String> {
+    let mut names: Vec<String> = shift.publishers.iter().map(|pid| name_for.get(pid).cloned().unwrap_or_else(|| format!("#{}", pid))).collect();
+    names.sort();
+    names
+}
+
+// Shifts between two dates (inclusive) grouped by day and sorted by start time
+pub fn shifts_by_day(start: NaiveDate, end: NaiveDate) -> Vec<ExportDay> {
+    let (shifts, name_for) = shifts_between(start, end);
+    let mut by_day: BTreeMap<NaiveDate, Vec<ExportShift>> = BTreeMap::new();
+    for s in shifts {
+        let names = names(&s, &name_for);
+        by_day.entry(s.start.date()).or_default().push(ExportShift {
+            start: s.start,
+            end: s.end,
+            time: format!("{}–{}", s.start.format("%H:%M"), s.end.format("%H:%M")),
+            location: s.location,
+            names,
+            warning: s.warning,
+        });
+    }
+    by_day.into_iter().map(|(date, mut shifts)| {
+        shifts.sort_by_key(|s| s.start);
+        ExportDay { date, shifts }
+    }).collect()
+}
+
+// Suggested file for a native export: <export dir>/shifts_<start>_<end>.<ext>
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+pub fn default_path(start: &str, end: &str, ext: &str) -> std::path::PathBuf {
+    crate::dialogs::export_dir().join(format!("shifts_{}_{}.{}", start, end, ext))
+}
+
+// Document or calendar title for a range of YYYY-MM-DD dates, in the UI language and date format
+pub fn title(start: &str, end: &str) -> String {
+    format!("{} {} {} {}", t("shifts.export_heading_from"), format_date_ymd(start), t("shifts.export_heading_to"), format_date_ymd(end))
+}
+
+// Day heading in the UI language, e.g. "Monday, March 3 2025"; the word order comes from the language file
+pub fn human_date(d: NaiveDate) -> String {
+    t("shifts.export_day_format")
+        .replace("{weekday}", &weekday_name_for_date(d.year(), d.month(), d.day()))
+        .replace("{day}", &d.day().to_string())
+        .replace("{month}", &t(&format!("months.long.{}", d.month())))
+        .replace("{year}", &d.year().to_string())
+}
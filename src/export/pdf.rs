@@ -0,0 +1,245 @@
+// Shift export as a PDF, laid out like the web print window: a title, then per day a blue heading
+// with a rule and one bordered card per shift (time pill, location, assigned names).
+// Text is set in the base-14 Helvetica fonts with WinAnsi encoding, so no font is embedded;
+// characters outside that code page print as '?'.
+use std::path::Path;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use super::{human_date, ExportDay, ExportShift};
+
+// A4 in points, 18mm margins; sizes are the print stylesheet's px * 0.75
+const PAGE_W: f32 = 595.0;
+const PAGE_H: f32 = 842.0;
+const MARGIN: f32 = 51.0;
+const LEADING: f32 = 1.4;
+const TITLE_SIZE: f32 = 15.0;
+const DAY_SIZE: f32 = 12.0;
+const BODY_SIZE: f32 = 10.5;
+const NAMES_SIZE: f32 = 9.75;
+const DAY_GAP: f32 = 12.0;
+const CARD_GAP: f32 = 4.5;
+const CARD_PAD_X: f32 = 7.5;
+const CARD_PAD_Y: f32 = 6.0;
+const PILL_PAD_X: f32 = 4.5;
+const PILL_PAD_Y: f32 = 1.5;
+const PILL_H: f32 = BODY_SIZE * LEADING + 2.0 * PILL_PAD_Y;
+
+type Rgb = (f32, f32, f32);
+const INK: Rgb = (0.059, 0.090, 0.165);
+const MUTED: Rgb = (0.278, 0.333, 0.412);
+const ACCENT: Rgb = (0.145, 0.388, 0.922);
+const BORDER: Rgb = (0.886, 0.910, 0.941);
+const PILL_BORDER: Rgb = (0.796, 0.835, 0.882);
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+// Advance widths (1/1000 em) of ' '..='~' from the Helvetica and Helvetica-Bold AFM files
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+fn win_ansi(c: char) -> u8 {
+    match c {
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+        '€' => 0x80, '‚' => 0x82, 'ƒ' => 0x83, '„' => 0x84, '…' => 0x85, '†' => 0x86, '‡' => 0x87,
+        'ˆ' => 0x88, '‰' => 0x89, 'Š' => 0x8a, '‹' => 0x8b, 'Œ' => 0x8c, 'Ž' => 0x8e, '‘' => 0x91,
+        '’' => 0x92, '“' => 0x93, '”' => 0x94, '•' => 0x95, '–' => 0x96, '—' => 0x97, '˜' => 0x98,
+        '™' => 0x99, 'š' => 0x9a, '›' => 0x9b, 'œ' => 0x9c, 'ž' => 0x9e, 'Ÿ' => 0x9f,
+        _ => b'?',
+    }
+}
+
+// Non-ASCII glyphs are not in the tables; over-estimating them only wraps a little early
+fn width(s: &str, bold: bool, size: f32) -> f32 {
+    let table = if bold { &HELVETICA_BOLD } else { &HELVETICA };
+    let units: u32 = s.chars().map(|c| match c {
+        ' '..='~' => table[c as usize - 32] as u32,
+        c if c.is_uppercase() => 778,
+        _ => 611,
+    }).sum();
+    units as f32 * size / 1000.0
+}
+
+// Greedy word wrap; words wider than a line are broken between characters
+fn wrap(text: &str, max: f32, bold: bool, size: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if width(&candidate, bold, size) <= max { line = candidate; continue; }
+        if !line.is_empty() { lines.push(std::mem::take(&mut line)); }
+        for c in word.chars() {
+            if !line.is_empty() && width(&format!("{}{}", line, c), bold, size) > max { lines.push(std::mem::take(&mut line)); }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() { lines.push(line); }
+    lines
+}
+
+// A shift card measured for the content width
+struct Card<'a> {
+    time: &'a str,
+    pill_w: f32,
+    location: Vec<String>,
+    names: Vec<String>,
+    header_h: f32,
+    height: f32,
+}
+
+fn measure(s: &ExportShift) -> Card<'_> {
+    let inner = PAGE_W - 2.0 * MARGIN - 2.0 * CARD_PAD_X;
+    let pill_w = width(&s.time, true, BODY_SIZE) + 2.0 * PILL_PAD_X;
+    let location = wrap(&s.location, inner - pill_w - CARD_PAD_X, false, BODY_SIZE);
+    let names = wrap(&s.names.join(", "), inner, false, NAMES_SIZE);
+    let header_h = PILL_H.max(location.len() as f32 * BODY_SIZE * LEADING);
+    let names_h = if names.is_empty() { 0.0 } else { 3.0 + names.len() as f32 * NAMES_SIZE * LEADING };
+    Card { time: &s.time, pill_w, location, names, header_h, height: 2.0 * CARD_PAD_Y + header_h + names_h }
+}
+
+// Content streams of finished pages plus the page being filled; `y` is the top of the free space
+struct Layout {
+    pages: Vec<Vec<u8>>,
+    content: Content,
+    y: f32,
+}
+
+impl Layout {
+    fn room(&self) -> f32 { self.y - MARGIN }
+
+    fn new_page(&mut self) {
+        let done = std::mem::replace(&mut self.content, Content::new());
+        self.pages.push(done.finish().into_vec());
+        self.y = PAGE_H - MARGIN;
+    }
+
+    // One line of text in a line box whose top edge is at `top`
+    fn text(&mut self, s: &str, bold: bool, size: f32, color: Rgb, x: f32, top: f32) {
+        let baseline = top - (size * LEADING - 0.925 * size) / 2.0 - 0.718 * size;
+        let bytes: Vec<u8> = s.chars().map(win_ansi).collect();
+        self.content
+            .set_fill_rgb(color.0, color.1, color.2)
+            .begin_text()
+            .set_font(if bold { BOLD } else { REGULAR }, size)
+            .next_line(x, baseline)
+            .show(Str(&bytes))
+            .end_text();
+    }
+
+    fn rounded_rect(&mut self, x: f32, top: f32, w: f32, h: f32, r: f32, color: Rgb) {
+        let (y, k) = (top - h, r * 0.5523);
+        self.content
+            .set_stroke_rgb(color.0, color.1, color.2)
+            .set_line_width(0.75)
+            .move_to(x + r, y)
+            .line_to(x + w - r, y)
+            .cubic_to(x + w - r + k, y, x + w, y + r - k, x + w, y + r)
+            .line_to(x + w, y + h - r)
+            .cubic_to(x + w, y + h - r + k, x + w - r + k, y + h, x + w - r, y + h)
+            .line_to(x + r, y + h)
+            .cubic_to(x + r - k, y + h, x, y + h - r + k, x, y + h - r)
+            .line_to(x, y + r)
+            .cubic_to(x, y + r - k, x + r - k, y, x + r, y)
+            .close_path()
+            .stroke();
+    }
+
+    fn day_heading(&mut self, label: &str) {
+        self.y -= DAY_GAP;
+        self.text(label, true, DAY_SIZE, ACCENT, MARGIN, self.y);
+        self.y -= DAY_SIZE * LEADING + 3.0;
+        self.content
+            .set_stroke_rgb(BORDER.0, BORDER.1, BORDER.2)
+            .set_line_width(0.75)
+            .move_to(MARGIN, self.y)
+            .line_to(PAGE_W - MARGIN, self.y)
+            .stroke();
+        self.y -= 6.0;
+    }
+
+    fn card(&mut self, c: &Card) {
+        self.y -= CARD_GAP;
+        let top = self.y;
+        self.rounded_rect(MARGIN, top, PAGE_W - 2.0 * MARGIN, c.height, 6.0, BORDER);
+        let x = MARGIN + CARD_PAD_X;
+        let header_top = top - CARD_PAD_Y;
+        // Pill and location are vertically centred on the header row
+        let pill_top = header_top - (c.header_h - PILL_H) / 2.0;
+        self.rounded_rect(x, pill_top, c.pill_w, PILL_H, PILL_H / 2.0, PILL_BORDER);
+        self.text(c.time, true, BODY_SIZE, INK, x + PILL_PAD_X, pill_top - PILL_PAD_Y);
+        let line_h = BODY_SIZE * LEADING;
+        let loc_top = header_top - (c.header_h - c.location.len() as f32 * line_h) / 2.0;
+        for (i, line) in c.location.iter().enumerate() {
+            self.text(line, false, BODY_SIZE, MUTED, x + c.pill_w + CARD_PAD_X, loc_top - i as f32 * line_h);
+        }
+        let names_top = header_top - c.header_h - 3.0;
+        for (i, line) in c.names.iter().enumerate() {
+            self.text(line, false, NAMES_SIZE, INK, x, names_top - i as f32 * NAMES_SIZE * LEADING);
+        }
+        self.y = top - c.height;
+    }
+}
+
+pub fn render(title: &str, days: &[ExportDay]) -> Vec<u8> {
+    let mut layout = Layout { pages: Vec::new(), content: Content::new(), y: PAGE_H - MARGIN };
+    for line in wrap(title, PAGE_W - 2.0 * MARGIN, true, TITLE_SIZE) {
+        layout.text(&line, true, TITLE_SIZE, INK, MARGIN, layout.y);
+        layout.y -= TITLE_SIZE * LEADING;
+    }
+    layout.y -= 9.0;
+
+    let heading_h = DAY_GAP + DAY_SIZE * LEADING + 3.0 + 6.0;
+    for day in days {
+        let cards: Vec<Card> = day.shifts.iter().map(measure).collect();
+        let first = heading_h + cards.first().map_or(0.0, |c| CARD_GAP + c.height);
+        let total = heading_h + cards.iter().map(|c| CARD_GAP + c.height).sum::<f32>() + CARD_GAP;
+        // Days don't break across pages unless they are longer than a page; a heading never ends a page
+        if first > layout.room() || (total > layout.room() && total <= PAGE_H - 2.0 * MARGIN) { layout.new_page(); }
+        layout.day_heading(&human_date(day.date));
+        for card in &cards {
+            if CARD_GAP + card.height > layout.room() { layout.new_page(); }
+            layout.card(card);
+        }
+        layout.y -= CARD_GAP;
+    }
+    layout.new_page();
+
+    let catalog = Ref::new(1);
+    let tree = Ref::new(2);
+    let regular = Ref::new(3);
+    let bold = Ref::new(4);
+    let info = Ref::new(5);
+    let ids: Vec<(Ref, Ref)> = (0..layout.pages.len() as i32).map(|i| (Ref::new(6 + 2 * i), Ref::new(7 + 2 * i))).collect();
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog).pages(tree);
+    pdf.pages(tree).kids(ids.iter().map(|(page, _)| *page)).count(ids.len() as i32);
+    for ((page_id, content_id), data) in ids.iter().zip(&layout.pages) {
+        let mut page = pdf.page(*page_id);
+        page.parent(tree).media_box(Rect::new(0.0, 0.0, PAGE_W, PAGE_H)).contents(*content_id);
+        page.resources().fonts().pair(REGULAR, regular).pair(BOLD, bold);
+        page.finish();
+        pdf.stream(*content_id, data);
+    }
+    pdf.type1_font(regular).base_font(Name(b"Helvetica")).encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold).base_font(Name(b"Helvetica-Bold")).encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.document_info(info).title(TextStr(title));
+    pdf.finish()
+}
+
+pub fn save(path: &Path, title: &str, days: &[ExportDay]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) { std::fs::create_dir_all(parent)?; }
+    std::fs::write(path, render(title, days))
+}
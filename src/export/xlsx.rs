@@ -0,0 +1,61 @@
+// Shift export as an Excel workbook: one sheet per month ("YYYY-MM"), one row per shift.
+// Dates and times are real Excel values; rows of shifts with a generator warning are filled amber.
+use chrono::Datelike;
+use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook, XlsxError};
+use crate::i18n::{t, weekday_name_for_date};
+use super::ExportDay;
+
+const WARNING_FILL: u32 = 0xFEF3C7;
+const HEADER_FILL: u32 = 0xE2E8F0;
+const WIDTHS: [f64; 7] = [12.0, 14.0, 8.0, 8.0, 28.0, 50.0, 40.0];
+
+pub fn render(days: &[ExportDay]) -> Result<Vec<u8>, XlsxError> {
+    let headers = [
+        t("shifts.export_date"), t("shifts.export_weekday"), t("schedules.start"), t("schedules.end"),
+        t("schedules.location"), t("schedules.publishers"), t("shifts.export_warning"),
+    ];
+    let header = Format::new().set_bold().set_background_color(Color::RGB(HEADER_FILL)).set_border_bottom(FormatBorder::Thin);
+    // (date, time, text) formats for plain and highlighted rows
+    let plain = [Format::new().set_num_format("yyyy-mm-dd"), Format::new().set_num_format("hh:mm"), Format::new()];
+    let warned = plain.clone().map(|f| f.set_background_color(Color::RGB(WARNING_FILL)));
+
+    let mut workbook = Workbook::new();
+    for month in days.chunk_by(|a, b| (a.date.year(), a.date.month()) == (b.date.year(), b.date.month())) {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(month[0].date.format("%Y-%m").to_string())?;
+        for (col, (title, width)) in headers.iter().zip(WIDTHS).enumerate() {
+            sheet.write_string_with_format(0, col as u16, title, &header)?;
+            sheet.set_column_width(col as u16, width)?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        let mut row = 0u32;
+        for day in month {
+            let weekday = weekday_name_for_date(day.date.year(), day.date.month(), day.date.day());
+            for s in &day.shifts {
+                row += 1;
+                let [date, time, text] = if s.warning.is_some() { &warned } else { &plain };
+                sheet.write_date_with_format(row, 0, day.date, date)?;
+                sheet.write_string_with_format(row, 1, &weekday, text)?;
+                sheet.write_time_with_format(row, 2, s.start.time(), time)?;
+                sheet.write_time_with_format(row, 3, s.end.time(), time)?;
+                sheet.write_string_with_format(row, 4, &s.location, text)?;
+                sheet.write_string_with_format(row, 5, s.names.join(", "), text)?;
+                sheet.write_string_with_format(row, 6, s.warning.clone().unwrap_or_default(), text)?;
+            }
+        }
+        sheet.autofilter(0, 0, row, (WIDTHS.len() - 1) as u16)?;
+    }
+    // Excel refuses workbooks without sheets
+    if days.is_empty() {
+        let sheet = workbook.add_worksheet();
+        for (col, title) in headers.iter().enumerate() { sheet.write_string_with_format(0, col as u16, title, &header)?; }
+    }
+    workbook.save_to_buffer()
+}
+
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+pub fn save(path: &std::path::Path, days: &[ExportDay]) -> Result<(), XlsxError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) { std::fs::create_dir_all(parent)?; }
+    std::fs::write(path, render(days)?)?;
+    Ok(())
+}
@@ -170,6 +170,17 @@ pub fn weekday_name_for_date(y: i32, m: u32, d: u32) -> String {
     names[(idx - 1).min(6)].clone()
 }
 
+// Lowercase and strip the accents used in es/fr/de names, for search and duplicate matching
+pub fn normalize_for_search(s: &str) -> String {
+    s.to_lowercase()
+        .replace(['á', 'à', 'ä', 'â'], "a")
+        .replace(['é', 'è', 'ë', 'ê'], "e")
+        .replace(['í', 'ì', 'ï', 'î'], "i")
+        .replace(['ó', 'ò', 'ö', 'ô'], "o")
+        .replace(['ú', 'ù', 'ü', 'û'], "u")
+        .replace('ñ', "n")
+}
+
 // Format a YYYY-MM-DD string according to configured format and locale
 #[allow(dead_code)]
 pub fn format_date_ymd(ymd: &str) -> String {
@@ -226,18 +237,9 @@ pub fn apply_theme(theme: &str) {
 #[cfg(all(not(target_arch = "wasm32")))]
 pub fn apply_theme(_theme: &str) { /* no-op on native for now */ }
 
-#[cfg(target_arch = "wasm32")]
-fn get_cfg() -> Option<(String, String)> {
-    use crate::db::wasm_store as backend;
-    backend::get_configuration().map(|c| (c.language, c.date_format))
-}
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
 fn get_cfg() -> Option<(String, String)> {
-    use crate::db::dao;
-    if let Ok(c) = dao::get_configuration() { Some((c.language, c.date_format)) } else { None }
+    crate::db::store().get_configuration().ok().map(|c| (c.language, c.date_format))
 }
-#[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-fn get_cfg() -> Option<(String, String)> { None }
 
 fn initial_state_from_config() -> I18nState {
     if let Some((lang, fmt)) = get_cfg() { I18nState { lang, date_format: fmt } } else { I18nState::default() }
@@ -0,0 +1,63 @@
+// Blackout date import from pasted text or a CSV/TSV file, one date or range per line:
+//   start[,end][,location][,reason]
+// An empty end is a single day and an empty location blacks out every location. A first line whose first
+// cell isn't a date is a header. Lines matching an existing blackout (same dates and location) are flagged
+// and skipped, so the same yearly list (assemblies, conventions, memorial) can be loaded again safely.
+use std::collections::HashSet;
+use chrono::NaiveDate;
+use crate::db::store::Blackout;
+use crate::i18n::normalize_for_search;
+use super::csv::{detect_delimiter, parse};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RowError { Date, EndBeforeStart }
+
+impl RowError {
+    pub fn key(self) -> &'static str {
+        match self {
+            RowError::Date => "blackouts.import_error_date",
+            RowError::EndBeforeStart => "blackouts.error_dates",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Row {
+    pub line: usize, // 1-based record number in the text
+    pub blackout: Blackout,
+    pub error: Option<RowError>,
+    pub duplicate: bool, // already stored, or listed earlier in the text
+}
+
+// ISO dates always; numeric day/month order follows the configured date format ('MM/DD/YYYY' or day first)
+pub fn parse_date(value: &str, date_format: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    let numeric = if date_format == "MM/DD/YYYY" { ["%m/%d/%Y", "%m-%d-%Y"] } else { ["%d/%m/%Y", "%d.%m.%Y"] };
+    ["%Y-%m-%d", "%Y/%m/%d"].into_iter().chain(numeric).find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+}
+
+fn key(b: &Blackout) -> (NaiveDate, NaiveDate, Option<String>) {
+    (b.start_date, b.end_date, b.location.as_deref().map(|l| normalize_for_search(l.trim())))
+}
+
+// Every record of `text` as a blackout, validated and checked against `existing`
+pub fn rows(text: &str, date_format: &str, existing: &[Blackout]) -> Vec<Row> {
+    let records = parse(text, detect_delimiter(text));
+    let skip = records.first().is_some_and(|r| parse_date(&r[0], date_format).is_none() && r[0].chars().any(char::is_alphabetic));
+    let mut seen: HashSet<_> = existing.iter().map(key).collect();
+    records.iter().enumerate().skip(usize::from(skip)).map(|(i, record)| {
+        let cell = |c: usize| record.get(c).map(|v| v.trim()).unwrap_or("");
+        let optional = |c: usize| Some(cell(c)).filter(|v| !v.is_empty()).map(str::to_string);
+        let start = parse_date(cell(0), date_format);
+        let end = if cell(1).is_empty() { start } else { parse_date(cell(1), date_format) };
+        let error = match (start, end) {
+            (Some(s), Some(e)) if e < s => Some(RowError::EndBeforeStart),
+            (Some(_), Some(_)) => None,
+            _ => Some(RowError::Date),
+        };
+        let fallback = NaiveDate::default();
+        let blackout = Blackout { id: 0, start_date: start.unwrap_or(fallback), end_date: end.unwrap_or(fallback), location: optional(2), reason: optional(3) };
+        let duplicate = error.is_none() && !seen.insert(key(&blackout));
+        Row { line: i + 1, blackout, error, duplicate }
+    }).collect()
+}
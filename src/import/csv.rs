@@ -0,0 +1,160 @@
+// Publisher import from CSV/TSV: parse the text, map columns to publisher fields, validate every row
+// and flag likely duplicates (accent-insensitive name match against existing publishers and earlier rows).
+use std::collections::HashMap;
+use crate::db::store::Publisher;
+use crate::i18n::normalize_for_search;
+use super::name_key;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Field { FirstName, LastName, Gender, ShiftManager, Priority }
+
+pub const FIELDS: [Field; 5] = [Field::FirstName, Field::LastName, Field::Gender, Field::ShiftManager, Field::Priority];
+
+impl Field {
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Field::FirstName => "publishers.first_name",
+            Field::LastName => "publishers.last_name",
+            Field::Gender => "publishers.gender",
+            Field::ShiftManager => "publishers.shift_manager",
+            Field::Priority => "publishers.priority",
+        }
+    }
+
+    // Header spellings recognised when guessing the mapping (compared after normalize_for_search)
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Field::FirstName => &["first_name", "first name", "firstname", "first", "given name", "nombre", "prenom", "vorname"],
+            Field::LastName => &["last_name", "last name", "lastname", "last", "surname", "family name", "apellido", "apellidos", "nom", "nom de famille", "nachname"],
+            Field::Gender => &["gender", "sex", "genero", "sexo", "genre", "sexe", "geschlecht"],
+            Field::ShiftManager => &["is_shift_manager", "shift manager", "manager", "capitan", "responsable", "encargado", "schichtleiter"],
+            Field::Priority => &["priority", "prioridad", "priorite", "prioritat"],
+        }
+    }
+}
+
+// Column index per field, in FIELDS order
+pub type Mapping = [Option<usize>; 5];
+
+pub fn column(mapping: &Mapping, field: Field) -> Option<usize> { mapping[FIELDS.iter().position(|f| *f == field).unwrap()] }
+
+// Comma, semicolon or tab, whichever occurs most in the first line
+pub fn detect_delimiter(text: &str) -> char {
+    let first = text.lines().next().unwrap_or("");
+    [',', ';', '\t'].into_iter().max_by_key(|d| first.matches(*d).count()).unwrap_or(',')
+}
+
+// RFC 4180 records: quoted fields may hold delimiters, doubled quotes and line breaks; blank lines are skipped
+pub fn parse(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => { field.push('"'); chars.next(); }
+                '"' => quoted = false,
+                c => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            quoted = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') { chars.next(); }
+            record.push(std::mem::take(&mut field));
+            if record.iter().any(|f| !f.trim().is_empty()) { records.push(std::mem::take(&mut record)); } else { record.clear(); }
+        } else {
+            field.push(c);
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.trim().is_empty()) { records.push(record); }
+    records
+}
+
+// Mapping guessed from a header row; None when fewer than two headers are recognised (no header row)
+pub fn guess_mapping(header: &[String]) -> Option<Mapping> {
+    let names: Vec<String> = header.iter().map(|h| normalize_for_search(h.trim()).replace(['-', '.'], " ")).collect();
+    let mut mapping: Mapping = [None; 5];
+    for (i, field) in FIELDS.iter().enumerate() {
+        mapping[i] = names.iter().position(|n| field.aliases().contains(&n.as_str()));
+    }
+    (mapping.iter().flatten().count() >= 2).then_some(mapping)
+}
+
+// Positional mapping for files without a header row
+pub fn default_mapping(columns: usize) -> Mapping {
+    let mut mapping: Mapping = [None; 5];
+    for (i, slot) in mapping.iter_mut().enumerate().take(columns) { *slot = Some(i); }
+    mapping
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Duplicate { Existing(i64), InFile(usize) }
+
+#[derive(Clone, Debug)]
+pub struct Row {
+    pub line: usize, // 1-based record number in the file
+    pub publisher: Publisher,
+    pub error: Option<RowError>,
+    pub duplicate: Option<Duplicate>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RowError { MissingName, Gender, ShiftManager, Priority }
+
+impl RowError {
+    pub fn key(self) -> &'static str {
+        match self {
+            RowError::MissingName => "publishers.error_required",
+            RowError::Gender => "publishers.import_error_gender",
+            RowError::ShiftManager => "publishers.import_error_manager",
+            RowError::Priority => "publishers.import_error_priority",
+        }
+    }
+}
+
+fn gender(value: &str) -> Option<&'static str> {
+    match normalize_for_search(value.trim()).as_str() {
+        "" | "m" | "male" | "man" | "h" | "hombre" | "masculino" | "homme" | "masculin" | "mann" | "mannlich" => Some("Male"),
+        "f" | "female" | "woman" | "mujer" | "femenino" | "femme" | "feminin" | "w" | "frau" | "weiblich" => Some("Female"),
+        _ => None,
+    }
+}
+
+fn flag(value: &str) -> Option<bool> {
+    match normalize_for_search(value.trim()).as_str() {
+        "1" | "true" | "yes" | "y" | "x" | "si" | "s" | "oui" | "o" | "ja" | "j" => Some(true),
+        "" | "0" | "false" | "no" | "n" | "non" | "nein" => Some(false),
+        _ => None,
+    }
+}
+
+// Validate the data records (header excluded) and mark duplicates of existing or earlier publishers
+pub fn rows(records: &[Vec<String>], first_line: usize, mapping: &Mapping, existing: &[Publisher]) -> Vec<Row> {
+    let mut seen: HashMap<String, Duplicate> = existing.iter().map(|p| (name_key(&p.first_name, &p.last_name), Duplicate::Existing(p.id))).collect();
+    records.iter().enumerate().map(|(i, record)| {
+        let cell = |field: Field| column(mapping, field).and_then(|c| record.get(c)).map(|v| v.trim()).unwrap_or("");
+        let (first, last) = (cell(Field::FirstName).to_string(), cell(Field::LastName).to_string());
+        let gender = gender(cell(Field::Gender));
+        let manager = flag(cell(Field::ShiftManager));
+        let priority = match cell(Field::Priority) { "" => Some(5), p => p.parse::<i64>().ok() };
+        let error = if first.is_empty() || last.is_empty() { Some(RowError::MissingName) }
+            else if gender.is_none() { Some(RowError::Gender) }
+            else if manager.is_none() { Some(RowError::ShiftManager) }
+            else if priority.is_none() { Some(RowError::Priority) }
+            else { None };
+        let key = name_key(&first, &last);
+        let duplicate = if error.is_none() { seen.get(&key).copied() } else { None };
+        if error.is_none() && duplicate.is_none() { seen.insert(key, Duplicate::InFile(i)); }
+        Row {
+            line: first_line + i,
+            publisher: Publisher { id: 0, first_name: first, last_name: last, gender: gender.unwrap_or("Male").into(), is_shift_manager: manager.unwrap_or(false), priority: priority.unwrap_or(5) },
+            error,
+            duplicate,
+        }
+    }).collect()
+}
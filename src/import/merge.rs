@@ -0,0 +1,211 @@
+// Merge import: add another coordinator's export file to the current data instead of replacing it.
+// Publishers match by name (case and accents ignored), schedules by location, weekday and hours,
+// shifts by time and location, absences by publisher and dates, blackout dates by dates and location. Matched rows keep their current
+// values and are reported as conflicts when the file disagrees; everything else is added with new ids.
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::db::envelope;
+use crate::db::store::{Blackout, MergeAbsence, MergeBatch, MergeShift, Publisher, Ref, RelationshipKind, Schedule, Store, StoreResult};
+use crate::i18n::{normalize_for_search, weekday_index_from_name};
+use super::name_key;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Counts { pub added: usize, pub matched: usize }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConflictKind { Publisher, Schedule, Relationship, Shift, Blackout }
+
+impl ConflictKind {
+    pub fn key(self) -> &'static str {
+        match self {
+            ConflictKind::Publisher => "config.merge_conflict_publisher",
+            ConflictKind::Schedule => "config.merge_conflict_schedule",
+            ConflictKind::Relationship => "config.merge_conflict_relationship",
+            ConflictKind::Shift => "config.merge_conflict_shift",
+            ConflictKind::Blackout => "config.merge_conflict_blackout",
+        }
+    }
+}
+
+// A matched row whose values in the file differ from the current ones (the current ones are kept)
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Conflict { pub kind: ConflictKind, pub label: String }
+
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Summary {
+    pub publishers: Counts,
+    pub schedules: Counts,
+    pub availability: Counts,
+    pub relationships: Counts,
+    pub absences: Counts,
+    pub shifts: Counts,
+    pub blackouts: Counts,
+    pub skipped: usize, // dangling references, absences and blackout dates that are already over
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Plan { pub batch: MergeBatch, pub summary: Summary }
+
+fn schedule_key(s: &Schedule) -> (String, u32, String, String) {
+    (normalize_for_search(s.location.trim()), weekday_index_from_name(s.weekday.trim()), s.start_hour.trim().to_string(), s.end_hour.trim().to_string())
+}
+
+// Match the file against the store and collect what a merge would add; nothing is written
+pub fn plan(json: &str, store: &dyn Store, today: NaiveDate) -> StoreResult<Plan> {
+    let file = envelope::open(json)?;
+    let mut batch = MergeBatch::default();
+    let mut summary = Summary::default();
+
+    // Publishers
+    let current = store.list_publishers()?;
+    let by_name: HashMap<String, &Publisher> = current.iter().map(|p| (name_key(&p.first_name, &p.last_name), p)).collect();
+    let mut added_names: HashMap<String, usize> = HashMap::new();
+    let mut pmap: HashMap<i64, Ref> = HashMap::new();
+    let mut conflicts = Vec::new();
+    for p in &file.publishers {
+        let key = name_key(&p.first_name, &p.last_name);
+        let r = if let Some(cur) = by_name.get(&key) {
+            summary.publishers.matched += 1;
+            if (cur.gender.as_str(), cur.is_shift_manager, cur.priority) != (p.gender.as_str(), p.is_shift_manager, p.priority) {
+                conflicts.push(Conflict { kind: ConflictKind::Publisher, label: format!("{} {}", cur.first_name, cur.last_name) });
+            }
+            Ref::Existing(cur.id)
+        } else if let Some(i) = added_names.get(&key) {
+            summary.publishers.matched += 1;
+            Ref::New(*i)
+        } else {
+            summary.publishers.added += 1;
+            added_names.insert(key, batch.publishers.len());
+            batch.publishers.push(p.clone());
+            Ref::New(batch.publishers.len() - 1)
+        };
+        pmap.insert(p.id, r);
+    }
+
+    // Schedules
+    let current_schedules = store.list_schedules()?;
+    let by_slot: HashMap<_, &Schedule> = current_schedules.iter().map(|s| (schedule_key(s), s)).collect();
+    let mut added_slots: HashMap<_, usize> = HashMap::new();
+    let mut smap: HashMap<i64, Ref> = HashMap::new();
+    for s in &file.schedules {
+        let key = schedule_key(s);
+        let r = if let Some(cur) = by_slot.get(&key) {
+            summary.schedules.matched += 1;
+            if (cur.num_publishers, cur.num_shift_managers, cur.num_brothers, cur.num_sisters, &cur.description, &cur.recurrence) != (s.num_publishers, s.num_shift_managers, s.num_brothers, s.num_sisters, &s.description, &s.recurrence) {
+                conflicts.push(Conflict { kind: ConflictKind::Schedule, label: format!("{} {} {}–{}", cur.weekday, cur.location, cur.start_hour, cur.end_hour) });
+            }
+            Ref::Existing(cur.id)
+        } else if let Some(i) = added_slots.get(&key) {
+            summary.schedules.matched += 1;
+            Ref::New(*i)
+        } else {
+            summary.schedules.added += 1;
+            added_slots.insert(key, batch.schedules.len());
+            batch.schedules.push(s.clone());
+            Ref::New(batch.schedules.len() - 1)
+        };
+        smap.insert(s.id, r);
+    }
+
+    // Availability: only pairs the store doesn't have yet
+    let mut current_availability: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for (p, s) in &file.availability {
+        let (Some(p), Some(s)) = (pmap.get(p).copied(), smap.get(s).copied()) else { summary.skipped += 1; continue };
+        if !seen.insert((p, s)) { continue; }
+        let exists = match (p, s) {
+            (Ref::Existing(pid), Ref::Existing(sid)) => {
+                let list = match current_availability.entry(pid) { Entry::Occupied(e) => e.into_mut(), Entry::Vacant(e) => e.insert(store.list_availability_for_publisher(pid)?) };
+                list.contains(&sid)
+            }
+            _ => false,
+        };
+        if exists { summary.availability.matched += 1; } else { summary.availability.added += 1; batch.availability.push((p, s)); }
+    }
+
+    // Relationships: pairs are unordered; a different kind on an existing pair is a conflict
+    let mut current_relationships: HashMap<i64, Vec<(i64, RelationshipKind)>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let name_of = |id: i64| current.iter().find(|p| p.id == id).map(|p| format!("{} {}", p.first_name, p.last_name)).unwrap_or_default();
+    for (a, b, kind) in &file.relationships {
+        let (Some(ra), Some(rb)) = (pmap.get(a).copied(), pmap.get(b).copied()) else { summary.skipped += 1; continue };
+        if ra == rb || !seen.insert((ra.min(rb), ra.max(rb))) { continue; }
+        let existing = match (ra, rb) {
+            (Ref::Existing(x), Ref::Existing(y)) => {
+                let list = match current_relationships.entry(x) { Entry::Occupied(e) => e.into_mut(), Entry::Vacant(e) => e.insert(store.list_relationships_for_publisher(x)?) };
+                list.iter().find(|(other, _)| *other == y).map(|(_, k)| (*k, x, y))
+            }
+            _ => None,
+        };
+        match existing {
+            Some((current_kind, x, y)) => {
+                summary.relationships.matched += 1;
+                if current_kind != *kind { conflicts.push(Conflict { kind: ConflictKind::Relationship, label: format!("{} – {}", name_of(x), name_of(y)) }); }
+            }
+            None => { summary.relationships.added += 1; batch.relationships.push((ra, rb, *kind)); }
+        }
+    }
+
+    // Absences that are still running; same publisher and dates counts as the same absence
+    let current_absences: HashSet<(i64, NaiveDate, NaiveDate)> = store.list_future_absences(today)?.into_iter().map(|a| (a.publisher_id, a.start_date, a.end_date)).collect();
+    let mut seen = HashSet::new();
+    for a in &file.absences {
+        let Some(r) = pmap.get(&a.publisher_id).copied() else { summary.skipped += 1; continue };
+        if a.end_date < today { summary.skipped += 1; continue; }
+        if !seen.insert((r, a.start_date, a.end_date)) { continue; }
+        if matches!(r, Ref::Existing(pid) if current_absences.contains(&(pid, a.start_date, a.end_date))) { summary.absences.matched += 1; continue; }
+        summary.absences.added += 1;
+        batch.absences.push(MergeAbsence { publisher: r, start_date: a.start_date, end_date: a.end_date, description: a.description.clone() });
+    }
+
+    // Shifts: same start, end and location is the same shift; differing assignments are a conflict
+    let current_shifts = match (file.shifts.iter().map(|s| s.start).min(), file.shifts.iter().map(|s| s.end).max()) {
+        (Some(from), Some(to)) => store.list_shifts_between(from, to)?,
+        _ => Vec::new(),
+    };
+    let by_slot: HashMap<(NaiveDateTime, NaiveDateTime, String), Vec<i64>> = current_shifts.into_iter().map(|s| ((s.start, s.end, normalize_for_search(s.location.trim())), s.publishers)).collect();
+    let mut seen = HashSet::new();
+    for s in &file.shifts {
+        let (start, end) = (s.start, s.end);
+        let key = (start, end, normalize_for_search(s.location.trim()));
+        if !seen.insert(key.clone()) { continue; }
+        let publishers: Vec<Ref> = s.publishers.iter().filter_map(|id| pmap.get(id).copied()).collect();
+        match by_slot.get(&key) {
+            Some(current) => {
+                summary.shifts.matched += 1;
+                let current: HashSet<Ref> = current.iter().map(|id| Ref::Existing(*id)).collect();
+                if current != publishers.iter().copied().collect() {
+                    conflicts.push(Conflict { kind: ConflictKind::Shift, label: format!("{} {}", start.format("%Y-%m-%d %H:%M"), s.location) });
+                }
+            }
+            None => {
+                summary.shifts.added += 1;
+                batch.shifts.push(MergeShift { start, end, location: s.location.clone(), publishers, warning: s.warning.clone() });
+            }
+        }
+    }
+
+    // Blackout dates that are still ahead; same dates and location is the same blackout, a different reason a conflict
+    let blackout_key = |b: &Blackout| (b.start_date, b.end_date, b.location.as_deref().map(|l| normalize_for_search(l.trim())));
+    let current_blackouts: HashMap<_, Blackout> = store.list_blackouts()?.into_iter().map(|b| (blackout_key(&b), b)).collect();
+    let mut seen = HashSet::new();
+    for b in &file.blackouts {
+        if b.end_date < today { summary.skipped += 1; continue; }
+        let key = blackout_key(b);
+        if !seen.insert(key.clone()) { continue; }
+        match current_blackouts.get(&key) {
+            Some(cur) => {
+                summary.blackouts.matched += 1;
+                if cur.reason != b.reason {
+                    let label = format!("{} – {} {}", cur.start_date, cur.end_date, cur.location.as_deref().unwrap_or_default());
+                    conflicts.push(Conflict { kind: ConflictKind::Blackout, label: label.trim_end().to_string() });
+                }
+            }
+            None => { summary.blackouts.added += 1; batch.blackouts.push(b.clone()); }
+        }
+    }
+
+    summary.conflicts = conflicts;
+    Ok(Plan { batch, summary })
+}
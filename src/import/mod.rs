@@ -0,0 +1,14 @@
+// Data imports into the active store
+pub mod csv;
+// Merge another export file into the current data
+pub mod merge;
+// Blackout date lists (holidays, assemblies) pasted or read from a file
+pub mod blackouts;
+
+use crate::i18n::normalize_for_search;
+
+// Matching key for publisher names: "José  Pérez-Gómez" and "jose perez gomez" are the same person
+pub fn name_key(first: &str, last: &str) -> String {
+    let plain: String = normalize_for_search(&format!("{} {}", first, last)).chars().map(|c| if c.is_alphanumeric() { c } else { ' ' }).collect();
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -3,14 +3,17 @@
 use dioxus::prelude::*;
 mod i18n;
 // Components
-use views::{Home, Publishers, Absences, Schedules, Shifts, Configuration};
-// Static web: use wasm local storage backend for configuration detection
-#[cfg(target_arch = "wasm32")] use crate::db::wasm_store as backend;
-#[cfg(not(target_arch = "wasm32"))] mod backend { pub fn configuration_is_set() -> bool { true } }
+use views::{Home, Publishers, Absences, Blackouts, Schedules, Shifts, Configuration};
 
 mod components;
 mod views;
 mod db; // universal db facade (native sqlite or wasm storage)
+mod scheduler; // pure shift generator shared by all backends
+mod recurrence; // schedule repeat rules (every n weeks, nth weekday, active range)
+mod export; // shift exports (web print view, native files)
+mod import; // publisher CSV import
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+mod dialogs; // save dialogs (desktop) and the share sheet (mobile) for native exports
 
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
@@ -21,6 +24,8 @@ enum Route {
     Publishers {},
     #[route("/absences")]
     Absences {},
+    #[route("/blackouts")]
+    Blackouts {},
     #[route("/schedules")]
     Schedules {},
     #[route("/shifts")]
@@ -32,6 +37,16 @@ enum Route {
 fn main() {
     #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
     install_panic_hook();
+    // Startup and daily snapshots of data.db (native builds; encrypted ones start after unlock)
+    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+    db::backups::start();
+    // The web build loads its IndexedDB (or OPFS) data before the first render
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(async {
+        db::init().await;
+        dioxus::launch(App);
+    });
+    #[cfg(not(target_arch = "wasm32"))]
     dioxus::launch(App);
 }
 #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
@@ -51,30 +66,68 @@ fn install_panic_hook() {
 
 #[component]
 fn App() -> Element {
-    // For static site (wasm), determine if initial configuration exists
-    let configured = use_signal(|| backend::configuration_is_set());
+    // Determine if initial configuration exists (Landpage is shown until it does)
+    let configured = use_signal(|| db::store().configuration_is_set());
     // Provide context so Landpage can flip it after user saves configuration
     provide_context(configured);
+    // Encrypted builds start locked; Unlock flips this once the passphrase opened the database
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    let unlocked = use_signal(|| !db::is_locked());
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    provide_context(unlocked);
     // Provide i18n context (reads initial language/date from configuration if present)
     i18n::provide_i18n_from_config();
 
     // Apply theme based on saved configuration (web/native)
     use_effect(move || {
-        #[cfg(target_arch = "wasm32")]
-        {
-            if let Some(cfg) = backend::get_configuration() { i18n::apply_theme(&cfg.theme); }
-            else { i18n::apply_theme("System"); }
+        match db::store().get_configuration() {
+            Ok(cfg) => i18n::apply_theme(&cfg.theme),
+            Err(_) => i18n::apply_theme("System"),
         }
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            if let Ok(cfg) = crate::db::dao::get_configuration() { i18n::apply_theme(&cfg.theme); }
-            else { i18n::apply_theme("System"); }
-        }
-        #[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-        {
-            i18n::apply_theme("System");
+    });
+
+    // A failed schema upgrade stops the app rather than running on an outdated schema
+    let content = match db::migration_error() {
+        Some(err) => rsx! {
+            div { class: "max-w-xl mx-auto pt-10 space-y-3",
+                h1 { class: "text-2xl font-bold tracking-tight", {i18n::t("migration.title")} }
+                p { class: "text-sm text-slate-600 dark:text-slate-300", {i18n::t("migration.message")} }
+                pre { class: "text-xs text-red-600 whitespace-pre-wrap break-all", {err} }
+            }
+        },
+        None => rsx! { Router::<Route> {} },
+    };
+    // The web database file could not be opened (OPFS builds; most often it is open in another tab)
+    let content = match db::open_error() {
+        Some(err) => rsx! {
+            div { class: "max-w-xl mx-auto pt-10 space-y-3",
+                h1 { class: "text-2xl font-bold tracking-tight", {i18n::t("storage.open_failed_title")} }
+                p { class: "text-sm text-slate-600 dark:text-slate-300", {i18n::t("storage.open_failed")} }
+                pre { class: "text-xs text-red-600 whitespace-pre-wrap break-all", {err} }
+            }
+        },
+        None => content,
+    };
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    let content = if unlocked() { content } else { rsx! { views::Unlock {} } };
+
+    // Web writes land in IndexedDB after the change was shown; a failed one is reported here
+    #[cfg(target_arch = "wasm32")]
+    let mut save_problem = use_signal(|| Option::<(bool, String)>::None);
+    #[cfg(target_arch = "wasm32")]
+    use_hook(|| db::on_save_error(move |kept, detail| save_problem.set(Some((kept, detail)))));
+    #[cfg(target_arch = "wasm32")]
+    let banner = save_problem().map(|(kept, detail)| rsx! {
+        div { class: if kept { "mb-4 rounded-md border border-amber-300 bg-amber-50 dark:bg-amber-900/30 p-3 text-sm text-amber-800 dark:text-amber-200 flex items-start gap-3" } else { "mb-4 rounded-md border border-red-300 bg-red-50 dark:bg-red-900/30 p-3 text-sm text-red-700 dark:text-red-200 flex items-start gap-3" },
+            div { class: "flex-1 space-y-1",
+                p { {i18n::t(if kept { "storage.kept_in_local_storage" } else { "storage.save_failed" })} }
+                p { class: "text-xs opacity-75 break-all", {detail} }
+            }
+            button { class: "text-lg leading-none", onclick: move |_| save_problem.set(None), "✕" }
         }
     });
+    #[cfg(not(target_arch = "wasm32"))]
+    let banner: Option<Element> = None;
 
     rsx! {
         document::Stylesheet { href: asset!("assets/tailwind.css") }
@@ -100,7 +153,8 @@ fn App() -> Element {
         }
         div { class: "app-layout flex min-h-screen",
             main { class: "main-content flex-1 p-8 bg-white dark:bg-slate-800 text-slate-900 dark:text-slate-100",
-                Router::<Route> {}
+                {banner}
+                {content}
             }
         }
     }
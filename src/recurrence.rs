@@ -0,0 +1,127 @@
+// Which weeks a schedule runs in, on top of its weekday.
+// A schedule without a rule runs every week; otherwise the rule picks the weeks and an optional
+// active range limits the dates, e.g. every other Saturday from an anchor date, the first and third
+// Tuesday of the month, or a summer-only cart. Stored as JSON with the schedule (the
+// Schedules.recurrence column on SQLite, a field of the row on the other backends).
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Rule {
+    #[default]
+    Weekly,
+    // Every `interval` weeks, counting from the week that contains `anchor`
+    EveryNWeeks { interval: u32, anchor: NaiveDate },
+    // Occurrences of the weekday within its month: 1..=5, or -1 for the last one
+    MonthlyNth { nth: Vec<i32> },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Recurrence {
+    pub rule: Rule,
+    pub from: Option<NaiveDate>, // active range, both ends included
+    pub until: Option<NaiveDate>,
+}
+
+fn week_monday(d: NaiveDate) -> NaiveDate { d - Duration::days(d.weekday().num_days_from_monday().into()) }
+
+fn is_last_in_month(d: NaiveDate) -> bool { (d + Duration::days(7)).month() != d.month() }
+
+impl Recurrence {
+    // Every week, no date limits: what schedules did before rules existed
+    pub fn is_weekly(&self) -> bool { *self == Recurrence::default() }
+
+    // Whether a schedule held on `weekday` (1=Mon..7=Sun) runs on `date`
+    pub fn runs_on(&self, weekday: u32, date: NaiveDate) -> bool {
+        if date.weekday().number_from_monday() != weekday { return false; }
+        if self.from.is_some_and(|f| date < f) || self.until.is_some_and(|u| date > u) { return false; }
+        match &self.rule {
+            Rule::Weekly => true,
+            Rule::EveryNWeeks { interval, anchor } => {
+                let weeks = (week_monday(date) - week_monday(*anchor)).num_days() / 7;
+                *interval > 0 && weeks.rem_euclid(i64::from(*interval)) == 0
+            }
+            Rule::MonthlyNth { nth } => {
+                let n = (date.day() as i32 - 1) / 7 + 1;
+                nth.contains(&n) || (nth.contains(&-1) && is_last_in_month(date))
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.rule {
+            Rule::Weekly => {}
+            Rule::EveryNWeeks { interval, .. } => if *interval == 0 { return Err("repeat interval must be at least one week".into()); },
+            Rule::MonthlyNth { nth } => {
+                if nth.is_empty() { return Err("pick at least one week of the month".into()); }
+                if let Some(n) = nth.iter().find(|n| !(**n == -1 || (1..=5).contains(*n))) { return Err(format!("week of the month {} is not 1-5 or last", n)); }
+            }
+        }
+        if let (Some(f), Some(u)) = (self.from, self.until) && u < f { return Err("active range ends before it starts".into()); }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate { NaiveDate::from_ymd_opt(y, m, d).unwrap() }
+
+    fn rule(rule: Rule) -> Recurrence { Recurrence { rule, ..Default::default() } }
+
+    #[test]
+    fn every_n_weeks_counts_from_the_anchor_week() {
+        // Anchored on a Wednesday: the Saturday of that week is the first occurrence
+        let every_other = rule(Rule::EveryNWeeks { interval: 2, anchor: date(2024, 3, 6) });
+        let saturday = 6;
+        assert!(every_other.runs_on(saturday, date(2024, 3, 9)));
+        assert!(!every_other.runs_on(saturday, date(2024, 3, 16)));
+        assert!(every_other.runs_on(saturday, date(2024, 3, 23)));
+        // Dates before the anchor follow the same rhythm
+        assert!(!every_other.runs_on(saturday, date(2024, 3, 2)));
+        assert!(every_other.runs_on(saturday, date(2024, 2, 24)));
+        assert!(!every_other.runs_on(saturday, date(2024, 2, 17)));
+    }
+
+    #[test]
+    fn monthly_nth_picks_weeks_of_the_month() {
+        let friday = 5;
+        let last = rule(Rule::MonthlyNth { nth: vec![-1] });
+        assert!(last.runs_on(friday, date(2024, 3, 29)));
+        assert!(!last.runs_on(friday, date(2024, 3, 22)));
+        assert!(last.runs_on(friday, date(2024, 2, 23)));
+        // February 2024 has only four Fridays
+        let fifth = rule(Rule::MonthlyNth { nth: vec![5] });
+        assert!(fifth.runs_on(friday, date(2024, 3, 29)));
+        assert!(!fifth.runs_on(friday, date(2024, 2, 23)));
+        let first_and_third = rule(Rule::MonthlyNth { nth: vec![1, 3] });
+        assert!(first_and_third.runs_on(friday, date(2024, 3, 1)));
+        assert!(!first_and_third.runs_on(friday, date(2024, 3, 8)));
+        assert!(first_and_third.runs_on(friday, date(2024, 3, 15)));
+    }
+
+    #[test]
+    fn active_range_includes_both_ends() {
+        let monday = 1;
+        let range = Recurrence { from: Some(date(2024, 3, 4)), until: Some(date(2024, 3, 11)), ..Default::default() };
+        assert!(!range.runs_on(monday, date(2024, 2, 26)));
+        assert!(range.runs_on(monday, date(2024, 3, 4)));
+        assert!(range.runs_on(monday, date(2024, 3, 11)));
+        assert!(!range.runs_on(monday, date(2024, 3, 18)));
+    }
+
+    #[test]
+    fn validate_rejects_unusable_rules() {
+        assert!(Recurrence::default().validate().is_ok());
+        assert!(rule(Rule::EveryNWeeks { interval: 0, anchor: date(2024, 3, 4) }).validate().is_err());
+        assert!(rule(Rule::MonthlyNth { nth: vec![] }).validate().is_err());
+        assert!(rule(Rule::MonthlyNth { nth: vec![0] }).validate().is_err());
+        let reversed = Recurrence { from: Some(date(2024, 3, 11)), until: Some(date(2024, 3, 4)), ..Default::default() };
+        assert!(reversed.validate().is_err());
+        let one_day = Recurrence { from: Some(date(2024, 3, 4)), until: Some(date(2024, 3, 4)), ..Default::default() };
+        assert!(one_day.validate().is_ok());
+    }
+}
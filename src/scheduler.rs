@@ -0,0 +1,678 @@
+// Backend-agnostic shift generator.
+// Views collect publishers, schedules, availability, relationships, absences, blackout
+// dates and shift history from whichever store is active, call `generate`, and persist the
+// resulting `Plan`. Nothing here touches Dioxus, the database or the browser, and nothing reads
+// the clock: the same input and seed always give the same plan.
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::db::store::Blackout;
+use crate::recurrence::Recurrence;
+
+mod exact; // branch and bound solver behind Mode::Exact
+
+// Scoring weights and the history window, kept with the configuration. A candidate scores
+// priority * priority weight + jitter * random [0, 1] + partner bonuses
+// - recent shifts * recent shift penalty - earlier shifts with the same people * pair repeat penalty.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    pub priority: f64,
+    pub recent_shift: f64, // per shift in the history window
+    pub pair_repeat: f64,  // per earlier shift with the same partner
+    pub recommended: f64,  // per recommended partner in the shift
+    pub mandatory: f64,    // per mandatory partner in the shift
+    pub jitter: f64,
+    pub history_days: i64,
+}
+
+impl Default for Weights {
+    fn default() -> Self { Weights::BALANCED }
+}
+
+impl Weights {
+    pub const BALANCED: Weights = Weights { priority: 10.0, recent_shift: 2.0, pair_repeat: 1.5, recommended: 2.0, mandatory: 5.0, jitter: 3.0, history_days: 60 };
+    // Spread shifts evenly: recent shifts and repeated pairings weigh most, over a longer window
+    pub const FAIRNESS: Weights = Weights { priority: 2.0, recent_shift: 6.0, pair_repeat: 3.0, recommended: 2.0, mandatory: 5.0, jitter: 2.0, history_days: 90 };
+    // Publishers with a higher priority first, whatever they served lately
+    pub const PRIORITY: Weights = Weights { priority: 25.0, recent_shift: 1.0, pair_repeat: 1.0, recommended: 2.0, mandatory: 5.0, jitter: 1.0, history_days: 30 };
+    pub const PRESETS: [(&'static str, Weights); 3] = [("balanced", Weights::BALANCED), ("fairness", Weights::FAIRNESS), ("priority", Weights::PRIORITY)];
+
+    // Key of the preset these weights match, if any
+    pub fn preset(&self) -> Option<&'static str> { Weights::PRESETS.iter().find(|(_, w)| w == self).map(|(k, _)| *k) }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let all = [self.priority, self.recent_shift, self.pair_repeat, self.recommended, self.mandatory, self.jitter];
+        if all.iter().any(|w| !w.is_finite() || *w < 0.0) { return Err("weights must be zero or more".into()); }
+        if !(0..=365).contains(&self.history_days) { return Err("history window must be 0-365 days".into()); }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender { Male, Female }
+
+impl Gender {
+    // Stores keep gender as 'Male' | 'Female'
+    pub fn from_label(s: &str) -> Self { if s == "Male" { Gender::Male } else { Gender::Female } }
+}
+
+#[derive(Debug, Clone)]
+pub struct Publisher {
+    pub id: i64,
+    pub gender: Gender,
+    pub is_shift_manager: bool,
+    pub priority: i64,
+}
+
+impl Publisher {
+    // Only brothers can fill shift manager slots
+    fn is_manager(&self) -> bool { self.is_shift_manager && self.gender == Gender::Male }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub id: i64,
+    pub location: String,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub weekday: u32, // 1=Mon..7=Sun
+    pub recurrence: Recurrence,
+    pub num_publishers: i64,
+    pub num_shift_managers: i64,
+    pub num_brothers: i64,
+    pub num_sisters: i64,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationshipKind { Mandatory, Recommended }
+
+#[derive(Debug, Clone)]
+pub struct Absence { pub publisher_id: i64, pub start: NaiveDate, pub end: NaiveDate }
+
+// A shift already stored (used for fairness history and to skip duplicates)
+#[derive(Debug, Clone)]
+pub struct ExistingShift {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub location: String,
+    pub publishers: Vec<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Input {
+    pub publishers: Vec<Publisher>,
+    pub schedules: Vec<Schedule>,
+    pub availability: Vec<(i64, i64)>, // (publisher_id, schedule_id)
+    pub relationships: Vec<(i64, i64, RelationshipKind)>,
+    pub absences: Vec<Absence>,
+    pub blackouts: Vec<Blackout>, // days no shift is generated on, as stored
+    pub history: Vec<ExistingShift>,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+// Greedy fills every slot as well as it can and flags what it had to give up; Exact treats quotas,
+// availability, absences, one shift per day and mandatory pairs as hard constraints and leaves a
+// slot empty, with the constraint that blocked it, rather than ship it short
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode { #[default] Greedy, Exact }
+
+impl Mode {
+    pub fn key(self) -> &'static str { match self { Mode::Greedy => "greedy", Mode::Exact => "exact" } }
+    pub fn from_key(s: &str) -> Self { if s == "exact" { Mode::Exact } else { Mode::Greedy } }
+}
+
+impl Input {
+    fn is_available(&self, pid: i64, schedule_id: i64) -> bool { self.availability.contains(&(pid, schedule_id)) }
+    fn is_absent(&self, pid: i64, day: NaiveDate) -> bool { self.absences.iter().any(|a| a.publisher_id == pid && a.start <= day && a.end >= day) }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub seed: u64, // drives the tie-breaking jitter; keep it to reproduce a run
+    pub weights: Weights,
+    pub mode: Mode,
+}
+
+impl Default for Options {
+    fn default() -> Self { Self { seed: 0, weights: Weights::default(), mode: Mode::Greedy } }
+}
+
+impl Options {
+    // First day of shift history the generator looks at for a run starting on `start`
+    pub fn history_start(&self, start: NaiveDate) -> NaiveDate { start - Duration::days(self.weights.history_days) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    ExtraManagersKept,
+    FewerSisters,
+    FewerBrothers,
+    NoSlotForSister,
+    NoSlotForBrother,
+    DroppedOverCapacity,
+    TrimmedOverCapacity,
+    NotEnoughPublishers,
+    Blocked(Constraint), // exact mode: slot left empty
+}
+
+// Hard constraint that kept an exact-mode slot from being filled, checked in this order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    Quotas,         // the schedule asks for more managers, brothers and sisters than it has places
+    Availability,   // too few publishers marked available for the schedule
+    Absences,       // enough available, but too many absent that day
+    Managers,
+    Brothers,
+    Sisters,
+    MandatoryPairs, // the counts work out, but not with partners kept together
+    OneShiftPerDay, // fillable alone, but not next to the day's other shifts
+    SearchLimit,    // the search gave up before settling it
+}
+
+impl Constraint {
+    pub fn key(self) -> &'static str {
+        match self {
+            Constraint::Quotas => "shifts.blocked_quotas",
+            Constraint::Availability => "shifts.blocked_availability",
+            Constraint::Absences => "shifts.blocked_absences",
+            Constraint::Managers => "shifts.blocked_managers",
+            Constraint::Brothers => "shifts.blocked_brothers",
+            Constraint::Sisters => "shifts.blocked_sisters",
+            Constraint::MandatoryPairs => "shifts.blocked_mandatory",
+            Constraint::OneShiftPerDay => "shifts.blocked_one_per_day",
+            Constraint::SearchLimit => "shifts.blocked_search_limit",
+        }
+    }
+}
+
+impl WarningKind {
+    // Text stored in Shifts.warning
+    pub fn message(&self) -> &'static str {
+        match self {
+            WarningKind::ExtraManagersKept => "Could not reduce extra managers due to mandatory pairs",
+            WarningKind::FewerSisters => "Fewer sisters available than required",
+            WarningKind::FewerBrothers => "Fewer brothers available than required",
+            WarningKind::NoSlotForSister => "Cannot free slot to add required sister",
+            WarningKind::NoSlotForBrother => "Cannot free slot to add required brother",
+            WarningKind::DroppedOverCapacity => "Had to drop some selections due to capacity; mandatory pairs may be affected",
+            WarningKind::TrimmedOverCapacity => "Trimmed extra selections to fit capacity",
+            WarningKind::NotEnoughPublishers => "Not enough available publishers",
+            WarningKind::Blocked(c) => match c {
+                Constraint::Quotas => "Not filled: the schedule asks for more managers, brothers and sisters than it has places",
+                Constraint::Availability => "Not filled: not enough publishers are available for this schedule",
+                Constraint::Absences => "Not filled: too many available publishers are absent",
+                Constraint::Managers => "Not filled: not enough shift managers can serve",
+                Constraint::Brothers => "Not filled: not enough brothers can serve",
+                Constraint::Sisters => "Not filled: not enough sisters can serve",
+                Constraint::MandatoryPairs => "Not filled: mandatory pairs don't fit in the places",
+                Constraint::OneShiftPerDay => "Not filled: everyone who could serve already has a shift that day",
+                Constraint::SearchLimit => "Not filled: the solver gave up before finding an assignment",
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning { pub date: NaiveDate, pub schedule_id: i64, pub kind: WarningKind }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedShift {
+    pub schedule_id: i64,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub location: String,
+    pub publishers: Vec<i64>,
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    pub shifts: Vec<ProposedShift>,
+    pub warnings: Vec<Warning>,
+}
+
+// Read-only view over the input plus the running fairness counters
+struct Ctx<'a> {
+    publishers: HashMap<i64, &'a Publisher>,
+    relationships: HashMap<i64, Vec<(i64, RelationshipKind)>>,
+    recent_count: HashMap<i64, i32>,
+    pair_count: HashMap<(i64, i64), i32>,
+    weights: Weights,
+    seed: u64,
+}
+
+impl Ctx<'_> {
+    fn is_male(&self, pid: i64) -> bool { self.publishers.get(&pid).is_some_and(|p| p.gender == Gender::Male) }
+    fn is_female(&self, pid: i64) -> bool { self.publishers.get(&pid).is_some_and(|p| p.gender == Gender::Female) }
+    fn is_manager(&self, pid: i64) -> bool { self.publishers.get(&pid).is_some_and(|p| p.is_manager()) }
+
+    fn score(&self, pid: i64, selected: &[i64], day: NaiveDate) -> f64 {
+        let w = &self.weights;
+        let base = self.publishers.get(&pid).map(|p| p.priority as f64).unwrap_or(0.0) * w.priority;
+        let rec_pen = (*self.recent_count.get(&pid).unwrap_or(&0)) as f64 * w.recent_shift;
+        let pair_pen: f64 = selected
+            .iter()
+            .map(|&o| (*self.pair_count.get(&(pid.min(o), pid.max(o))).unwrap_or(&0)) as f64 * w.pair_repeat)
+            .sum();
+        // relationship bonus for recommended, stronger for mandatory
+        let mut rel_bonus = 0.0;
+        if let Some(rs) = self.relationships.get(&pid) {
+            for o in selected {
+                if let Some((_, kind)) = rs.iter().find(|(oid, _)| oid == o) {
+                    rel_bonus += match kind { RelationshipKind::Recommended => w.recommended, RelationshipKind::Mandatory => w.mandatory };
+                }
+            }
+        }
+        base + jitter(self.seed, pid, day) * w.jitter + rel_bonus - rec_pen - pair_pen
+    }
+
+    // Best score first, ties broken by id so runs are stable
+    fn rank(&self, mut pool: Vec<i64>, selected: &[i64], day: NaiveDate) -> Vec<i64> {
+        pool.sort_by(|a, b| {
+            self.score(*b, selected, day)
+                .partial_cmp(&self.score(*a, selected, day))
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.cmp(b))
+        });
+        pool
+    }
+
+    // Selected publishers that are part of a mandatory pair with another selected publisher
+    fn mandatory_set(&self, selected: &[i64]) -> HashSet<i64> {
+        let mut set = HashSet::new();
+        for &pid in selected {
+            if let Some(rs) = self.relationships.get(&pid) {
+                for &(oid, kind) in rs {
+                    if kind == RelationshipKind::Mandatory && selected.contains(&oid) {
+                        set.insert(pid);
+                        set.insert(oid);
+                    }
+                }
+            }
+        }
+        set
+    }
+
+    // Pairs (pid, score) sorted lowest score first
+    fn lowest_first(&self, pids: impl Iterator<Item = i64>, selected: &[i64], day: NaiveDate) -> Vec<(i64, f64)> {
+        let mut v: Vec<(i64, f64)> = pids.map(|pid| (pid, self.score(pid, selected, day))).collect();
+        v.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+        v
+    }
+
+    fn record(&mut self, selected: &[i64]) {
+        for &pid in selected {
+            *self.recent_count.entry(pid).or_insert(0) += 1;
+            for &other in selected {
+                if pid < other { *self.pair_count.entry((pid, other)).or_insert(0) += 1; }
+            }
+        }
+    }
+}
+
+// Deterministic per-(seed, publisher, day) noise in [0, 1] (splitmix64 finalizer)
+fn jitter(seed: u64, pid: i64, day: NaiveDate) -> f64 {
+    let mut x = (pid as u64)
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(seed ^ (day.num_days_from_ce() as u64));
+    x ^= x >> 30; x = x.wrapping_mul(0xbf58476d1ce4e5b9);
+    x ^= x >> 27; x = x.wrapping_mul(0x94d049bb133111eb);
+    x ^= x >> 31;
+    (x as f64) / (u64::MAX as f64)
+}
+
+pub fn generate(input: &Input, opts: &Options) -> Plan {
+    let hist_start = opts.history_start(input.start);
+    let history: Vec<&ExistingShift> = input
+        .history
+        .iter()
+        .filter(|sh| sh.start.date() >= hist_start && sh.end.date() <= input.end)
+        .collect();
+
+    let mut relationships: HashMap<i64, Vec<(i64, RelationshipKind)>> = HashMap::new();
+    for &(a, b, kind) in &input.relationships {
+        if a == b { continue; }
+        relationships.entry(a).or_default().push((b, kind));
+        relationships.entry(b).or_default().push((a, kind));
+    }
+    let mut ctx = Ctx {
+        publishers: input.publishers.iter().map(|p| (p.id, p)).collect(),
+        relationships,
+        recent_count: HashMap::new(),
+        pair_count: HashMap::new(),
+        weights: opts.weights,
+        seed: opts.seed,
+    };
+    for sh in &history { ctx.record(&sh.publishers); }
+
+    let mut plan = Plan::default();
+    let mut d = input.start;
+    while d <= input.end {
+        let blacked_out = |s: &Schedule| input.blackouts.iter().any(|b| b.covers(d, &s.location));
+        // The day's slots, skipping shifts already stored and repeats of the same place and time
+        let mut slots: Vec<&Schedule> = Vec::new();
+        for s in input.schedules.iter().filter(|s| s.recurrence.runs_on(s.weekday, d) && !blacked_out(s)) {
+            let (start_dt, end_dt) = (NaiveDateTime::new(d, s.start), NaiveDateTime::new(d, s.end));
+            let same = |loc: &str, st: NaiveDateTime, et: NaiveDateTime| loc == s.location && st == start_dt && et == end_dt;
+            if history.iter().any(|e| same(&e.location, e.start, e.end))
+                || slots.iter().any(|o| same(&o.location, NaiveDateTime::new(d, o.start), NaiveDateTime::new(d, o.end))) { continue; }
+            slots.push(s);
+        }
+        match opts.mode {
+            Mode::Greedy => {
+                let mut day_assigned: HashSet<i64> = HashSet::new();
+                for s in slots {
+                    let candidates: Vec<&Publisher> = input
+                        .publishers
+                        .iter()
+                        .filter(|p| input.is_available(p.id, s.id) && !input.is_absent(p.id, d))
+                        .filter(|p| !day_assigned.contains(&p.id))
+                        .collect();
+                    let (selected, kinds) = fill_slot(&ctx, s, &candidates, d);
+                    day_assigned.extend(&selected);
+                    ctx.record(&selected);
+                    plan.warnings.extend(kinds.iter().map(|&kind| Warning { date: d, schedule_id: s.id, kind }));
+                    plan.shifts.push(proposed(s, d, selected, kinds.last()));
+                }
+            }
+            Mode::Exact => {
+                let solved = exact::solve_day(&ctx, input, &slots, d);
+                for (s, res) in slots.into_iter().zip(solved) {
+                    match res {
+                        Ok(selected) => {
+                            ctx.record(&selected);
+                            plan.shifts.push(proposed(s, d, selected, None));
+                        }
+                        Err(c) => plan.warnings.push(Warning { date: d, schedule_id: s.id, kind: WarningKind::Blocked(c) }),
+                    }
+                }
+            }
+        }
+        d = match d.succ_opt() { Some(n) => n, None => break };
+    }
+    plan
+}
+
+fn proposed(s: &Schedule, d: NaiveDate, publishers: Vec<i64>, warning: Option<&WarningKind>) -> ProposedShift {
+    ProposedShift {
+        schedule_id: s.id,
+        start: NaiveDateTime::new(d, s.start),
+        end: NaiveDateTime::new(d, s.end),
+        location: s.location.clone(),
+        publishers,
+        warning: warning.map(|k| k.message().to_string()),
+    }
+}
+
+// Greedy pass for one shift: quotas first, then mandatory partners, then rebalance and fill
+fn fill_slot(ctx: &Ctx, s: &Schedule, candidates: &[&Publisher], d: NaiveDate) -> (Vec<i64>, Vec<WarningKind>) {
+    let mut selected: Vec<i64> = Vec::new();
+    let mut warnings: Vec<WarningKind> = Vec::new();
+    let is_candidate = |pid: i64| candidates.iter().any(|p| p.id == pid);
+
+    // managers first (male)
+    let mgr_pool = ctx.rank(candidates.iter().filter(|p| p.is_manager()).map(|p| p.id).collect(), &selected, d);
+    for pid in mgr_pool.into_iter().take(s.num_shift_managers as usize) {
+        if !selected.contains(&pid) { selected.push(pid); }
+    }
+    // brothers (male) including managers
+    let male_have = selected.iter().filter(|pid| ctx.is_male(**pid)).count();
+    let male_needed = (s.num_brothers as usize).saturating_sub(male_have);
+    let male_pool = ctx.rank(candidates.iter().filter(|p| p.gender == Gender::Male && !selected.contains(&p.id)).map(|p| p.id).collect(), &selected, d);
+    selected.extend(male_pool.into_iter().take(male_needed));
+    // sisters
+    let female_pool = ctx.rank(candidates.iter().filter(|p| p.gender == Gender::Female && !selected.contains(&p.id)).map(|p| p.id).collect(), &selected, d);
+    selected.extend(female_pool.into_iter().take(s.num_sisters as usize));
+
+    // Enforce mandatory relationships: if one is selected, add its partners when available
+    let must_have: Vec<i64> = selected
+        .iter()
+        .filter_map(|pid| ctx.relationships.get(pid))
+        .flat_map(|rs| rs.iter().filter(|(_, k)| *k == RelationshipKind::Mandatory).map(|(oid, _)| *oid))
+        .collect();
+    for oid in must_have {
+        if !selected.contains(&oid) && is_candidate(oid) { selected.push(oid); }
+    }
+
+    // Rebalance to respect manager and gender minima
+    {
+        let mandatory_set = ctx.mandatory_set(&selected);
+        let mut count_mgr = selected.iter().filter(|pid| ctx.is_manager(**pid)).count() as i64;
+        let mut count_male = selected.iter().filter(|pid| ctx.is_male(**pid)).count() as i64;
+        let mut count_female = selected.iter().filter(|pid| ctx.is_female(**pid)).count() as i64;
+
+        // Reduce managers if exceeding required
+        if count_mgr > s.num_shift_managers {
+            let excess = (count_mgr - s.num_shift_managers) as usize;
+            let removals = ctx.lowest_first(selected.iter().copied().filter(|pid| ctx.is_manager(*pid) && !mandatory_set.contains(pid)), &selected, d);
+            let mut removed = 0usize;
+            for (pid, _) in removals {
+                if removed >= excess { break; }
+                if let Some(pos) = selected.iter().position(|x| *x == pid) {
+                    selected.remove(pos);
+                    removed += 1;
+                    count_mgr -= 1;
+                    count_male -= 1;
+                }
+            }
+            if removed < excess { warnings.push(WarningKind::ExtraManagersKept); }
+        }
+
+        // Ensure minimum sisters
+        while count_female < s.num_sisters {
+            let fem_pool = ctx.rank(candidates.iter().filter(|p| p.gender == Gender::Female && !selected.contains(&p.id)).map(|p| p.id).collect(), &selected, d);
+            let Some(&add_id) = fem_pool.first() else { warnings.push(WarningKind::FewerSisters); break; };
+            if selected.len() >= s.num_publishers as usize {
+                // remove lowest scoring non-mandatory brother (prefer non-manager)
+                let mut males = ctx.lowest_first(selected.iter().copied().filter(|pid| ctx.is_male(*pid) && !mandatory_set.contains(pid) && !ctx.is_manager(*pid)), &selected, d);
+                if males.is_empty() {
+                    males = ctx.lowest_first(selected.iter().copied().filter(|pid| ctx.is_male(*pid) && !mandatory_set.contains(pid)), &selected, d);
+                }
+                let Some(&(rm, _)) = males.first() else { warnings.push(WarningKind::NoSlotForSister); break; };
+                if let Some(pos) = selected.iter().position(|x| *x == rm) {
+                    selected.remove(pos);
+                    if ctx.is_manager(rm) { count_mgr -= 1; }
+                    count_male -= 1;
+                }
+            }
+            selected.push(add_id);
+            count_female += 1;
+        }
+
+        // Ensure minimum brothers
+        while count_male < s.num_brothers {
+            // prefer non-managers once the manager quota is met
+            let prefer_non_mgr = count_mgr >= s.num_shift_managers;
+            let mut male_pool = ctx.rank(candidates.iter().filter(|p| p.gender == Gender::Male && !selected.contains(&p.id) && (!prefer_non_mgr || !p.is_shift_manager)).map(|p| p.id).collect(), &selected, d);
+            if male_pool.is_empty() {
+                male_pool = ctx.rank(candidates.iter().filter(|p| p.gender == Gender::Male && !selected.contains(&p.id)).map(|p| p.id).collect(), &selected, d);
+            }
+            let Some(&add_id) = male_pool.first() else { warnings.push(WarningKind::FewerBrothers); break; };
+            if selected.len() >= s.num_publishers as usize {
+                // remove lowest scoring non-mandatory sister
+                let females = ctx.lowest_first(selected.iter().copied().filter(|pid| ctx.is_female(*pid) && !mandatory_set.contains(pid)), &selected, d);
+                let Some(&(rm, _)) = females.first() else { warnings.push(WarningKind::NoSlotForBrother); break; };
+                if let Some(pos) = selected.iter().position(|x| *x == rm) { selected.remove(pos); }
+            }
+            if ctx.is_manager(add_id) { count_mgr += 1; }
+            selected.push(add_id);
+            count_male += 1;
+        }
+    }
+
+    // fill remaining
+    let remaining_slots = (s.num_publishers as usize).saturating_sub(selected.len());
+    if remaining_slots > 0 {
+        let rest = ctx.rank(candidates.iter().filter(|p| !selected.contains(&p.id)).map(|p| p.id).collect(), &selected, d);
+        selected.extend(rest.into_iter().take(remaining_slots));
+    }
+
+    // Ensure we don't exceed capacity; prefer keeping mandatory pairs
+    let limit = s.num_publishers as usize;
+    if selected.len() > limit {
+        let mandatory_set = ctx.mandatory_set(&selected);
+        let removable = ctx.lowest_first(selected.iter().copied().filter(|pid| !mandatory_set.contains(pid)), &selected, d);
+        for (pid, _) in removable {
+            if selected.len() <= limit { break; }
+            if let Some(pos) = selected.iter().position(|x| *x == pid) { selected.remove(pos); }
+        }
+        if selected.len() > limit {
+            // As last resort, drop lowest-scoring overall
+            let overall = ctx.lowest_first(selected.iter().copied(), &selected, d);
+            for (pid, _) in overall {
+                if selected.len() <= limit { break; }
+                if let Some(pos) = selected.iter().position(|x| *x == pid) { selected.remove(pos); }
+            }
+            warnings.push(WarningKind::DroppedOverCapacity);
+        }
+        if warnings.is_empty() { warnings.push(WarningKind::TrimmedOverCapacity); }
+    }
+    if selected.len() < limit { warnings.push(WarningKind::NotEnoughPublishers); }
+    (selected, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONDAY: (i32, u32, u32) = (2024, 3, 4);
+
+    fn monday() -> NaiveDate { NaiveDate::from_ymd_opt(MONDAY.0, MONDAY.1, MONDAY.2).unwrap() }
+
+    fn publisher(id: i64, gender: Gender, is_shift_manager: bool) -> Publisher { Publisher { id, gender, is_shift_manager, priority: 1 } }
+
+    // Monday 9-11 at "Station" with the given places: (publishers, managers, brothers, sisters)
+    fn schedule(id: i64, (num_publishers, num_shift_managers, num_brothers, num_sisters): (i64, i64, i64, i64)) -> Schedule {
+        Schedule {
+            id,
+            location: "Station".into(),
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            weekday: 1,
+            recurrence: Recurrence::default(),
+            num_publishers,
+            num_shift_managers,
+            num_brothers,
+            num_sisters,
+        }
+    }
+
+    // One day, everyone available for every schedule
+    fn input(publishers: Vec<Publisher>, schedules: Vec<Schedule>) -> Input {
+        let availability = publishers.iter().flat_map(|p| schedules.iter().map(move |s| (p.id, s.id))).collect();
+        Input { publishers, schedules, availability, relationships: vec![], absences: vec![], blackouts: vec![], history: vec![], start: monday(), end: monday() }
+    }
+
+    // The same schedule moved to the afternoon, so it doesn't repeat the morning's place and time
+    fn afternoon(s: Schedule) -> Schedule {
+        Schedule { start: NaiveTime::from_hms_opt(14, 0, 0).unwrap(), end: NaiveTime::from_hms_opt(16, 0, 0).unwrap(), ..s }
+    }
+
+    fn exact() -> Options { Options { mode: Mode::Exact, ..Options::default() } }
+
+    #[test]
+    fn fills_manager_brother_and_sister_quotas() {
+        let publishers = vec![publisher(1, Gender::Male, true), publisher(2, Gender::Male, false), publisher(3, Gender::Female, false), publisher(4, Gender::Female, false)];
+        let plan = generate(&input(publishers, vec![schedule(1, (3, 1, 2, 1))]), &Options::default());
+        assert_eq!(plan.shifts.len(), 1);
+        assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+        let selected = &plan.shifts[0].publishers;
+        assert_eq!(selected.len(), 3);
+        assert!(selected.contains(&1) && selected.contains(&2));
+        assert_eq!(selected.iter().filter(|pid| [3, 4].contains(*pid)).count(), 1);
+    }
+
+    #[test]
+    fn skips_absent_publishers() {
+        let publishers = vec![publisher(1, Gender::Female, false), publisher(2, Gender::Female, false)];
+        let mut input = input(publishers, vec![schedule(1, (1, 0, 0, 1))]);
+        input.absences.push(Absence { publisher_id: 1, start: monday(), end: monday() });
+        let plan = generate(&input, &Options::default());
+        assert_eq!(plan.shifts[0].publishers, vec![2]);
+    }
+
+    #[test]
+    fn warns_when_too_few_are_available() {
+        let plan = generate(&input(vec![publisher(1, Gender::Female, false)], vec![schedule(1, (2, 0, 0, 1))]), &Options::default());
+        assert_eq!(plan.shifts[0].publishers, vec![1]);
+        assert!(plan.warnings.iter().any(|w| w.kind == WarningKind::NotEnoughPublishers));
+    }
+
+    #[test]
+    fn exact_keeps_mandatory_pairs_together_or_leaves_them_out() {
+        let publishers = vec![
+            Publisher { priority: 5, ..publisher(1, Gender::Female, false) },
+            publisher(2, Gender::Female, false),
+            Publisher { priority: 3, ..publisher(3, Gender::Female, false) },
+        ];
+        let mut pair = input(publishers, vec![schedule(1, (2, 0, 0, 1))]);
+        pair.relationships.push((1, 2, RelationshipKind::Mandatory));
+        let plan = generate(&pair, &exact());
+        let mut selected = plan.shifts[0].publishers.clone();
+        selected.sort_unstable();
+        assert_eq!(selected, vec![1, 2]);
+
+        pair.schedules = vec![schedule(1, (1, 0, 0, 1))];
+        let plan = generate(&pair, &exact());
+        assert_eq!(plan.shifts[0].publishers, vec![3]);
+        assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+    }
+
+    #[test]
+    fn exact_blocks_impossible_quotas() {
+        let publishers = (1..=4).map(|id| publisher(id, if id % 2 == 0 { Gender::Female } else { Gender::Male }, true)).collect();
+        let plan = generate(&input(publishers, vec![schedule(1, (2, 1, 1, 2))]), &exact());
+        assert!(plan.shifts.is_empty());
+        assert_eq!(plan.warnings, vec![Warning { date: monday(), schedule_id: 1, kind: WarningKind::Blocked(Constraint::Quotas) }]);
+    }
+
+    #[test]
+    fn exact_never_books_anyone_twice_a_day() {
+        let plan = generate(&input(vec![publisher(1, Gender::Female, false)], vec![schedule(1, (1, 0, 0, 1)), afternoon(schedule(2, (1, 0, 0, 1)))]), &exact());
+        assert_eq!(plan.shifts.len(), 1);
+        assert_eq!(plan.shifts[0].publishers, vec![1]);
+        let blocked = if plan.shifts[0].schedule_id == 1 { 2 } else { 1 };
+        assert_eq!(plan.warnings, vec![Warning { date: monday(), schedule_id: blocked, kind: WarningKind::Blocked(Constraint::OneShiftPerDay) }]);
+    }
+
+    #[test]
+    fn exact_fills_what_greedy_leaves_short() {
+        // Greedy gives the morning to the higher priority sister and has nobody left for the afternoon,
+        // which the brother can't make
+        let publishers = vec![publisher(1, Gender::Male, false), Publisher { priority: 5, ..publisher(2, Gender::Female, false) }];
+        let mut input = input(publishers, vec![schedule(1, (1, 0, 0, 0)), afternoon(schedule(2, (1, 0, 0, 1)))]);
+        input.availability.retain(|&a| a != (1, 2));
+        let greedy = generate(&input, &Options::default());
+        assert!(greedy.warnings.iter().any(|w| w.kind == WarningKind::NotEnoughPublishers));
+
+        let plan = generate(&input, &exact());
+        assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+        let assigned: Vec<(i64, Vec<i64>)> = plan.shifts.iter().map(|sh| (sh.schedule_id, sh.publishers.clone())).collect();
+        assert_eq!(assigned, vec![(1, vec![1]), (2, vec![2])]);
+    }
+
+    // Two sisters of equal priority for one sister's place: a tie only the jitter breaks
+    fn tied_input() -> Input { input(vec![publisher(1, Gender::Female, false), publisher(2, Gender::Female, false)], vec![schedule(1, (1, 0, 0, 1))]) }
+
+    #[test]
+    fn same_input_and_seed_give_the_same_plan() {
+        let input = tied_input();
+        for seed in [0, 7, u64::MAX] {
+            let opts = Options { seed, ..Options::default() };
+            assert_eq!(generate(&input, &opts), generate(&input, &opts));
+        }
+    }
+
+    #[test]
+    fn another_seed_can_break_a_tie_differently() {
+        let input = tied_input();
+        let assigned = |seed| generate(&input, &Options { seed, ..Options::default() }).shifts[0].publishers.clone();
+        let first = assigned(0);
+        assert_eq!(first.len(), 1);
+        assert!((1..64).any(|seed| assigned(seed) != first));
+    }
+}
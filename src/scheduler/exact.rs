@@ -0,0 +1,276 @@
+// Exact mode: fills all of a day's slots at once by branch and bound.
+// Hard constraints: exactly the slot's number of publishers, at least its managers, brothers and
+// sisters, availability, absences, one shift per publisher per day, and mandatory pairs (partners
+// who could both serve in a slot serve in it together or not at all; a partner who can't come
+// doesn't keep the other one home). Among the assignments that fill the most slots the one with
+// the best total score wins, with the same score as the greedy pass (pair terms counted once per pair).
+// Days are solved one after another, so fairness across a range builds up the same way as greedy.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use super::{Constraint, Ctx, Input, RelationshipKind, Schedule};
+
+// Search nodes per day; past it the best assignment found so far is kept
+const NODE_LIMIT: usize = 50_000;
+const EPSILON: f64 = 1e-9;
+
+// Places in a slot (exact) and managers, brothers, sisters among them (at least)
+#[derive(Clone, Copy, Default)]
+struct Counts { seats: usize, managers: usize, males: usize, females: usize }
+
+impl Counts {
+    fn plus(self, o: Counts) -> Counts {
+        Counts { seats: self.seats + o.seats, managers: self.managers + o.managers, males: self.males + o.males, females: self.females + o.females }
+    }
+
+    // One seat per publisher, counted by role
+    fn of(ctx: &Ctx, pids: impl Iterator<Item = i64>) -> Counts {
+        pids.fold(Counts::default(), |c, pid| c.plus(Counts {
+            seats: 1,
+            managers: usize::from(ctx.is_manager(pid)),
+            males: usize::from(ctx.is_male(pid)),
+            females: usize::from(ctx.is_female(pid)),
+        }))
+    }
+
+    fn meets(self, need: Counts) -> bool { self.managers >= need.managers && self.males >= need.males && self.females >= need.females }
+
+    // Whether publishers taken from `avail` could still bring `self` up to `need`
+    fn reachable(self, avail: Counts, need: Counts) -> bool {
+        let seats = need.seats - self.seats;
+        let males = need.males.saturating_sub(self.males).max(need.managers.saturating_sub(self.managers));
+        avail.seats >= seats
+            && self.plus(avail).meets(need)
+            && males + need.females.saturating_sub(self.females) <= seats
+    }
+}
+
+// Publishers who serve in a slot together or not at all: a chain of mandatory partners, or one person.
+// Members are indices into the day's publishers.
+struct Group { members: Vec<usize>, counts: Counts }
+
+struct Slot { need: Counts, groups: Vec<Group>, cands: Vec<usize> }
+
+// One day's publishers, numbered 0..n, and its slots
+struct Problem {
+    ids: Vec<i64>,
+    value: Vec<f64>,     // own score for the day
+    pair: Vec<Vec<f64>>, // bonus or penalty for two publishers sharing a shift
+    best: Vec<f64>,      // own score plus every bonus the publisher could collect
+    slots: Vec<Slot>,
+}
+
+impl Problem {
+    // Score added by putting `members` into a slot that already holds `selected`
+    fn gain(&self, selected: &[usize], members: &[usize]) -> f64 {
+        members.iter().enumerate().map(|(i, &m)| {
+            self.value[m] + selected.iter().chain(&members[..i]).map(|&o| self.pair[m][o]).sum::<f64>()
+        }).sum()
+    }
+}
+
+fn top_sum(mut values: Vec<f64>, n: usize) -> f64 {
+    values.sort_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+    values.into_iter().take(n).sum()
+}
+
+type Assignment = Vec<Option<Vec<usize>>>;
+
+// Depth first over `order` (indices into the problem's slots): fill a slot group by group, or leave it empty
+struct Search<'a> {
+    p: &'a Problem,
+    order: Vec<usize>,
+    first: bool, // stop at the first assignment that fills every slot
+    used: Vec<bool>,
+    later: Vec<f64>, // bound of the slots after order[k], taken when slot k was entered
+    current: Assignment,
+    best: Option<(usize, f64, Assignment)>,
+    nodes: usize,
+}
+
+impl<'a> Search<'a> {
+    fn run(p: &'a Problem, order: Vec<usize>, first: bool) -> Search<'a> {
+        let mut search = Search { p, order, first, used: vec![false; p.ids.len()], later: Vec::new(), current: vec![None; p.slots.len()], best: None, nodes: 0 };
+        search.next_slot(0, 0, 0.0);
+        search
+    }
+
+    fn exhausted(&self) -> bool { self.nodes > NODE_LIMIT }
+
+    fn done(&self) -> bool {
+        self.exhausted() || (self.first && self.best.as_ref().is_some_and(|(filled, _, _)| *filled == self.order.len()))
+    }
+
+    fn may_beat(&self, filled: usize, score: f64) -> bool {
+        match &self.best {
+            None => true,
+            Some((f, s, _)) => filled > *f || (filled == *f && score > s + EPSILON),
+        }
+    }
+
+    // Best conceivable score of slots order[k..] with everyone still free: each slot's own best
+    // places, or the best places of them all together (nobody serves twice), whichever is lower
+    fn bound(&self, k: usize) -> f64 {
+        let p = self.p;
+        let mut separate = 0.0;
+        let mut seats = 0;
+        let mut seen = vec![false; p.ids.len()];
+        let mut pooled = Vec::new();
+        for &s in &self.order[k..] {
+            let slot = &p.slots[s];
+            let free: Vec<usize> = slot.cands.iter().copied().filter(|&m| !self.used[m]).collect();
+            separate += top_sum(free.iter().map(|&m| p.best[m]).collect(), slot.need.seats);
+            seats += slot.need.seats;
+            for m in free {
+                if !seen[m] { seen[m] = true; pooled.push(p.best[m]); }
+            }
+        }
+        f64::min(separate, top_sum(pooled, seats))
+    }
+
+    fn next_slot(&mut self, k: usize, filled: usize, score: f64) {
+        if k == self.order.len() {
+            if self.may_beat(filled, score) { self.best = Some((filled, score, self.current.clone())); }
+            return;
+        }
+        if self.done() { return; }
+        let left = self.order.len() - k;
+        let later = self.bound(k + 1);
+        self.later.truncate(k);
+        self.later.push(later);
+        if self.may_beat(filled + left, score + self.bound(k)) {
+            self.pick(k, 0, &mut Vec::new(), Counts::default(), filled, score);
+        }
+        if !self.done() && self.may_beat(filled + left - 1, score + later) { self.next_slot(k + 1, filled, score); }
+    }
+
+    fn pick(&mut self, k: usize, i: usize, selected: &mut Vec<usize>, have: Counts, filled: usize, score: f64) {
+        self.nodes += 1;
+        if self.done() { return; }
+        let p = self.p;
+        let slot = &p.slots[self.order[k]];
+        if have.seats == slot.need.seats {
+            if have.meets(slot.need) {
+                self.current[self.order[k]] = Some(selected.clone());
+                self.next_slot(k + 1, filled + 1, score);
+                self.current[self.order[k]] = None;
+            }
+            return;
+        }
+        // What the groups left can still offer, and the most they could add
+        let mut avail = Counts::default();
+        let mut values = Vec::new();
+        for g in slot.groups[i..].iter().filter(|g| !g.members.iter().any(|&m| self.used[m])) {
+            avail = avail.plus(g.counts);
+            values.extend(g.members.iter().map(|&m| p.best[m]));
+        }
+        if !have.reachable(avail, slot.need) { return; }
+        let bound = score + top_sum(values, slot.need.seats - have.seats) + self.later[k];
+        if !self.may_beat(filled + self.order.len() - k, bound) { return; }
+
+        let g = &slot.groups[i];
+        if have.seats + g.counts.seats <= slot.need.seats && !g.members.iter().any(|&m| self.used[m]) {
+            let gain = p.gain(selected, &g.members);
+            selected.extend(&g.members);
+            for &m in &g.members { self.used[m] = true; }
+            self.pick(k, i + 1, selected, have.plus(g.counts), filled, score + gain);
+            for &m in &g.members { self.used[m] = false; }
+            selected.truncate(selected.len() - g.members.len());
+        }
+        self.pick(k, i + 1, selected, have, filled, score);
+    }
+}
+
+// Publishers for each of the day's slots, or the constraint that kept it empty
+pub(super) fn solve_day(ctx: &Ctx, input: &Input, schedules: &[&Schedule], d: NaiveDate) -> Vec<Result<Vec<i64>, Constraint>> {
+    // Everyone who could serve somewhere today, numbered in input order
+    let ids: Vec<i64> = input.publishers.iter().map(|p| p.id)
+        .filter(|&pid| !input.is_absent(pid, d) && schedules.iter().any(|s| input.is_available(pid, s.id)))
+        .collect();
+    let index: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, &pid)| (pid, i)).collect();
+    let value: Vec<f64> = ids.iter().map(|&pid| ctx.score(pid, &[], d)).collect();
+    let pair: Vec<Vec<f64>> = ids.iter().enumerate()
+        .map(|(a, &pa)| ids.iter().enumerate().map(|(b, &pb)| if a == b { 0.0 } else { ctx.score(pa, &[pb], d) - value[a] }).collect())
+        .collect();
+    let best = (0..ids.len()).map(|a| value[a] + pair[a].iter().map(|v| v.max(0.0)).sum::<f64>()).collect();
+    let mut problem = Problem { ids, value, pair, best, slots: Vec::new() };
+
+    let mut results: Vec<Result<Vec<i64>, Constraint>> = Vec::new();
+    for s in schedules {
+        let cands: Vec<usize> = problem.ids.iter().enumerate().filter(|&(_, &pid)| input.is_available(pid, s.id)).map(|(i, _)| i).collect();
+        let slot = build_slot(ctx, &problem, &index, s, cands);
+        results.push(match diagnose(ctx, input, &problem, s, &slot) {
+            Some(c) => Err(c),
+            None => Ok(Vec::new()),
+        });
+        problem.slots.push(slot);
+    }
+
+    // Slots that can't be filled even with the whole day to themselves are settled first
+    for (i, res) in results.iter_mut().enumerate() {
+        if res.is_err() { continue; }
+        let alone = Search::run(&problem, vec![i], true);
+        if alone.best.as_ref().is_none_or(|(filled, _, _)| *filled == 0) {
+            *res = Err(if alone.exhausted() { Constraint::SearchLimit } else { Constraint::MandatoryPairs });
+        }
+    }
+
+    // The rest together, tightest slots first
+    let mut order: Vec<usize> = (0..schedules.len()).filter(|&i| results[i].is_ok()).collect();
+    order.sort_by_key(|&i| (problem.slots[i].cands.len(), i));
+    let search = Search::run(&problem, order.clone(), false);
+    let best = search.best.map(|(_, _, a)| a).unwrap_or_else(|| vec![None; schedules.len()]);
+    for i in order {
+        results[i] = match &best[i] {
+            Some(selected) => Ok(selected.iter().map(|&m| problem.ids[m]).collect()),
+            None => Err(if search.nodes > NODE_LIMIT { Constraint::SearchLimit } else { Constraint::OneShiftPerDay }),
+        };
+    }
+    results
+}
+
+fn build_slot(ctx: &Ctx, p: &Problem, index: &HashMap<i64, usize>, s: &Schedule, cands: Vec<usize>) -> Slot {
+    let need = Counts {
+        seats: s.num_publishers.max(0) as usize,
+        managers: s.num_shift_managers.max(0) as usize,
+        males: s.num_brothers.max(0) as usize,
+        females: s.num_sisters.max(0) as usize,
+    };
+    let mut groups: Vec<Group> = Vec::new();
+    let mut placed = vec![false; p.ids.len()];
+    for &first in &cands {
+        if placed[first] { continue; }
+        placed[first] = true;
+        let mut members = vec![first];
+        let mut i = 0;
+        while i < members.len() {
+            for &(o, kind) in ctx.relationships.get(&p.ids[members[i]]).into_iter().flatten() {
+                let Some(&o) = index.get(&o) else { continue };
+                if kind == RelationshipKind::Mandatory && cands.contains(&o) && !placed[o] {
+                    placed[o] = true;
+                    members.push(o);
+                }
+            }
+            i += 1;
+        }
+        members.sort_unstable();
+        let counts = Counts::of(ctx, members.iter().map(|&m| p.ids[m]));
+        groups.push(Group { members, counts });
+    }
+    // Most promising first, so good assignments turn up early and prune the rest
+    let avg = |g: &Group| g.members.iter().map(|&m| p.best[m]).sum::<f64>() / g.members.len() as f64;
+    groups.sort_by(|a, b| avg(b).partial_cmp(&avg(a)).unwrap_or(Ordering::Equal).then_with(|| a.members.cmp(&b.members)));
+    Slot { need, groups, cands }
+}
+
+// The first constraint that rules the slot out by counting alone
+fn diagnose(ctx: &Ctx, input: &Input, p: &Problem, s: &Schedule, slot: &Slot) -> Option<Constraint> {
+    let (need, have) = (slot.need, Counts::of(ctx, slot.cands.iter().map(|&m| p.ids[m])));
+    if need.males.max(need.managers) + need.females > need.seats { return Some(Constraint::Quotas); }
+    if input.publishers.iter().filter(|p| input.is_available(p.id, s.id)).count() < need.seats { return Some(Constraint::Availability); }
+    if have.seats < need.seats { return Some(Constraint::Absences); }
+    if have.managers < need.managers { return Some(Constraint::Managers); }
+    if have.males < need.males { return Some(Constraint::Brothers); }
+    if have.females < need.females { return Some(Constraint::Sisters); }
+    None
+}
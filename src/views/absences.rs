@@ -1,12 +1,7 @@
 use dioxus::prelude::*;
-#[allow(unused_imports)]
+use chrono::NaiveDate;
 use crate::i18n::format_date_ymd;
 use crate::i18n::t;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] use crate::db::dao;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] use crate::db::dao::Absence as NativeAbsence;
-#[cfg(target_arch = "wasm32")] use crate::db::wasm_store as wasm_backend;
-#[cfg(target_arch = "wasm32")] use wasm_backend::Absence as WebAbsence;
-#[cfg(target_arch = "wasm32")] use wasm_backend::Publisher as WebPublisher;
 
 const PAGE_SIZE: usize = 25;
 
@@ -19,6 +14,14 @@ struct AbsenceForm { id: Option<i64>, publisher_id: String, start_date: String,
 #[derive(Clone)]
 struct AbsenceItem { id: i64, title: String, subtitle: String, _publisher_id: i64 }
 
+// Upcoming absences labelled with the publisher names from `publishers`
+fn load_items(publishers: &[(i64, String)]) -> Vec<AbsenceItem> {
+    crate::db::store().list_future_absences(crate::db::today()).unwrap_or_default().into_iter().map(|a| {
+        let name = publishers.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id));
+        AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date.to_string()), format_date_ymd(&a.end_date.to_string())), subtitle: a.description.unwrap_or_default() }
+    }).collect()
+}
+
 #[component]
 #[allow(unused_mut)]
 pub fn Absences() -> Element {
@@ -36,42 +39,14 @@ pub fn Absences() -> Element {
     let mut publishers = use_signal(|| Vec::<(i64, String)>::new());
 
     use_effect(move || {
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            // load configuration for name order
-            let order = dao::get_configuration().ok().map(|c| c.name_order).unwrap_or_else(|| "first_last".into());
-            if let Ok(items) = dao::list_publishers() {
-                let mapped = items.into_iter().map(|p| if order == "last_first" { (p.id, format!("{} {}", p.last_name, p.first_name)) } else { (p.id, format!("{} {}", p.first_name, p.last_name)) }).collect();
-                publishers.set(mapped);
-            }
-            let today_effect = chrono::Local::now().date_naive();
-            let _ = dao::cleanup_expired_absences(today_effect);
-            if let Ok(items) = dao::list_future_absences(chrono::Local::now().date_naive()) {
-                let name_lookup = publishers.read().clone();
-                let mapped = items.into_iter().map(|a: NativeAbsence| {
-                    let name = name_lookup.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id));
-                    AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date.to_string()), format_date_ymd(&a.end_date.to_string())), subtitle: a.description.clone().unwrap_or_default() }
-                }).collect::<Vec<_>>();
-                raw.set(mapped.clone()); list.set(mapped);
-            }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let mut ps = wasm_backend::list_publishers();
-            // build labels based on configuration
-            let order = wasm_backend::get_name_order();
-            let mapped = ps.drain(..).map(|p: WebPublisher| if order == "last_first" { (p.id, format!("{} {}", p.last_name, p.first_name)) } else { (p.id, format!("{} {}", p.first_name, p.last_name)) }).collect::<Vec<_>>();
-            publishers.set(mapped);
-            let now = js_sys::Date::new_0();
-            let today_effect = format!("{:04}-{:02}-{:02}", now.get_full_year() as i32, now.get_month() as u32 + 1, now.get_date() as u32);
-            wasm_backend::cleanup_expired_absences(&today_effect);
-            let name_lookup = publishers.read().clone();
-            let mapped = wasm_backend::list_future_absences(&today_effect).into_iter().map(|a: WebAbsence| {
-                let name = name_lookup.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id));
-                AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date), format_date_ymd(&a.end_date)), subtitle: a.description.unwrap_or_default() }
-            }).collect::<Vec<_>>();
-            raw.set(mapped.clone()); list.set(mapped);
-        }
+        let store = crate::db::store();
+        // labels honor the configured name order
+        let order = store.name_order();
+        let mapped = store.list_publishers().unwrap_or_default().into_iter().map(|p| (p.id, p.display_name(&order))).collect::<Vec<_>>();
+        publishers.set(mapped);
+        let _ = store.cleanup_expired_absences(crate::db::today());
+        let mapped = load_items(&publishers.read());
+        raw.set(mapped.clone()); list.set(mapped);
     });
 
     let mut apply_filter = {
@@ -99,39 +74,20 @@ pub fn Absences() -> Element {
     if f.publisher_id.trim().is_empty() || f.start_date.trim().is_empty() || f.end_date.trim().is_empty() { error.set(Some(t("absences.error_required"))); return; }
         let pid = f.publisher_id.parse::<i64>().unwrap_or(0);
     if pid <= 0 { error.set(Some(t("absences.error_invalid_publisher"))); return; }
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            if let Some(id) = f.id { let _ = dao::update_absence(id, pid, chrono::NaiveDate::parse_from_str(&f.start_date, "%Y-%m-%d").unwrap(), chrono::NaiveDate::parse_from_str(&f.end_date, "%Y-%m-%d").unwrap(), if f.description.trim().is_empty() { None } else { Some(&*f.description) }); }
-            else { let _ = dao::create_absence(pid, chrono::NaiveDate::parse_from_str(&f.start_date, "%Y-%m-%d").unwrap(), chrono::NaiveDate::parse_from_str(&f.end_date, "%Y-%m-%d").unwrap(), if f.description.trim().is_empty() { None } else { Some(&*f.description) }); }
-            let today_submit = chrono::Local::now().date_naive();
-            if let Ok(items) = dao::list_future_absences(today_submit) {
-                let name_lookup = publishers.read().clone();
-                let mapped = items.into_iter().map(|a: NativeAbsence| {
-                    let name = name_lookup.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id));
-                    AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date.to_string()), format_date_ymd(&a.end_date.to_string())), subtitle: a.description.clone().unwrap_or_default() }
-                }).collect::<Vec<_>>(); raw.set(mapped.clone()); list.set(mapped);
-            }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let now = js_sys::Date::new_0();
-            let today_submit = format!("{:04}-{:02}-{:02}", now.get_full_year() as i32, now.get_month() as u32 + 1, now.get_date() as u32);
-            if let Some(id) = f.id { wasm_backend::update_absence(id, pid, &f.start_date, &f.end_date, if f.description.trim().is_empty() { None } else { Some(&*f.description) }); }
-            else { let _ = wasm_backend::create_absence(pid, &f.start_date, &f.end_date, if f.description.trim().is_empty() { None } else { Some(&*f.description) }); }
-            let name_lookup = publishers.read().clone();
-            let mapped = wasm_backend::list_future_absences(&today_submit).into_iter().map(|a: WebAbsence| {
-                let name = name_lookup.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id));
-                AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date), format_date_ymd(&a.end_date)), subtitle: a.description.unwrap_or_default() }
-            }).collect::<Vec<_>>(); raw.set(mapped.clone()); list.set(mapped);
-        }
+        let (Ok(start), Ok(end)) = (NaiveDate::parse_from_str(&f.start_date, "%Y-%m-%d"), NaiveDate::parse_from_str(&f.end_date, "%Y-%m-%d")) else { error.set(Some(t("absences.error_required"))); return; };
+        let desc = if f.description.trim().is_empty() { None } else { Some(&*f.description) };
+        let store = crate::db::store();
+        let res = match f.id { Some(id) => store.update_absence(id, pid, start, end, desc), None => store.create_absence(pid, start, end, desc).map(|_| ()) };
+        if let Err(e) = res { error.set(Some(e.to_string())); return; }
+        let mapped = load_items(&publishers.read());
+        raw.set(mapped.clone()); list.set(mapped);
         modal_open.set(false);
     };
 
-    let mut delete_absence = move |_id: i64| {
-    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-    { let _ = dao::delete_absence(_id); let today_del = chrono::Local::now().date_naive(); if let Ok(items) = dao::list_future_absences(today_del) { let name_lookup = publishers.read().clone(); let mapped = items.into_iter().map(|a: NativeAbsence| { let name = name_lookup.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id)); AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date.to_string()), format_date_ymd(&a.end_date.to_string())), subtitle: a.description.clone().unwrap_or_default() } }).collect::<Vec<_>>(); raw.set(mapped.clone()); list.set(mapped); } }
-        #[cfg(target_arch = "wasm32")]
-    { wasm_backend::delete_absence(_id); let now = js_sys::Date::new_0(); let today_del = format!("{:04}-{:02}-{:02}", now.get_full_year() as i32, now.get_month() as u32 + 1, now.get_date() as u32); let name_lookup = publishers.read().clone(); let mapped = wasm_backend::list_future_absences(&today_del).into_iter().map(|a: WebAbsence| { let name = name_lookup.iter().find(|(id, _)| *id == a.publisher_id).map(|(_, n)| n.clone()).unwrap_or_else(|| format!("#{:?}", a.publisher_id)); AbsenceItem { id: a.id, _publisher_id: a.publisher_id, title: format!("{} • {} → {}", name, format_date_ymd(&a.start_date), format_date_ymd(&a.end_date)), subtitle: a.description.unwrap_or_default() } }).collect::<Vec<_>>(); raw.set(mapped.clone()); list.set(mapped); }
+    let mut delete_absence = move |id: i64| {
+        let _ = crate::db::store().delete_absence(id);
+        let mapped = load_items(&publishers.read());
+        raw.set(mapped.clone()); list.set(mapped);
     };
     rsx! {
         div { class: "min-h-[70vh] flex items-start justify-center",
@@ -294,44 +250,20 @@ pub fn Absences() -> Element {
                                                             class: "min-w-0 flex-1 cursor-pointer hover:bg-slate-50 dark:hover:bg-slate-700/30 rounded-md px-3 -mx-3 py-2",
                                                             onclick: move |_| {
                                                                 error.set(None);
-                                                                #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                                                {
-                                                                    let t = chrono::Local::now().date_naive();
-                                                                    if let Ok(items) = dao::list_future_absences(t) {
-                                                                        if let Some(a) = items.into_iter().find(|x| x.id == p.id) {
-                                                                            form.set(AbsenceForm {
-                                                                                id: Some(a.id),
-                                                                                publisher_id: a.publisher_id.to_string(),
-                                                                                start_date: a.start_date.to_string(),
-                                                                                end_date: a.end_date.to_string(),
-                                                                                description: a.description.unwrap_or_default(),
-                                                                            });
-                                                                            modal_open.set(true);
-                                                                        }
-                                                                    }
-                                                                }
-                                                                #[cfg(target_arch = "wasm32")]
+                                                                if let Some(a) = crate::db::store()
+                                                                    .list_future_absences(crate::db::today())
+                                                                    .unwrap_or_default()
+                                                                    .into_iter()
+                                                                    .find(|x| x.id == p.id)
                                                                 {
-                                                                    let now = js_sys::Date::new_0();
-                                                                    let t = format!(
-                                                                        "{:04}-{:02}-{:02}",
-                                                                        now.get_full_year() as i32,
-                                                                        now.get_month() as u32 + 1,
-                                                                        now.get_date() as u32,
-                                                                    );
-                                                                    if let Some(a) = wasm_backend::list_future_absences(&t)
-                                                                        .into_iter()
-                                                                        .find(|x| x.id == p.id)
-                                                                    {
-                                                                        form.set(AbsenceForm {
-                                                                            id: Some(a.id),
-                                                                            publisher_id: a.publisher_id.to_string(),
-                                                                            start_date: a.start_date.to_string(),
-                                                                            end_date: a.end_date.to_string(),
-                                                                            description: a.description.unwrap_or_default(),
-                                                                        });
-                                                                        modal_open.set(true);
-                                                                    }
+                                                                    form.set(AbsenceForm {
+                                                                        id: Some(a.id),
+                                                                        publisher_id: a.publisher_id.to_string(),
+                                                                        start_date: a.start_date.to_string(),
+                                                                        end_date: a.end_date.to_string(),
+                                                                        description: a.description.unwrap_or_default(),
+                                                                    });
+                                                                    modal_open.set(true);
                                                                 }
                                                             },
                                                             div { class: "font-medium text-slate-800 dark:text-slate-100", {p.title.clone()} }
@@ -463,77 +395,12 @@ pub fn Absences() -> Element {
                                                     selected.set(v);
                                                 }
                                                 Some(ConfirmAction::DeleteMany(ids)) => {
-                                                    let _ = &ids;
-                                                    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                                    {
-                                                        for id in ids.iter().copied() {
-                                                            let _ = dao::delete_absence(id);
-                                                        }
-                                                        let t = chrono::Local::now().date_naive();
-                                                        if let Ok(items) = dao::list_future_absences(t) {
-                                                            let name_lookup = publishers.read().clone();
-                                                            let mapped = items
-                                                                .into_iter()
-                                                                .map(|a: NativeAbsence| {
-                                                                    let name = name_lookup
-                                                                        .iter()
-                                                                        .find(|(id, _)| *id == a.publisher_id)
-                                                                        .map(|(_, n)| n.clone())
-                                                                        .unwrap_or_else(|| format!("#{:?}", a.publisher_id));
-                                                                    AbsenceItem {
-                                                                        id: a.id,
-                                                                        _publisher_id: a.publisher_id,
-                                                                        title: format!(
-                                                                            "{} • {} → {}",
-                                                                            name,
-                                                                            format_date_ymd(&a.start_date.to_string()),
-                                                                            format_date_ymd(&a.end_date.to_string()),
-                                                                        ),
-                                                                        subtitle: a.description.clone().unwrap_or_default(),
-                                                                    }
-                                                                })
-                                                                .collect::<Vec<_>>();
-                                                            raw.set(mapped.clone());
-                                                            list.set(mapped);
-                                                        }
-                                                    }
-                                                    #[cfg(target_arch = "wasm32")]
-                                                    {
-                                                        for id in ids.iter().copied() {
-                                                            wasm_backend::delete_absence(id);
-                                                        }
-                                                        let now = js_sys::Date::new_0();
-                                                        let t = format!(
-                                                            "{:04}-{:02}-{:02}",
-                                                            now.get_full_year() as i32,
-                                                            now.get_month() as u32 + 1,
-                                                            now.get_date() as u32,
-                                                        );
-                                                        let name_lookup = publishers.read().clone();
-                                                        let mapped = wasm_backend::list_future_absences(&t)
-                                                            .into_iter()
-                                                            .map(|a: WebAbsence| {
-                                                                let name = name_lookup
-                                                                    .iter()
-                                                                    .find(|(id, _)| *id == a.publisher_id)
-                                                                    .map(|(_, n)| n.clone())
-                                                                    .unwrap_or_else(|| format!("#{:?}", a.publisher_id));
-                                                                AbsenceItem {
-                                                                    id: a.id,
-                                                                    _publisher_id: a.publisher_id,
-                                                                    title: format!(
-                                                                        "{} • {} → {}",
-                                                                        name,
-                                                                        format_date_ymd(&a.start_date),
-                                                                        format_date_ymd(&a.end_date),
-                                                                    ),
-                                                                    subtitle: a.description.unwrap_or_default(),
-                                                                }
-                                                            })
-                                                            .collect::<Vec<_>>();
-                                                        raw.set(mapped.clone());
-                                                        list.set(mapped);
+                                                    for id in ids.iter().copied() {
+                                                        let _ = crate::db::store().delete_absence(id);
                                                     }
+                                                    let mapped = load_items(&publishers.read());
+                                                    raw.set(mapped.clone());
+                                                    list.set(mapped);
                                                     selected.set(vec![]);
                                                 }
                                                 None => {}
@@ -0,0 +1,146 @@
+use dioxus::prelude::*;
+use crate::db::backups::{self, Backup, Reason, Retention};
+use crate::i18n::{format_date_ymd, t};
+
+const KEEP_COUNTS: [usize; 5] = [3, 5, 10, 20, 50];
+const KEEP_DAYS: [u32; 5] = [7, 30, 90, 365, 0];
+
+fn size_text(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 { format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)) } else { format!("{} KB", bytes.div_ceil(1024)) }
+}
+
+// Configuration section on plain native builds: retention settings, a manual snapshot and the
+// snapshot list with restore. `on_restored` lets the page reload what the restore replaced.
+#[component]
+pub fn Backups(on_restored: EventHandler<()>) -> Element {
+    let mut list = use_signal(backups::list);
+    let mut retention = use_signal(backups::retention);
+    let mut message = use_signal(|| Option::<String>::None);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut confirm_restore = use_signal(|| Option::<Backup>::None);
+
+    let mut set_retention = move |r: Retention| {
+        error.set(None);
+        match backups::set_retention(r) {
+            Ok(()) => { retention.set(r); list.set(backups::list()); }
+            Err(e) => error.set(Some(format!("{}: {}", t("backups.failed"), e))),
+        }
+    };
+
+    let on_backup_now = move |_| {
+        message.set(None);
+        error.set(None);
+        match backups::take(Reason::Manual) {
+            Ok(_) => message.set(Some(t("backups.done"))),
+            Err(e) => error.set(Some(format!("{}: {}", t("backups.failed"), e))),
+        }
+        list.set(backups::list());
+    };
+
+    let on_restore = move |_| {
+        let Some(b) = confirm_restore.read().clone() else { return };
+        confirm_restore.set(None);
+        message.set(None);
+        error.set(None);
+        match backups::restore(&b.path) {
+            Ok(()) => { message.set(Some(t("backups.restored"))); on_restored.call(()); }
+            Err(e) => error.set(Some(format!("{}: {}", t("backups.restore_failed"), e))),
+        }
+        list.set(backups::list());
+    };
+
+    let select_class = "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500";
+    let when = |b: &Backup| format!("{} {}", format_date_ymd(&b.taken_at.date().to_string()), b.taken_at.format("%H:%M"));
+
+    rsx! {
+        div { class: "pt-2 border-t border-slate-200 dark:border-slate-700 mt-2 space-y-3",
+            h2 { class: "text-sm font-semibold text-slate-700 dark:text-slate-200",
+                {t("backups.title")}
+            }
+            p { class: "text-xs text-slate-500 dark:text-slate-400 break-all",
+                {format!("{} {}", t("backups.help"), backups::dir().display())}
+            }
+            div { class: "grid grid-cols-2 gap-3",
+                div { class: "flex flex-col gap-2",
+                    label { class: "text-sm font-medium text-slate-700 dark:text-slate-200", {t("backups.keep_count")} }
+                    select {
+                        class: select_class,
+                        value: retention().keep_count.to_string(),
+                        onchange: move |e| if let Ok(n) = e.value().parse() { set_retention(Retention { keep_count: n, ..retention() }) },
+                        for n in KEEP_COUNTS {
+                            option { value: "{n}", "{n}" }
+                        }
+                    }
+                }
+                div { class: "flex flex-col gap-2",
+                    label { class: "text-sm font-medium text-slate-700 dark:text-slate-200", {t("backups.keep_days")} }
+                    select {
+                        class: select_class,
+                        value: retention().keep_days.to_string(),
+                        onchange: move |e| if let Ok(d) = e.value().parse() { set_retention(Retention { keep_days: d, ..retention() }) },
+                        for d in KEEP_DAYS {
+                            option { value: "{d}",
+                                {if d == 0 { t("backups.keep_forever") } else { format!("{} {}", d, t("backups.days")) }}
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                class: "inline-flex items-center justify-center gap-2 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium px-4 py-2 transition w-full h-10",
+                onclick: on_backup_now,
+                {t("backups.now")}
+            }
+            {message.read().as_ref().map(|m| rsx! {
+                p { class: "text-sm text-green-600 text-center", {m.clone()} }
+            })}
+            {error.read().as_ref().map(|e| rsx! {
+                p { class: "text-sm text-red-600 text-center", {e.clone()} }
+            })}
+            if list.read().is_empty() {
+                p { class: "text-sm text-slate-500 dark:text-slate-400 text-center", {t("backups.none")} }
+            } else {
+                ul { class: "max-h-64 overflow-y-auto divide-y divide-slate-200 dark:divide-slate-700 rounded-md border border-slate-200 dark:border-slate-700",
+                    for b in list.read().iter().cloned() {
+                        li { class: "flex items-center justify-between gap-2 px-3 py-2 text-sm",
+                            div { class: "flex flex-col",
+                                span { {when(&b)} }
+                                span { class: "text-xs text-slate-500 dark:text-slate-400",
+                                    {format!("{} · {}", t(b.reason.label_key()), size_text(b.size))}
+                                }
+                            }
+                            button {
+                                class: "inline-flex items-center h-8 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-xs font-medium transition",
+                                onclick: move |_| confirm_restore.set(Some(b.clone())),
+                                {t("backups.restore")}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        {confirm_restore.read().as_ref().map(|b| rsx! {
+            div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                    h2 { class: "text-lg font-semibold", {t("backups.confirm_title")} }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300",
+                        {format!("{} {}", t("backups.confirm_message"), when(b))}
+                    }
+                    div { class: "flex items-center justify-end gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| confirm_restore.set(None),
+                            {t("common.cancel")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-red-600 hover:bg-red-500 text-white text-sm font-medium transition",
+                            onclick: on_restore,
+                            {t("backups.restore")}
+                        }
+                    }
+                }
+            }
+        })}
+    }
+}
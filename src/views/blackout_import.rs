@@ -0,0 +1,122 @@
+use dioxus::prelude::*;
+use crate::i18n::t;
+use crate::db::store::Blackout;
+use crate::import::blackouts;
+
+// Date list import for the Blackouts page: load a file (or pasted lines), review and import.
+// Rows with errors and dates already listed are skipped. `on_close` gets true after an import.
+#[component]
+pub fn BlackoutImport(existing: Vec<Blackout>, on_close: EventHandler<bool>) -> Element {
+    let mut text = use_signal(String::new);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    let existing = use_signal(move || existing);
+    let date_format = use_signal(|| crate::db::store().get_configuration().map(|c| c.date_format).unwrap_or_default());
+
+    let preview = move || blackouts::rows(&text.read(), &date_format.read(), &existing.read());
+
+    let on_file = move |e: FormEvent| async move {
+        if let Some(file) = e.files().into_iter().next() {
+            match file.read_string().await {
+                Ok(s) => { error.set(None); text.set(s); }
+                Err(_) => error.set(Some(t("blackouts.import_unreadable"))),
+            }
+        }
+    };
+
+    let on_import = move |_| {
+        let chosen: Vec<Blackout> = preview().into_iter().filter(|r| r.error.is_none() && !r.duplicate).map(|r| r.blackout).collect();
+        if chosen.is_empty() { return; }
+        match crate::db::store().create_blackouts(&chosen) {
+            Ok(_) => on_close.call(true),
+            Err(e) => error.set(Some(format!("{}: {}", t("blackouts.import_failed"), e))),
+        }
+    };
+
+    let rows = preview();
+    let count = rows.iter().filter(|r| r.error.is_none() && !r.duplicate).count();
+    let total = rows.len();
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+            div { class: "w-full max-w-2xl max-h-[90vh] overflow-y-auto rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                h2 { class: "text-lg font-semibold", {t("blackouts.import_title")} }
+                {error.read().as_ref().map(|err| rsx! {
+                    p { class: "text-red-600 text-sm", {err.clone()} }
+                })}
+                div { class: "space-y-2",
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("blackouts.import_hint")} }
+                    input {
+                        r#type: "file",
+                        accept: ".csv,.tsv,.txt,text/csv,text/plain",
+                        class: "block w-full text-sm",
+                        onchange: on_file,
+                    }
+                    textarea {
+                        class: "w-full h-28 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        placeholder: "2026-04-02,,,Memorial\n2026-07-17,2026-07-19,,Convention",
+                        value: text.read().clone(),
+                        oninput: move |e| { error.set(None); text.set(e.value()); },
+                    }
+                }
+                {(total > 0).then(|| rsx! {
+                    div { class: "max-h-80 overflow-auto rounded-md border border-slate-200 dark:border-slate-700",
+                        table { class: "w-full text-sm",
+                            thead { class: "bg-slate-50 dark:bg-slate-900 text-left",
+                                tr {
+                                    th { class: "px-2 py-1", "#" }
+                                    th { class: "px-2 py-1", {t("blackouts.dates")} }
+                                    th { class: "px-2 py-1", {t("blackouts.location")} }
+                                    th { class: "px-2 py-1", {t("blackouts.reason")} }
+                                    th { class: "px-2 py-1" }
+                                }
+                            }
+                            tbody { class: "divide-y divide-slate-200 dark:divide-slate-700",
+                                for row in rows.iter().cloned() {
+                                    {
+                                        let b = &row.blackout;
+                                        let dates = if row.error == Some(blackouts::RowError::Date) { String::new() } else { super::blackouts::range_label(b) };
+                                        let note = match row.error {
+                                            Some(err) => t(err.key()),
+                                            None if row.duplicate => t("blackouts.import_duplicate"),
+                                            None => String::new(),
+                                        };
+                                        let tone = if row.error.is_some() { "text-red-600" } else { "text-amber-600" };
+                                        rsx! {
+                                            tr { class: if row.error.is_some() || row.duplicate { "opacity-60" } else { "" },
+                                                td { class: "px-2 py-1 text-slate-500", "{row.line}" }
+                                                td { class: "px-2 py-1", {dates} }
+                                                td { class: "px-2 py-1", {b.location.clone().unwrap_or_else(|| t("blackouts.all_locations"))} }
+                                                td { class: "px-2 py-1", {b.reason.clone().unwrap_or_default()} }
+                                                td { class: "px-2 py-1 text-xs {tone}", {note} }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                })}
+                div { class: "flex items-center justify-between gap-2",
+                    span { class: "text-sm text-slate-600 dark:text-slate-300",
+                        {(total > 0).then(|| format!("{} {} {} {}", count, t("common.of"), total, t("blackouts.import_selected")))}
+                    }
+                    div { class: "flex items-center gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| on_close.call(false),
+                            {t("common.cancel")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition disabled:opacity-50",
+                            disabled: count == 0,
+                            onclick: on_import,
+                            {t("blackouts.import_submit")}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
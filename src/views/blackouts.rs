@@ -0,0 +1,255 @@
+use dioxus::prelude::*;
+use chrono::NaiveDate;
+use crate::db::store::Blackout;
+use crate::i18n::{format_date_ymd, t};
+use super::blackout_import::BlackoutImport;
+
+#[derive(PartialEq, Clone, Default)]
+struct BlackoutForm { id: Option<i64>, start_date: String, end_date: String, location: String, reason: String }
+
+// One day, or "first → last", in the configured date format
+pub(super) fn range_label(b: &Blackout) -> String {
+    if b.start_date == b.end_date { format_date_ymd(&b.start_date.to_string()) } else { format!("{} → {}", format_date_ymd(&b.start_date.to_string()), format_date_ymd(&b.end_date.to_string())) }
+}
+
+#[component]
+pub fn Blackouts() -> Element {
+    let mut all = use_signal(Vec::<Blackout>::new);
+    // Schedule locations offered by the location selector
+    let mut locations = use_signal(Vec::<String>::new);
+    let mut show_past = use_signal(|| false);
+    let mut modal_open = use_signal(|| false);
+    let mut import_open = use_signal(|| false);
+    let mut form = use_signal(BlackoutForm::default);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut confirm_delete = use_signal(|| Option::<i64>::None);
+
+    let mut reload = move || {
+        let store = crate::db::store();
+        all.set(store.list_blackouts().unwrap_or_default());
+        let mut locs: Vec<String> = store.list_schedules().unwrap_or_default().into_iter().map(|s| s.location.trim().to_string()).collect();
+        locs.sort();
+        locs.dedup();
+        locations.set(locs);
+    };
+    use_effect(reload);
+
+    let open_create = move |_| {
+        error.set(None);
+        form.set(BlackoutForm::default());
+        modal_open.set(true);
+    };
+
+    let on_submit = move |_| {
+        error.set(None);
+        let f = form.read().clone();
+        let Ok(start) = NaiveDate::parse_from_str(&f.start_date, "%Y-%m-%d") else { error.set(Some(t("blackouts.error_required"))); return; };
+        let end = if f.end_date.is_empty() { Ok(start) } else { NaiveDate::parse_from_str(&f.end_date, "%Y-%m-%d") };
+        let Ok(end) = end else { error.set(Some(t("blackouts.error_required"))); return; };
+        if end < start { error.set(Some(t("blackouts.error_dates"))); return; }
+        let optional = |v: &str| Some(v.trim()).filter(|v| !v.is_empty()).map(str::to_string);
+        let b = Blackout { id: f.id.unwrap_or(0), start_date: start, end_date: end, location: optional(&f.location), reason: optional(&f.reason) };
+        let store = crate::db::store();
+        let res = match f.id { Some(_) => store.update_blackout(&b), None => store.create_blackout(&b).map(|_| ()) };
+        if let Err(e) = res { error.set(Some(e.to_string())); return; }
+        reload();
+        modal_open.set(false);
+    };
+
+    let on_delete = move |_| {
+        if let Some(id) = confirm_delete() { let _ = crate::db::store().delete_blackout(id); }
+        confirm_delete.set(None);
+        modal_open.set(false);
+        reload();
+    };
+
+    let today = crate::db::today();
+    let items: Vec<Blackout> = all.read().iter().filter(|b| show_past() || b.end_date >= today).cloned().collect();
+    let input_class = "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500";
+
+    rsx! {
+        div { class: "min-h-[70vh] flex items-start justify-center",
+            div { class: "w-full max-w-2xl mx-auto space-y-5",
+                div { class: "flex items-center justify-between",
+                    a {
+                        href: "/",
+                        class: "inline-flex items-center gap-2 h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                        span { "←" }
+                        span { class: "hidden sm:inline", {t("nav.home")} }
+                    }
+                    div { class: "flex items-center gap-2",
+                        button {
+                            class: "inline-flex items-center gap-2 h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| import_open.set(true),
+                            span { "📥" }
+                            span { class: "hidden sm:inline", {t("blackouts.import")} }
+                        }
+                        button {
+                            class: "inline-flex items-center gap-2 h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition",
+                            onclick: open_create,
+                            span { "➕" }
+                            span { class: "hidden sm:inline", {t("common.new")} }
+                        }
+                    }
+                }
+                div { class: "rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-sm p-4 sm:p-5 space-y-4",
+                    div { class: "flex flex-col sm:flex-row gap-2 sm:items-center sm:justify-between",
+                        h1 { class: "text-xl sm:text-2xl font-semibold", {t("nav.blackouts")} }
+                        label { class: "inline-flex items-center gap-2 text-sm text-slate-600 dark:text-slate-300",
+                            input {
+                                r#type: "checkbox",
+                                checked: show_past(),
+                                onchange: move |e| show_past.set(e.checked()),
+                            }
+                            span { {t("blackouts.show_past")} }
+                        }
+                    }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("blackouts.help")} }
+                    if items.is_empty() {
+                        div { class: "text-sm text-slate-600 dark:text-slate-300", {t("blackouts.empty")} }
+                    } else {
+                        ul { class: "divide-y divide-slate-200 dark:divide-slate-700",
+                            for b in items.into_iter() {
+                                li {
+                                    class: if b.end_date < today { "py-3 opacity-60" } else { "py-3" },
+                                    div {
+                                        class: "cursor-pointer hover:bg-slate-50 dark:hover:bg-slate-700/30 rounded-md px-3 -mx-3 py-2",
+                                        onclick: {
+                                            let b = b.clone();
+                                            move |_| {
+                                                error.set(None);
+                                                form.set(BlackoutForm {
+                                                    id: Some(b.id),
+                                                    start_date: b.start_date.to_string(),
+                                                    end_date: b.end_date.to_string(),
+                                                    location: b.location.clone().unwrap_or_default(),
+                                                    reason: b.reason.clone().unwrap_or_default(),
+                                                });
+                                                modal_open.set(true);
+                                            }
+                                        },
+                                        div { class: "font-medium text-slate-800 dark:text-slate-100", {range_label(&b)} }
+                                        div { class: "text-xs text-slate-500",
+                                            {format!("{} · {}", b.reason.clone().unwrap_or_else(|| t("blackouts.no_shifts")), b.location.clone().unwrap_or_else(|| t("blackouts.all_locations")))}
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        {modal_open().then(|| rsx! {
+            div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                    h2 { class: "text-lg font-semibold",
+                        {if form.read().id.is_some() { t("blackouts.edit_title") } else { t("blackouts.new_title") }}
+                    }
+                    {error.read().as_ref().map(|err| rsx! {
+                        p { class: "text-red-600 text-sm", {err.clone()} }
+                    })}
+                    div { class: "grid grid-cols-2 gap-3",
+                        div { class: "space-y-1",
+                            label { class: "text-xs text-slate-600 dark:text-slate-300", {t("blackouts.start_date")} }
+                            input {
+                                r#type: "date",
+                                class: "{input_class} w-full",
+                                value: form.read().start_date.clone(),
+                                oninput: move |e| form.write().start_date = e.value(),
+                            }
+                        }
+                        div { class: "space-y-1",
+                            label { class: "text-xs text-slate-600 dark:text-slate-300", {t("blackouts.end_date")} }
+                            input {
+                                r#type: "date",
+                                class: "{input_class} w-full",
+                                value: form.read().end_date.clone(),
+                                oninput: move |e| form.write().end_date = e.value(),
+                            }
+                        }
+                    }
+                    div { class: "grid grid-cols-1 gap-3",
+                        div { class: "space-y-1",
+                            label { class: "text-xs text-slate-600 dark:text-slate-300", {t("blackouts.location")} }
+                            select {
+                                class: "{input_class} w-full",
+                                value: form.read().location.clone(),
+                                onchange: move |e| form.write().location = e.value(),
+                                option { value: "", selected: form.read().location.is_empty(), {t("blackouts.all_locations")} }
+                                for loc in locations.read().iter().cloned() {
+                                    option { value: "{loc}", selected: form.read().location == loc, "{loc}" }
+                                }
+                                // A location no schedule uses any more stays selectable
+                                {(!form.read().location.is_empty() && !locations.read().contains(&form.read().location)).then(|| {
+                                    let loc = form.read().location.clone();
+                                    rsx! { option { value: "{loc}", selected: true, "{loc}" } }
+                                })}
+                            }
+                        }
+                        input {
+                            r#type: "text",
+                            class: input_class,
+                            placeholder: t("blackouts.reason_optional"),
+                            value: form.read().reason.clone(),
+                            oninput: move |e| form.write().reason = e.value(),
+                        }
+                    }
+                    div { class: "flex items-center justify-between gap-2",
+                        {form.read().id.map(|id| rsx! {
+                            button {
+                                class: "inline-flex items-center h-9 px-3 rounded-md border border-red-300 text-red-700 text-sm font-medium transition",
+                                onclick: move |_| confirm_delete.set(Some(id)),
+                                {t("common.delete")}
+                            }
+                        })}
+                        div { class: "flex items-center gap-2 ml-auto",
+                            button {
+                                class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                                onclick: move |_| modal_open.set(false),
+                                {t("common.cancel")}
+                            }
+                            button {
+                                class: "inline-flex items-center h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition",
+                                onclick: on_submit,
+                                {if form.read().id.is_some() { t("common.save") } else { t("common.create") }}
+                            }
+                        }
+                    }
+                }
+            }
+        })}
+
+        {confirm_delete().is_some().then(|| rsx! {
+            div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                    h2 { class: "text-lg font-semibold", {t("common.confirm_delete_title")} }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("blackouts.confirm_delete")} }
+                    div { class: "flex items-center justify-end gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| confirm_delete.set(None),
+                            {t("common.cancel")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-red-600 hover:bg-red-500 text-white text-sm font-medium transition",
+                            onclick: on_delete,
+                            {t("common.delete")}
+                        }
+                    }
+                }
+            }
+        })}
+
+        {import_open().then(|| rsx! {
+            BlackoutImport {
+                existing: all.read().clone(),
+                on_close: move |imported: bool| {
+                    import_open.set(false);
+                    if imported { reload(); }
+                },
+            }
+        })}
+    }
+}
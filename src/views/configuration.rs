@@ -1,29 +1,23 @@
 use dioxus::prelude::*;
 use crate::i18n::t;
 
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-mod backend {
-    use crate::db::dao;
-    #[derive(Clone)]
-    pub struct Configuration { pub congregation_name: String, pub theme: String, pub name_order: String, pub week_start: String, pub language: String, pub date_format: String }
-    pub fn get_configuration() -> Option<Configuration> { dao::get_configuration().ok().map(|c| Configuration { congregation_name: c.congregation_name, theme: c.theme, name_order: c.name_order, week_start: c.week_start, language: c.language, date_format: c.date_format }) }
-    pub fn update_configuration(name: &str, theme: &str, name_order: &str, week_start: &str, language: &str, date_format: &str) { let _ = dao::update_configuration(name, theme, name_order, week_start, language, date_format); }
-    pub fn export_data() -> Option<String> { dao::export_data().ok() }
-    pub fn import_data(json: &str) -> bool { dao::import_data(json).is_ok() }
-    pub fn reset_data() -> bool { dao::reset_data().is_ok() }
-}
-#[cfg(target_arch = "wasm32")]
-use crate::db::wasm_store as backend;
-#[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-#[allow(dead_code)]
-mod backend {
-    #[derive(Clone)]
-    pub struct Configuration { pub congregation_name: String, pub theme: String, pub name_order: String, pub week_start: String, pub language: String, pub date_format: String }
-    pub fn get_configuration() -> Option<Configuration> { None }
-    pub fn update_configuration(_name: &str, _theme: &str, _name_order: &str, _week_start: &str, _language: &str, _date_format: &str) {}
-    pub fn export_data() -> Option<String> { Some("{}".to_string()) }
-    pub fn import_data(_json: &str) -> bool { true }
-    pub fn reset_data() -> bool { true }
+use crate::db::encrypted::{self, EncryptedError};
+use crate::db::store::{Configuration as Config, StoreError};
+use crate::import::merge;
+use crate::scheduler::Weights;
+
+// Label key, getter and setter of each editable generator weight
+type WeightField = (&'static str, fn(&Weights) -> f64, fn(&mut Weights, f64));
+
+fn weight_fields() -> [WeightField; 6] {
+    [
+        ("config.weight_priority", |w| w.priority, |w, v| w.priority = v),
+        ("config.weight_recent_shift", |w| w.recent_shift, |w, v| w.recent_shift = v),
+        ("config.weight_pair_repeat", |w| w.pair_repeat, |w, v| w.pair_repeat = v),
+        ("config.weight_recommended", |w| w.recommended, |w, v| w.recommended = v),
+        ("config.weight_mandatory", |w| w.mandatory, |w, v| w.mandatory = v),
+        ("config.weight_jitter", |w| w.jitter, |w, v| w.jitter = v),
+    ]
 }
 
 #[component]
@@ -35,71 +29,125 @@ pub fn Configuration() -> Element {
     let mut saved = use_signal(|| false);
     let mut language = use_signal(|| String::from("system"));
     let mut date_format = use_signal(|| String::from("YYYY-MM-DD"));
+    let mut weights = use_signal(Weights::default);
+    let mut weights_error = use_signal(|| false);
     let mut confirm_import = use_signal(|| false);
     let mut import_error = use_signal(|| Option::<String>::None);
+    // "replace" overwrites everything; "merge" adds the file to the current data after a summary
+    let mut import_mode = use_signal(|| String::from("replace"));
+    let mut merge_plan = use_signal(|| Option::<merge::Plan>::None);
+    // Export dialog: an optional passphrase encrypts the file
+    let mut export_open = use_signal(|| false);
+    let mut export_passphrase = use_signal(String::new);
+    let mut export_repeat = use_signal(String::new);
+    let mut export_error = use_signal(|| Option::<String>::None);
+    #[cfg_attr(not(all(feature = "native-db", not(target_arch = "wasm32"))), allow(unused_mut))]
+    let mut export_notice = use_signal(|| Option::<String>::None); // where a mobile export went when it couldn't be shared
+    // Native builds read the chosen file when it is picked; the web reads it after confirmation
+    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+    let mut picked_file = use_signal(|| Option::<String>::None);
+    // An encrypted file waiting for its passphrase before it is imported
+    let mut locked_file = use_signal(|| Option::<String>::None);
+    let mut unlock_passphrase = use_signal(String::new);
+    let mut unlock_error = use_signal(|| Option::<String>::None);
     let mut confirm_reset = use_signal(|| false);
     // Access global configured flag from App to toggle after a reset
     let mut configured: Signal<bool> = use_context();
 
     // Load existing configuration on mount (web or native-db builds)
-    use_effect(move || {
-    #[cfg(any(target_arch = "wasm32", all(feature = "native-db", not(target_arch = "wasm32"))))]
-        if let Some(cfg) = backend::get_configuration() {
+    #[cfg_attr(not(all(feature = "native-db", not(target_arch = "wasm32"))), allow(unused_mut))]
+    let mut load_config = move || {
+        if let Ok(cfg) = crate::db::store().get_configuration() {
             name.set(cfg.congregation_name);
             theme.set(cfg.theme);
             name_order.set(cfg.name_order);
             week_start.set(cfg.week_start);
             language.set(cfg.language);
             date_format.set(cfg.date_format);
+            weights.set(cfg.weights);
         }
-    });
+    };
+    use_effect(load_config);
 
     let on_save = move |_| {
         let n = name.read().trim().to_string();
         if n.is_empty() { return; }
-    backend::update_configuration(&n, &theme.read(), &name_order.read(), &week_start.read(), &language.read(), &date_format.read());
+        weights_error.set(weights.read().validate().is_err());
+        if weights_error() { return; }
+    let cfg = Config {
+        congregation_name: n,
+        theme: theme.read().clone(),
+        name_order: name_order.read().clone(),
+        week_start: week_start.read().clone(),
+        language: language.read().clone(),
+        date_format: date_format.read().clone(),
+        weights: weights(),
+    };
+    let _ = crate::db::store().update_configuration(&cfg);
         crate::i18n::set_lang(&language.read());
         crate::i18n::set_date_format(&date_format.read());
         crate::i18n::apply_theme(&theme.read());
         saved.set(true);
     };
 
-    // Export handler
-    // Helper to get export JSON on all targets
-    #[cfg(target_arch = "wasm32")]
-    fn get_export_json() -> Option<String> { Some(backend::export_data()) }
-    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-    fn get_export_json() -> Option<String> { backend::export_data() }
-    #[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-    fn get_export_json() -> Option<String> { backend::export_data() }
-
+    // Export: plain JSON, or encrypted when a passphrase is given
     let on_export = move |_| {
-    if let Some(_json) = get_export_json() {
-            #[cfg(target_arch = "wasm32")]
-            {
-                if let Some(win) = web_sys::window() {
-                    if let Some(doc) = win.document() {
-                        if let Ok(a) = doc.create_element("a") {
-                            use web_sys::wasm_bindgen::JsCast;
-                let href = format!("data:application/json;charset=utf-8,{}", urlencoding::encode(&_json));
-                            a.set_attribute("href", &href).ok();
-                            a.set_attribute("download", "dx_app_export.json").ok();
-                            if let Ok(ae) = a.dyn_into::<web_sys::HtmlElement>() { ae.click(); }
-                        }
+        export_passphrase.set(String::new());
+        export_repeat.set(String::new());
+        export_error.set(None);
+        export_notice.set(None);
+        export_open.set(true);
+    };
+
+    let do_export = move |_| {
+        let passphrase = export_passphrase.read().clone();
+        if !passphrase.is_empty() && passphrase.chars().count() < encrypted::MIN_PASSPHRASE_LEN { export_error.set(Some(t("config.export_passphrase_short"))); return; }
+        if passphrase != *export_repeat.read() { export_error.set(Some(t("config.export_passphrase_mismatch"))); return; }
+        let Ok(json) = crate::db::store().export_data() else { export_error.set(Some(t("config.export_failed"))); return };
+        let _contents = if passphrase.is_empty() { json } else {
+            match encrypted::encrypt(&json, &passphrase) {
+                Ok(file) => file,
+                Err(_) => { export_error.set(Some(t("config.export_failed"))); return; }
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        {
+            if let Some(win) = web_sys::window() {
+                if let Some(doc) = win.document() {
+                    if let Ok(a) = doc.create_element("a") {
+                        use web_sys::wasm_bindgen::JsCast;
+                        let href = format!("data:application/json;charset=utf-8,{}", urlencoding::encode(&_contents));
+                        a.set_attribute("href", &href).ok();
+                        a.set_attribute("download", "dx_app_export.json").ok();
+                        if let Ok(ae) = a.dyn_into::<web_sys::HtmlElement>() { ae.click(); }
                     }
                 }
             }
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            {
-        let path = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")).join("dx_app_export.json");
-        let _ = std::fs::write(path, _json);
-            }
+        }
+        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+        {
+            use crate::dialogs;
+            spawn(async move {
+                let suggested = dialogs::export_dir().join(format!("dx_app_export_{}.json", chrono::Local::now().format("%Y-%m-%d")));
+                let Some(path) = dialogs::save_path(&suggested, &dialogs::JSON).await else { return };
+                if std::fs::write(&path, _contents).is_err() { export_error.set(Some(t("config.export_failed"))); return; }
+                export_passphrase.set(String::new());
+                export_repeat.set(String::new());
+                export_open.set(false);
+                if !dialogs::share(std::slice::from_ref(&path), &dialogs::JSON).await { export_notice.set(Some(format!("{} {}", t("config.export_saved_to"), path.display()))); }
+            });
+        }
+        #[cfg(not(all(feature = "native-db", not(target_arch = "wasm32"))))]
+        {
+            export_passphrase.set(String::new());
+            export_repeat.set(String::new());
+            export_open.set(false);
         }
     };
 
     // File-based import
 
-    // Import button: trigger hidden file input (web) or open confirm (native)
+    // Import button: open the file picker through the hidden file input
     let on_import_click = move |_| {
         import_error.set(None);
         #[cfg(target_arch = "wasm32")]
@@ -117,12 +165,58 @@ pub fn Configuration() -> Element {
         }
         #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
         {
-            // Native fallback: confirm and read default path
-            confirm_import.set(true);
+            // The webview shows the system open dialog (desktop) or document picker (mobile)
+            document::eval(r#"document.getElementById("importFile").click()"#);
+        }
+    };
+
+    // Replace the data with the file, or work out what merging it would add.
+    // A rejected file shows why: the format problem, or every bad row the validation found.
+    let mut apply_import = move |text: String| {
+        // Encrypted files ask for their passphrase first and come back here decrypted
+        if encrypted::is_encrypted(&text) {
+            confirm_import.set(false);
+            unlock_passphrase.set(String::new());
+            unlock_error.set(None);
+            locked_file.set(Some(text));
+            return;
+        }
+        let store = crate::db::store();
+        let result = if import_mode() == "merge" {
+            merge::plan(&text, store, crate::db::today()).map(|plan| merge_plan.set(Some(plan)))
+        } else {
+            store.import_data(&text)
+        };
+        match result {
+            Ok(()) => confirm_import.set(false),
+            Err(StoreError::Parse(e) | StoreError::Invalid(e)) => import_error.set(Some(format!("{}\n{}", t("config.import_invalid_file"), e))),
+            Err(_) => import_error.set(Some(t("config.import_invalid_file"))),
+        }
+    };
+
+    let on_unlock = move |_| {
+        let Some(file) = locked_file.read().clone() else { return };
+        let result = encrypted::decrypt(&file, &unlock_passphrase.read());
+        match result {
+            Ok(plain) => {
+                locked_file.set(None);
+                unlock_passphrase.set(String::new());
+                apply_import(plain);
+            }
+            Err(EncryptedError::WrongPassphrase) => unlock_error.set(Some(t("config.unlock_wrong"))),
+            Err(e) => unlock_error.set(Some(format!("{}\n{}", t("config.import_invalid_file"), e))),
+        }
+    };
+
+    let on_merge = move |_| {
+        let Some(plan) = merge_plan.read().clone() else { return };
+        match crate::db::store().merge_data(&plan.batch) {
+            Ok(()) => merge_plan.set(None),
+            Err(_) => { merge_plan.set(None); import_error.set(Some(t("config.merge_failed"))); }
         }
     };
 
-    // Confirmed import: read file (web) or read from default path (native stub)
+    // Confirmed import: read the file (web) or use the one read when it was picked (native)
     let do_import = move |_| {
         #[cfg(target_arch = "wasm32")]
         {
@@ -136,12 +230,9 @@ pub fn Configuration() -> Element {
                                 if let Some(file) = files.get(0) {
                                     let reader = FileReader::new().unwrap();
                                     let fr_c = reader.clone();
-                                    let mut confirm_copy = confirm_import.clone();
-                                    let mut import_err_copy = import_error.clone();
                                     let onload = web_sys::wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: Event| {
                                         let result = fr_c.result().unwrap_or(web_sys::wasm_bindgen::JsValue::from_str(""));
-                                        let text = result.as_string().unwrap_or_default();
-                                        if backend::import_data(&text) { confirm_copy.set(false); } else { import_err_copy.set(Some(t("config.import_invalid_file"))); }
+                                        apply_import(result.as_string().unwrap_or_default());
                                     }) as Box<dyn FnMut(_)>);
                                     reader.set_onload(Some(onload.as_ref().unchecked_ref()));
                                     onload.forget();
@@ -157,17 +248,14 @@ pub fn Configuration() -> Element {
         }
         #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
         {
-            let path = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")).join("dx_app_export.json");
-            if let Ok(text) = std::fs::read_to_string(path) {
-                let ok = backend::import_data(&text);
-                if ok { confirm_import.set(false); } else { import_error.set(Some(t("config.import_invalid_file"))); }
-            } else {
-                import_error.set(Some(t("config.import_could_not_read")));
+            match picked_file.read().clone() {
+                Some(text) => apply_import(text),
+                None => import_error.set(Some(t("config.import_choose_file"))),
             }
         }
     };
 
-    // File input node for web import
+    // File input node for import
     let import_box = {
         // Hidden file input; when a file is chosen, open confirmation modal
         let mut confirm_copy = confirm_import.clone();
@@ -177,14 +265,43 @@ pub fn Configuration() -> Element {
                 r#type: "file",
                 accept: ".json",
                 class: "hidden",
-                onchange: move |_| {
-                    // Only on web will this be useful, native path uses default file
+                onchange: move |_e: FormEvent| async move {
+                    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+                    {
+                        let Some(file) = _e.files().into_iter().next() else { return };
+                        match file.read_string().await {
+                            Ok(text) => picked_file.set(Some(text)),
+                            Err(_) => { import_error.set(Some(t("config.import_could_not_read"))); return; }
+                        }
+                    }
                     confirm_copy.set(true);
                 },
             }
         )
     };
 
+    // Passphrase and recovery code settings exist only on encrypted builds
+    #[cfg(not(all(feature = "encryption", not(target_arch = "wasm32"))))]
+    let security = rsx! {};
+    #[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+    let security = rsx! { super::security::Security {} };
+
+    // Snapshots of data.db exist only on native builds; a restore reloads the page's settings
+    #[cfg(not(all(feature = "native-db", not(target_arch = "wasm32"))))]
+    let backups = rsx! {};
+    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+    let backups = rsx! {
+        super::backups::Backups {
+            on_restored: move |_| {
+                load_config();
+                configured.set(crate::db::store().configuration_is_set());
+                crate::i18n::set_lang(&language.read());
+                crate::i18n::set_date_format(&date_format.read());
+                crate::i18n::apply_theme(&theme.read());
+            },
+        }
+    };
+
     rsx! {
         // Centered responsive card
         div { class: "min-h-[70vh] flex items-center justify-center",
@@ -280,6 +397,60 @@ pub fn Configuration() -> Element {
                             option { value: "sunday", {t("common.sunday")} }
                         }
                     }
+                    div { class: "flex flex-col gap-2",
+                        label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                            {t("config.generator")}
+                        }
+                        select {
+                            class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                            value: weights.read().preset().unwrap_or("custom"),
+                            oninput: move |e| {
+                                if let Some((_, w)) = Weights::PRESETS.iter().find(|(k, _)| *k == e.value()) { weights.set(*w); }
+                            },
+                            for (key, _) in Weights::PRESETS {
+                                option { value: key, selected: weights.read().preset() == Some(key), {t(&format!("config.weights_preset_{}", key))} }
+                            }
+                            // Hand-tuned weights that match no preset
+                            {weights.read().preset().is_none().then(|| rsx! {
+                                option { value: "custom", selected: true, {t("config.weights_preset_custom")} }
+                            })}
+                        }
+                        p { class: "text-xs text-slate-500 dark:text-slate-400",
+                            {t(&format!("config.weights_preset_{}_hint", weights.read().preset().unwrap_or("custom")))}
+                        }
+                        details { class: "text-sm",
+                            summary { class: "cursor-pointer text-slate-600 dark:text-slate-300", {t("config.weights_advanced")} }
+                            div { class: "grid grid-cols-2 gap-3 pt-3",
+                                for (label, get, set) in weight_fields() {
+                                    div { class: "flex flex-col gap-1",
+                                        label { class: "text-xs text-slate-600 dark:text-slate-300", {t(label)} }
+                                        input {
+                                            r#type: "number",
+                                            min: "0",
+                                            step: "0.5",
+                                            class: "h-9 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-2 text-sm",
+                                            value: get(&weights.read()).to_string(),
+                                            oninput: move |e| if let Ok(v) = e.value().parse::<f64>() { set(&mut weights.write(), v); },
+                                        }
+                                    }
+                                }
+                                div { class: "flex flex-col gap-1",
+                                    label { class: "text-xs text-slate-600 dark:text-slate-300", {t("config.weight_history_days")} }
+                                    input {
+                                        r#type: "number",
+                                        min: "0",
+                                        max: "365",
+                                        class: "h-9 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-2 text-sm",
+                                        value: weights.read().history_days.to_string(),
+                                        oninput: move |e| if let Ok(v) = e.value().parse::<i64>() { weights.write().history_days = v; },
+                                    }
+                                }
+                            }
+                        }
+                        {weights_error().then(|| rsx! {
+                            p { class: "text-sm text-red-600", {t("config.weights_invalid")} }
+                        })}
+                    }
                     div { class: "flex flex-col items-stretch gap-2",
                         button {
                             class: "inline-flex justify-center items-center gap-2 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium px-4 py-2 transition disabled:opacity-50 disabled:cursor-not-allowed w-full",
@@ -321,10 +492,15 @@ pub fn Configuration() -> Element {
                                 span { class: "sm:inline", {t("config.delete_all")} }
                             }
                             {import_error.read().as_ref().map(|e| rsx! {
-                                p { class: "text-sm text-red-600 text-center w-full", {e.clone()} }
+                                p { class: "text-sm text-red-600 text-center w-full whitespace-pre-line", {e.clone()} }
+                            })}
+                            {export_notice.read().as_ref().map(|m| rsx! {
+                                p { class: "text-sm text-green-600 text-center w-full break-all", {m.clone()} }
                             })}
                         }
                     }
+                    {backups}
+                    {security}
                 }
             }
         }
@@ -333,9 +509,25 @@ pub fn Configuration() -> Element {
             div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
                 div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
                     h2 { class: "text-lg font-semibold", {t("config.confirm_import_title")} }
+                    div { class: "space-y-2",
+                        for (mode , label) in [("replace", t("config.import_replace")), ("merge", t("config.import_merge"))] {
+                            label { class: "flex items-center gap-2 text-sm",
+                                input {
+                                    r#type: "radio",
+                                    name: "import_mode",
+                                    checked: import_mode() == mode,
+                                    onchange: move |_| import_mode.set(mode.to_string()),
+                                }
+                                span { {label} }
+                            }
+                        }
+                    }
                     p { class: "text-sm text-slate-600 dark:text-slate-300",
-                        {t("config.confirm_import_message")}
+                        {if import_mode() == "merge" { t("config.confirm_merge_message") } else { t("config.confirm_import_message") }}
                     }
+                    {import_error.read().as_ref().map(|e| rsx! {
+                        p { class: "max-h-40 overflow-y-auto text-sm text-red-600 whitespace-pre-line", {e.clone()} }
+                    })}
                     div { class: "flex items-center justify-end gap-2",
                         button {
                             class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
@@ -352,6 +544,160 @@ pub fn Configuration() -> Element {
             }
         })}
 
+        {export_open().then(|| rsx! {
+            div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                    h2 { class: "text-lg font-semibold", {t("config.export")} }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("config.export_encrypt_hint")} }
+                    div { class: "flex flex-col gap-2",
+                        label { class: "text-sm font-medium text-slate-700 dark:text-slate-200", {t("config.export_passphrase")} }
+                        input {
+                            class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                            r#type: "password",
+                            autocomplete: "new-password",
+                            value: export_passphrase.read().clone(),
+                            oninput: move |e| export_passphrase.set(e.value()),
+                        }
+                    }
+                    {(!export_passphrase.read().is_empty()).then(|| rsx! {
+                        div { class: "flex flex-col gap-2",
+                            label { class: "text-sm font-medium text-slate-700 dark:text-slate-200", {t("config.export_passphrase_repeat")} }
+                            input {
+                                class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                r#type: "password",
+                                autocomplete: "new-password",
+                                value: export_repeat.read().clone(),
+                                oninput: move |e| export_repeat.set(e.value()),
+                            }
+                        }
+                    })}
+                    {export_error.read().as_ref().map(|e| rsx! {
+                        p { class: "text-sm text-red-600", {e.clone()} }
+                    })}
+                    div { class: "flex items-center justify-end gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| export_open.set(false),
+                            {t("common.cancel")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-emerald-600 hover:bg-emerald-500 text-white text-sm font-medium transition",
+                            onclick: do_export,
+                            {t("config.export")}
+                        }
+                    }
+                }
+            }
+        })}
+
+        {locked_file.read().is_some().then(|| rsx! {
+            div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                    h2 { class: "text-lg font-semibold", {t("config.unlock_title")} }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("config.unlock_message")} }
+                    input {
+                        class: "h-10 w-full rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        r#type: "password",
+                        autocomplete: "current-password",
+                        value: unlock_passphrase.read().clone(),
+                        oninput: move |e| unlock_passphrase.set(e.value()),
+                    }
+                    {unlock_error.read().as_ref().map(|e| rsx! {
+                        p { class: "text-sm text-red-600 whitespace-pre-line", {e.clone()} }
+                    })}
+                    div { class: "flex items-center justify-end gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| locked_file.set(None),
+                            {t("common.cancel")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-red-600 hover:bg-red-500 text-white text-sm font-medium transition disabled:opacity-50",
+                            disabled: unlock_passphrase.read().is_empty(),
+                            onclick: on_unlock,
+                            {t("config.unlock")}
+                        }
+                    }
+                }
+            }
+        })}
+
+        {merge_plan.read().as_ref().map(|plan| {
+            let sm = &plan.summary;
+            let rows = [
+                (t("nav.publishers"), sm.publishers),
+                (t("nav.schedules"), sm.schedules),
+                (t("publishers.availability"), sm.availability),
+                (t("publishers.relationships"), sm.relationships),
+                (t("nav.absences"), sm.absences),
+                (t("nav.shifts"), sm.shifts),
+                (t("nav.blackouts"), sm.blackouts),
+            ];
+            let conflicts = sm.conflicts.clone();
+            let skipped = sm.skipped;
+            let empty = plan.batch.is_empty();
+            rsx! {
+                div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                    div { class: "w-full max-w-lg max-h-[90vh] overflow-y-auto rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                        h2 { class: "text-lg font-semibold", {t("config.merge_title")} }
+                        table { class: "w-full text-sm",
+                            thead { class: "text-left text-slate-500",
+                                tr {
+                                    th { class: "py-1" }
+                                    th { class: "py-1 text-right", {t("config.merge_added")} }
+                                    th { class: "py-1 text-right", {t("config.merge_matched")} }
+                                }
+                            }
+                            tbody {
+                                for (label , counts) in rows {
+                                    tr {
+                                        td { class: "py-1", {label} }
+                                        td { class: "py-1 text-right", "{counts.added}" }
+                                        td { class: "py-1 text-right", "{counts.matched}" }
+                                    }
+                                }
+                            }
+                        }
+                        {(skipped > 0).then(|| rsx! {
+                            p { class: "text-sm text-slate-600 dark:text-slate-300", {format!("{}: {}", t("config.merge_skipped"), skipped)} }
+                        })}
+                        {(!conflicts.is_empty()).then(|| rsx! {
+                            div { class: "space-y-1",
+                                h3 { class: "text-sm font-semibold text-amber-700 dark:text-amber-400",
+                                    {format!("{} ({})", t("config.merge_conflicts"), conflicts.len())}
+                                }
+                                p { class: "text-xs text-slate-500", {t("config.merge_conflicts_hint")} }
+                                ul { class: "max-h-48 overflow-y-auto divide-y divide-slate-200 dark:divide-slate-700 rounded-md border border-slate-200 dark:border-slate-700",
+                                    for c in conflicts.iter() {
+                                        li { class: "px-3 py-1 text-sm",
+                                            span { class: "text-slate-500", {format!("{}: ", t(c.kind.key()))} }
+                                            span { {c.label.clone()} }
+                                        }
+                                    }
+                                }
+                            }
+                        })}
+                        {empty.then(|| rsx! {
+                            p { class: "text-sm text-slate-600 dark:text-slate-300", {t("config.merge_nothing")} }
+                        })}
+                        div { class: "flex items-center justify-end gap-2",
+                            button {
+                                class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                                onclick: move |_| merge_plan.set(None),
+                                {t("common.cancel")}
+                            }
+                            button {
+                                class: "inline-flex items-center h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition disabled:opacity-50",
+                                disabled: empty,
+                                onclick: on_merge,
+                                {t("config.merge")}
+                            }
+                        }
+                    }
+                }
+            }
+        })}
+
         // mark as not configured and send user to home so Landpage shows
         {confirm_reset().then(|| rsx! {
             div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
@@ -369,7 +715,7 @@ pub fn Configuration() -> Element {
                         button {
                             class: "inline-flex items-center h-9 px-3 rounded-md bg-red-600 hover:bg-red-500 text-white text-sm font-medium transition",
                             onclick: move |_| {
-                                if backend::reset_data() {
+                                if crate::db::store().reset_data().is_ok() {
                                     configured.set(false);
                                     confirm_reset.set(false);
                                     #[cfg(target_arch = "wasm32")]
@@ -0,0 +1,125 @@
+use dioxus::prelude::*;
+use chrono::{NaiveDateTime, NaiveTime};
+use crate::i18n::{format_date_ymd, t};
+use crate::db::store::GenerationBatch;
+
+// Accepted generator runs, newest first, with how many of their shifts are left and how many were edited
+// since. Reverting a run deletes its untouched shifts; `on_close` gets true after a revert.
+#[component]
+pub fn GenerationHistory(on_close: EventHandler<bool>) -> Element {
+    let mut runs = use_signal(Vec::<(GenerationBatch, usize, usize)>::new); // (run, shifts left, edited)
+    let mut confirm_revert = use_signal(|| Option::<i64>::None);
+    let mut reverted = use_signal(|| false);
+    let mut notice = use_signal(|| Option::<String>::None);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    let mut reload = move || {
+        let store = crate::db::store();
+        let rows = store.list_generation_batches().unwrap_or_default().into_iter().map(|b| {
+            // Counted over the run's range: a shift moved out of it is still reverted, just not counted here
+            let shifts: Vec<_> = store
+                .list_shifts_between(NaiveDateTime::new(b.start, NaiveTime::MIN), NaiveDateTime::new(b.end, NaiveTime::from_hms_opt(23, 59, 59).unwrap()))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|sh| sh.batch_id == Some(b.id))
+                .collect();
+            let edited = shifts.iter().filter(|sh| sh.edited).count();
+            (b, shifts.len(), edited)
+        }).collect();
+        runs.set(rows);
+    };
+    use_effect(reload);
+
+    let on_revert = move |_| {
+        let Some(id) = confirm_revert() else { return };
+        confirm_revert.set(None);
+        match crate::db::store().revert_generation(id) {
+            Ok((removed, kept)) => {
+                error.set(None);
+                reverted.set(true);
+                notice.set(Some(if kept > 0 {
+                    format!("{} {} · {} {}", removed, t("shifts.generations_removed"), kept, t("shifts.generations_kept"))
+                } else {
+                    format!("{} {}", removed, t("shifts.generations_removed"))
+                }));
+            }
+            Err(e) => error.set(Some(format!("{}: {}", t("shifts.generations_revert_failed"), e))),
+        }
+        reload();
+    };
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+            div { class: "w-full max-w-2xl max-h-[90vh] overflow-y-auto rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                h2 { class: "text-lg font-semibold", {t("shifts.generations_title")} }
+                p { class: "text-sm text-slate-600 dark:text-slate-300", {t("shifts.generations_hint")} }
+                {error.read().as_ref().map(|err| rsx! {
+                    p { class: "text-red-600 text-sm", {err.clone()} }
+                })}
+                {notice.read().as_ref().map(|msg| rsx! {
+                    p { class: "text-emerald-700 dark:text-emerald-400 text-sm", {msg.clone()} }
+                })}
+                if runs.read().is_empty() {
+                    div { class: "text-sm text-slate-600 dark:text-slate-300", {t("shifts.generations_empty")} }
+                } else {
+                    ul { class: "divide-y divide-slate-200 dark:divide-slate-700",
+                        for (b, count, edited) in runs.read().iter().cloned() {
+                            li { class: "py-3 flex flex-col sm:flex-row sm:items-center gap-2",
+                                div { class: "flex-1 min-w-0",
+                                    div { class: "font-medium text-slate-800 dark:text-slate-100",
+                                        {format!("{} → {}", format_date_ymd(&b.start.to_string()), format_date_ymd(&b.end.to_string()))}
+                                    }
+                                    div { class: "text-xs text-slate-500",
+                                        {format!(
+                                            "{} {} · {} · {} {}",
+                                            format_date_ymd(&b.created_at.date().to_string()),
+                                            b.created_at.format("%H:%M"),
+                                            t(&format!("shifts.auto_mode_{}", b.mode)),
+                                            t("shifts.seed"),
+                                            b.seed,
+                                        )}
+                                    }
+                                    div { class: "text-xs text-slate-600 dark:text-slate-300",
+                                        {if edited > 0 {
+                                            format!("{} {} · {} {}", count, t("shifts.generations_shifts"), edited, t("shifts.generations_edited"))
+                                        } else {
+                                            format!("{} {}", count, t("shifts.generations_shifts"))
+                                        }}
+                                    }
+                                }
+                                if confirm_revert() == Some(b.id) {
+                                    div { class: "flex items-center gap-2",
+                                        span { class: "text-xs text-slate-600 dark:text-slate-300", {t("shifts.generations_confirm")} }
+                                        button {
+                                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                                            onclick: move |_| confirm_revert.set(None),
+                                            {t("common.cancel")}
+                                        }
+                                        button {
+                                            class: "inline-flex items-center h-9 px-3 rounded-md bg-red-600 hover:bg-red-500 text-white text-sm font-medium transition",
+                                            onclick: on_revert,
+                                            {t("shifts.generations_revert")}
+                                        }
+                                    }
+                                } else {
+                                    button {
+                                        class: "inline-flex items-center h-9 px-3 rounded-md border border-red-300 text-red-700 text-sm font-medium transition",
+                                        onclick: move |_| { notice.set(None); confirm_revert.set(Some(b.id)); },
+                                        {t("shifts.generations_revert")}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "flex items-center justify-end",
+                    button {
+                        class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                        onclick: move |_| on_close.call(reverted()),
+                        {t("common.done")}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,11 +2,7 @@ use dioxus::prelude::*;
 use crate::i18n::{set_lang, set_date_format};
 use crate::i18n::t;
 
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] mod backend { pub use crate::db::dao::update_configuration; }
-#[cfg(target_arch = "wasm32")] use crate::db::wasm_store as backend;
-#[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-#[allow(dead_code)]
-mod backend { pub fn update_configuration(_: &str, _: &str, _: &str, _: &str, _: &str, _: &str) {} pub fn configuration_is_set() -> bool { false } }
+use crate::db::store::Configuration;
 
 #[component]
 pub fn Landpage() -> Element {
@@ -23,7 +19,15 @@ pub fn Landpage() -> Element {
         let n = name.read().trim().to_string();
         if n.is_empty() { return; }
         // Persist configuration (default name order first_last, choose week_start)
-    let _ = backend::update_configuration(&n, &theme.read(), "first_last", &week_start.read(), &language.read(), &date_format.read());
+    let cfg = Configuration {
+        congregation_name: n,
+        theme: theme.read().clone(),
+        week_start: week_start.read().clone(),
+        language: language.read().clone(),
+        date_format: date_format.read().clone(),
+        ..Configuration::default()
+    };
+    let _ = crate::db::store().update_configuration(&cfg);
     // Flip global flag so components react immediately and update i18n
         configured.set(true);
     set_lang(&language.read());
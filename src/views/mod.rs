@@ -1,13 +1,26 @@
 mod home;
 mod publishers;
+mod publisher_import;
 mod absences;
+mod blackouts;
+mod blackout_import;
 mod schedules;
 mod shifts;
+mod generations;
 mod configuration;
 mod landpage;
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+mod unlock;
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+mod security;
+#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+mod backups;
 pub use home::Home;
 pub use publishers::Publishers;
 pub use absences::Absences;
+pub use blackouts::Blackouts;
 pub use schedules::Schedules;
 pub use shifts::Shifts;
 pub use configuration::Configuration;
+#[cfg(all(feature = "encryption", not(target_arch = "wasm32")))]
+pub use unlock::Unlock;
@@ -0,0 +1,204 @@
+use dioxus::prelude::*;
+use crate::i18n::t;
+use crate::db::store::Publisher;
+use crate::import::csv::{self, Duplicate, Mapping, FIELDS};
+
+// CSV import wizard for the Publishers page: load a file (or pasted text), map columns, review and import.
+// Rows with errors can't be imported; likely duplicates start unchecked. `on_close` gets true after an import.
+#[component]
+pub fn PublisherImport(existing: Vec<Publisher>, on_close: EventHandler<bool>) -> Element {
+    let mut text = use_signal(String::new);
+    let mut has_header = use_signal(|| false);
+    let mut mapping = use_signal(|| [None; 5] as Mapping);
+    // Per data row: whether it will be imported
+    let mut included = use_signal(Vec::<bool>::new);
+    let mut error = use_signal(|| Option::<String>::None);
+
+    let existing = use_signal(move || existing);
+
+    // Parsed records and validated data rows for the current text, header toggle and mapping
+    let preview = move || {
+        let recs = { let s = text.read(); csv::parse(&s, csv::detect_delimiter(&s)) };
+        let skip = if has_header() { 1.min(recs.len()) } else { 0 };
+        let rows = csv::rows(&recs[skip..], skip + 1, &mapping.read(), &existing.read());
+        (recs, rows)
+    };
+
+    // Errors are never imported; duplicates are opt-in
+    let mut reset_included = move || {
+        included.set(preview().1.iter().map(|r| r.error.is_none() && r.duplicate.is_none()).collect());
+    };
+    let mut load = move |s: String| {
+        error.set(None);
+        text.set(s);
+        let recs = preview().0;
+        match recs.first().and_then(|h| csv::guess_mapping(h)) {
+            Some(m) => { has_header.set(true); mapping.set(m); }
+            None => { has_header.set(false); mapping.set(csv::default_mapping(recs.first().map_or(0, |r| r.len()))); }
+        }
+        reset_included();
+    };
+
+    let on_file = move |e: FormEvent| async move {
+        if let Some(file) = e.files().into_iter().next() {
+            match file.read_string().await {
+                Ok(s) => load(s),
+                Err(_) => error.set(Some(t("publishers.import_unreadable"))),
+            }
+        }
+    };
+
+    let on_import = move |_| {
+        let chosen: Vec<Publisher> = preview().1.iter().zip(included.read().iter())
+            .filter(|(r, inc)| **inc && r.error.is_none())
+            .map(|(r, _)| r.publisher.clone())
+            .collect();
+        if chosen.is_empty() { error.set(Some(t("publishers.import_none"))); return; }
+        match crate::db::store().create_publishers(&chosen) {
+            Ok(_) => on_close.call(true),
+            Err(_) => error.set(Some(t("publishers.import_failed"))),
+        }
+    };
+
+    let (records, rows) = preview();
+    // Column choices: header names when the first row is a header, "Column N" otherwise
+    let columns: Vec<String> = match records.first() {
+        Some(h) if has_header() => h.iter().enumerate().map(|(i, n)| if n.trim().is_empty() { format!("{} {}", t("publishers.import_column"), i + 1) } else { n.trim().to_string() }).collect(),
+        Some(h) => (1..=h.len()).map(|i| format!("{} {}", t("publishers.import_column"), i)).collect(),
+        None => vec![],
+    };
+    let count = included.read().iter().zip(rows.iter()).filter(|(inc, r)| **inc && r.error.is_none()).count();
+    let total = rows.len();
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+            div { class: "w-full max-w-3xl max-h-[90vh] overflow-y-auto rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                h2 { class: "text-lg font-semibold", {t("publishers.import_title")} }
+                {error.read().as_ref().map(|err| rsx! {
+                    p { class: "text-red-600 text-sm", {err.clone()} }
+                })}
+                div { class: "space-y-2",
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("publishers.import_hint")} }
+                    input {
+                        r#type: "file",
+                        accept: ".csv,.tsv,.txt,text/csv",
+                        class: "block w-full text-sm",
+                        onchange: on_file,
+                    }
+                    textarea {
+                        class: "w-full h-24 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-blue-500",
+                        placeholder: "first_name,last_name,gender,is_shift_manager,priority",
+                        value: text.read().clone(),
+                        oninput: move |e| load(e.value()),
+                    }
+                }
+                {(!columns.is_empty()).then(|| rsx! {
+                    div { class: "space-y-2",
+                        label { class: "inline-flex items-center gap-2 text-sm",
+                            input {
+                                r#type: "checkbox",
+                                checked: has_header(),
+                                onchange: move |e| {
+                                    has_header.set(e.value().parse::<bool>().unwrap_or(false));
+                                    reset_included();
+                                },
+                            }
+                            span { {t("publishers.import_has_header")} }
+                        }
+                        div { class: "grid grid-cols-1 sm:grid-cols-5 gap-2",
+                            for (i , field) in FIELDS.iter().enumerate() {
+                                div { class: "space-y-1",
+                                    label { class: "block text-xs text-slate-500", {t(field.label_key())} }
+                                    select {
+                                        class: "h-9 w-full rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-2 text-sm",
+                                        value: mapping.read()[i].map(|c| c.to_string()).unwrap_or_default(),
+                                        onchange: move |e| {
+                                            mapping.write()[i] = e.value().parse::<usize>().ok();
+                                            reset_included();
+                                        },
+                                        option { value: "", {t("publishers.import_column_none")} }
+                                        for (c , name) in columns.iter().enumerate() {
+                                            option { value: "{c}", {name.clone()} }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                })}
+                {(total > 0).then(|| rsx! {
+                    div { class: "max-h-80 overflow-auto rounded-md border border-slate-200 dark:border-slate-700",
+                        table { class: "w-full text-sm",
+                            thead { class: "bg-slate-50 dark:bg-slate-900 text-left",
+                                tr {
+                                    th { class: "px-2 py-1" }
+                                    th { class: "px-2 py-1", "#" }
+                                    th { class: "px-2 py-1", {t("publishers.first_name")} }
+                                    th { class: "px-2 py-1", {t("publishers.last_name")} }
+                                    th { class: "px-2 py-1", {t("publishers.gender")} }
+                                    th { class: "px-2 py-1", {t("publishers.manager_short")} }
+                                    th { class: "px-2 py-1", {t("publishers.priority")} }
+                                    th { class: "px-2 py-1" }
+                                }
+                            }
+                            tbody { class: "divide-y divide-slate-200 dark:divide-slate-700",
+                                for (i , row) in rows.iter().cloned().enumerate() {
+                                    {
+                                        let note = match (row.error, row.duplicate) {
+                                            (Some(err), _) => t(err.key()),
+                                            (None, Some(Duplicate::Existing(_))) => t("publishers.import_duplicate_existing"),
+                                            (None, Some(Duplicate::InFile(j))) => format!("{} {}", t("publishers.import_duplicate_row"), rows[j].line),
+                                            (None, None) => String::new(),
+                                        };
+                                        let tone = if row.error.is_some() { "text-red-600" } else { "text-amber-600" };
+                                        rsx! {
+                                            tr { class: if row.error.is_some() { "opacity-60" } else { "" },
+                                                td { class: "px-2 py-1",
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        disabled: row.error.is_some(),
+                                                        checked: included.read().get(i).copied().unwrap_or(false),
+                                                        onchange: move |e| {
+                                                            if let Some(v) = included.write().get_mut(i) { *v = e.value().parse::<bool>().unwrap_or(false); }
+                                                        },
+                                                    }
+                                                }
+                                                td { class: "px-2 py-1 text-slate-500", "{row.line}" }
+                                                td { class: "px-2 py-1", {row.publisher.first_name.clone()} }
+                                                td { class: "px-2 py-1", {row.publisher.last_name.clone()} }
+                                                td { class: "px-2 py-1",
+                                                    {if row.publisher.gender == "Female" { t("publishers.gender.female") } else { t("publishers.gender.male") }}
+                                                }
+                                                td { class: "px-2 py-1", {if row.publisher.is_shift_manager { "✓" } else { "" }} }
+                                                td { class: "px-2 py-1", "{row.publisher.priority}" }
+                                                td { class: "px-2 py-1 text-xs {tone}", {note} }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                })}
+                div { class: "flex items-center justify-between gap-2",
+                    span { class: "text-sm text-slate-600 dark:text-slate-300",
+                        {(total > 0).then(|| format!("{} {} {} {}", count, t("common.of"), total, t("publishers.import_selected")))}
+                    }
+                    div { class: "flex items-center gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| on_close.call(false),
+                            {t("common.cancel")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition disabled:opacity-50",
+                            disabled: count == 0,
+                            onclick: on_import,
+                            {t("publishers.import_submit")}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
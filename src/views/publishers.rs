@@ -1,13 +1,7 @@
 use dioxus::prelude::*;
-use crate::i18n::t;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] use crate::db::dao;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] use crate::db::dao::Publisher;
-#[cfg(target_arch = "wasm32")] use crate::db::wasm_store as wasm_backend;
-#[cfg(target_arch = "wasm32")] use wasm_backend::Publisher;
+use crate::i18n::{normalize_for_search, t};
+use crate::db::store::{Publisher, RelationshipKind};
 #[cfg(target_arch = "wasm32")] use web_sys::window;
-#[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-#[derive(PartialEq, Clone)]
-struct Publisher { id: i64, first_name: String, last_name: String, gender: String, is_shift_manager: bool, priority: i64 }
 
 const PAGE_SIZE: usize = 25;
 
@@ -82,22 +76,13 @@ enum ConfirmAction { DeleteOne(i64), DeleteMany(Vec<i64>) }
 #[derive(PartialEq, Clone)]
 struct PublisherForm { id: Option<i64>, first_name: String, last_name: String, gender: String, is_shift_manager: bool, priority: String }
 
-fn normalize_for_search(s: &str) -> String {
-    let lower = s.to_lowercase();
-    lower.replace('á', "a").replace('à', "a").replace('ä', "a").replace('â', "a")
-         .replace('é', "e").replace('è', "e").replace('ë', "e").replace('ê', "e")
-         .replace('í', "i").replace('ì', "i").replace('ï', "i").replace('î', "i")
-         .replace('ó', "o").replace('ò', "o").replace('ö', "o").replace('ô', "o")
-         .replace('ú', "u").replace('ù', "u").replace('ü', "u").replace('û', "u")
-         .replace('ñ', "n")
-}
-
 #[allow(unused_mut)]
 #[component]
 pub fn Publishers() -> Element {
     let mut list = use_signal(|| Vec::<Publisher>::new());
     let mut query = use_signal(|| String::new());
     let mut modal_open = use_signal(|| false);
+    let mut import_open = use_signal(|| false);
     let mut form = use_signal(|| PublisherForm { id: None, first_name: String::new(), last_name: String::new(), gender: "Male".into(), is_shift_manager: false, priority: "5".into() });
     let mut error = use_signal(|| Option::<String>::None);
     let mut current_page = use_signal(|| 0usize);
@@ -113,37 +98,18 @@ pub fn Publishers() -> Element {
     let mut rel_add_kind = use_signal(|| "recommended".to_string());
 
     use_effect(move || {
-    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            if let Ok(items) = dao::list_publishers() { list.set(items); }
-            if let Ok(mut schs) = dao::list_schedules() {
-                let week_start = dao::get_configuration().ok().map(|c| c.week_start).unwrap_or_else(|| "monday".into());
-                schs.sort_by(|a,b| {
-                    weekday_rank_any(&a.weekday, &week_start)
-                        .cmp(&weekday_rank_any(&b.weekday, &week_start))
-                        .then(a.start_hour.cmp(&b.start_hour))
-                        .then(a.location.cmp(&b.location))
-                });
-                let mapped = schs.into_iter().map(|s| (s.id, format!("{} • {} {}-{}", s.weekday, s.location, s.start_hour, s.end_hour))).collect();
-                schedules.set(mapped);
-            }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let mut list_sig = list.clone();
-            list_sig.set(wasm_backend::list_publishers());
-            let mut v = wasm_backend::list_schedules();
-            let week_start = wasm_backend::get_configuration().map(|c| c.week_start).unwrap_or_else(|| "monday".into());
-            v.sort_by(|a,b| {
-                weekday_rank_any(&a.weekday, &week_start)
-                    .cmp(&weekday_rank_any(&b.weekday, &week_start))
-                    .then(a.start_hour.cmp(&b.start_hour))
-                    .then(a.location.cmp(&b.location))
-            });
-            let mapped = v.into_iter().map(|s| (s.id, format!("{} • {} {}-{}", s.weekday, s.location, s.start_hour, s.end_hour))).collect();
-            let mut schedules_sig = schedules.clone();
-            schedules_sig.set(mapped);
-        }
+        let store = crate::db::store();
+        list.set(store.list_publishers().unwrap_or_default());
+        let mut schs = store.list_schedules().unwrap_or_default();
+        let week_start = store.week_start();
+        schs.sort_by(|a,b| {
+            weekday_rank_any(&a.weekday, &week_start)
+                .cmp(&weekday_rank_any(&b.weekday, &week_start))
+                .then(a.start_hour.cmp(&b.start_hour))
+                .then(a.location.cmp(&b.location))
+        });
+        let mapped = schs.into_iter().map(|s| (s.id, format!("{} • {} {}-{}", s.weekday, s.location, s.start_hour, s.end_hour))).collect();
+        schedules.set(mapped);
     });
 
     let filtered = || {
@@ -191,31 +157,11 @@ pub fn Publishers() -> Element {
             error.set(None);
             if let Some(p) = list.read().iter().find(|x| x.id == id).cloned() {
                 form.set(PublisherForm { id: Some(p.id), first_name: p.first_name, last_name: p.last_name, gender: p.gender, is_shift_manager: p.is_shift_manager, priority: p.priority.to_string() });
-        // load availability for this publisher
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        if let Ok(a) = dao::list_availability_for_publisher(id) { avail_selected.set(a); }
-        #[cfg(target_arch = "wasm32")]
-        { avail_selected.set(wasm_backend::list_availability_for_publisher(id)); }
-        // load relationships for this publisher
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            if let Ok(rels) = dao::list_relationships_for_publisher(id) {
-                let v: Vec<(i64,String)> = rels.into_iter().map(|(oid, k)| {
-                    let kind = match k { dao::RelationshipKind::Mandatory => "mandatory".to_string(), dao::RelationshipKind::Recommended => "recommended".to_string() };
-                    (oid, kind)
-                }).collect();
-                rel_selected.set(v);
-            }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let rels = wasm_backend::list_relationships_for_publisher(id);
-            let v: Vec<(i64,String)> = rels.into_iter().map(|(oid, k)| {
-                let kind = match k { wasm_backend::RelationshipKind::Mandatory => "mandatory".to_string(), wasm_backend::RelationshipKind::Recommended => "recommended".to_string() };
-                (oid, kind)
-            }).collect();
-            rel_selected.set(v);
-        }
+        // load availability and relationships for this publisher
+        let store = crate::db::store();
+        avail_selected.set(store.list_availability_for_publisher(id).unwrap_or_default());
+        let rels = store.list_relationships_for_publisher(id).unwrap_or_default();
+        rel_selected.set(rels.into_iter().map(|(oid, k)| (oid, k.as_str().to_string())).collect());
                 modal_open.set(true);
             }
         }
@@ -226,83 +172,43 @@ pub fn Publishers() -> Element {
         error.set(None);
         let f = form.read().clone();
     if f.first_name.trim().is_empty() || f.last_name.trim().is_empty() { error.set(Some(t("publishers.error_required"))); return; }
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            if let Some(id) = f.id {
-                if dao::update_publisher(id, &f.first_name, &f.last_name, &f.gender, f.is_shift_manager, f.priority.parse().unwrap_or(5)).is_err() { error.set(Some(t("publishers.error_update"))); return; }
-                // save availability
-                let _ = dao::set_publisher_availability(id, &avail_selected.read());
-                // sync relationships
-                use std::collections::HashSet;
-                let target: Vec<(i64,String)> = rel_selected.read().clone();
-                let existing = dao::list_relationships_for_publisher(id).unwrap_or_default();
-                let target_ids: HashSet<i64> = target.iter().map(|(oid,_)| *oid).collect();
-                for (oid, _k) in existing.iter() { if !target_ids.contains(oid) { let _ = dao::remove_relationship(id, *oid); } }
-                for (oid, kind) in target.iter() {
-                    let k = if kind == "mandatory" { dao::RelationshipKind::Mandatory } else { dao::RelationshipKind::Recommended };
-                    let _ = dao::add_relationship(id, *oid, k);
-                }
-                if let Ok(items) = dao::list_publishers() { list.set(items); }
-            } else {
-                match dao::create_publisher(&f.first_name, &f.last_name, &f.gender, f.is_shift_manager, f.priority.parse().unwrap_or(5)) {
-                    Ok(new_id) => {
-                        let _ = dao::set_publisher_availability(new_id, &avail_selected.read());
-                        // add relationships for new publisher
-                        for (oid, kind) in rel_selected.read().iter() {
-                            let k = if kind == "mandatory" { dao::RelationshipKind::Mandatory } else { dao::RelationshipKind::Recommended };
-                            let _ = dao::add_relationship(new_id, *oid, k);
-                        }
-                        if let Ok(items) = dao::list_publishers() { list.set(items); }
-                    }
-                    Err(_) => { error.set(Some(t("publishers.error_create"))); return; }
-                }
+        let store = crate::db::store();
+        let priority = f.priority.parse().unwrap_or(5);
+        if let Some(id) = f.id {
+            if store.update_publisher(id, &f.first_name, &f.last_name, &f.gender, f.is_shift_manager, priority).is_err() { error.set(Some(t("publishers.error_update"))); return; }
+            // save availability
+            let _ = store.set_publisher_availability(id, &avail_selected.read());
+            // sync relationships
+            use std::collections::HashSet;
+            let target: Vec<(i64,String)> = rel_selected.read().clone();
+            let existing = store.list_relationships_for_publisher(id).unwrap_or_default();
+            let target_ids: HashSet<i64> = target.iter().map(|(oid,_)| *oid).collect();
+            for (oid, _k) in existing.iter() { if !target_ids.contains(oid) { let _ = store.remove_relationship(id, *oid); } }
+            for (oid, kind) in target.iter() {
+                let _ = store.add_relationship(id, *oid, RelationshipKind::from_db(kind));
             }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            if let Some(id) = f.id {
-                wasm_backend::update_publisher(id, &f.first_name, &f.last_name, &f.gender, f.is_shift_manager, f.priority.parse().unwrap_or(5));
-                wasm_backend::set_publisher_availability(id, &avail_selected.read());
-                // sync relationships
-                use std::collections::HashSet;
-                let target: Vec<(i64,String)> = rel_selected.read().clone();
-                let existing = wasm_backend::list_relationships_for_publisher(id);
-                let target_ids: HashSet<i64> = target.iter().map(|(oid,_)| *oid).collect();
-                for (oid, _k) in existing.iter() { if !target_ids.contains(oid) { wasm_backend::remove_relationship(id, *oid); } }
-                for (oid, kind) in target.iter() {
-                    let k = if kind == "mandatory" { wasm_backend::RelationshipKind::Mandatory } else { wasm_backend::RelationshipKind::Recommended };
-                    wasm_backend::add_relationship(id, *oid, k);
-                }
-            } else {
-                let new_id = wasm_backend::create_publisher(&f.first_name, &f.last_name, &f.gender, f.is_shift_manager, f.priority.parse().unwrap_or(5));
-                wasm_backend::set_publisher_availability(new_id, &avail_selected.read());
-                for (oid, kind) in rel_selected.read().iter() {
-                    let k = if kind == "mandatory" { wasm_backend::RelationshipKind::Mandatory } else { wasm_backend::RelationshipKind::Recommended };
-                    wasm_backend::add_relationship(new_id, *oid, k);
+        } else {
+            match store.create_publisher(&f.first_name, &f.last_name, &f.gender, f.is_shift_manager, priority) {
+                Ok(new_id) => {
+                    let _ = store.set_publisher_availability(new_id, &avail_selected.read());
+                    // add relationships for new publisher
+                    for (oid, kind) in rel_selected.read().iter() {
+                        let _ = store.add_relationship(new_id, *oid, RelationshipKind::from_db(kind));
+                    }
                 }
+                Err(_) => { error.set(Some(t("publishers.error_create"))); return; }
             }
-            let mut list_sig = list.clone();
-            list_sig.set(wasm_backend::list_publishers());
         }
+        list.set(store.list_publishers().unwrap_or_default());
     modal_open.set(false);
     clear_selection();
     };
 
     let mut delete_publisher = move |id: i64| {
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            if dao::delete_publisher(id).is_ok() {
-                if let Ok(items) = dao::list_publishers() { list.set(items); }
-            }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            wasm_backend::delete_publisher(id);
-            let mut list_sig = list.clone();
-            list_sig.set(wasm_backend::list_publishers());
+        let store = crate::db::store();
+        if store.delete_publisher(id).is_ok() {
+            list.set(store.list_publishers().unwrap_or_default());
         }
-        #[cfg(all(not(target_arch = "wasm32"), not(feature = "native-db")))]
-        { let _ = id; }
     };
 
     rsx! {
@@ -315,11 +221,19 @@ pub fn Publishers() -> Element {
                         span { "←" }
                         span { class: "hidden sm:inline", {t("nav.home")} }
                     }
-                    button {
-                        class: "inline-flex items-center gap-2 h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition",
-                        onclick: open_create,
-                        span { "➕" }
-                        span { class: "hidden sm:inline", {t("common.new")} }
+                    div { class: "flex items-center gap-2",
+                        button {
+                            class: "inline-flex items-center gap-2 h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| import_open.set(true),
+                            span { "📥" }
+                            span { class: "hidden sm:inline", {t("publishers.import")} }
+                        }
+                        button {
+                            class: "inline-flex items-center gap-2 h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition",
+                            onclick: open_create,
+                            span { "➕" }
+                            span { class: "hidden sm:inline", {t("common.new")} }
+                        }
                     }
                 }
                 div { class: "rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-sm p-4 sm:p-5 space-y-4",
@@ -457,19 +371,7 @@ pub fn Publishers() -> Element {
                                                             class: "min-w-0 flex-1 cursor-pointer hover:bg-slate-50 dark:hover:bg-slate-700/30 rounded-md px-3 -mx-3 py-2",
                                                             onclick: move |_| open_edit_id(p.id),
                                                             div { class: "font-medium text-slate-800 dark:text-slate-100",
-                                                                {
-                                                                    #[cfg(target_arch = "wasm32")]
-                                                                    {
-                                                                        let order = wasm_backend::get_name_order();
-                                                                        if order == "last_first" {
-                                                                            format!("{} {}", p.last_name, p.first_name)
-                                                                        } else {
-                                                                            format!("{} {}", p.first_name, p.last_name)
-                                                                        }
-                                                                    }
-                                                                    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                                                    { format!("{} {}", p.first_name, p.last_name) }
-                                                                }
+                                                                {p.display_name(&crate::db::store().name_order())}
                                                             }
                                                             div { class: "text-xs text-slate-500 flex items-center gap-2",
                                                                 span { {if p.gender == "Male" { "♂️" } else { "♀️" }} }
@@ -755,6 +657,16 @@ pub fn Publishers() -> Element {
             }
         })}
 
+        {import_open().then(|| rsx! {
+            super::publisher_import::PublisherImport {
+                existing: list.read().clone(),
+                on_close: move |imported: bool| {
+                    if imported { list.set(crate::db::store().list_publishers().unwrap_or_default()); }
+                    import_open.set(false);
+                },
+            }
+        })}
+
         // Confirm modal
         {
             confirm_action
@@ -797,25 +709,12 @@ pub fn Publishers() -> Element {
                                                     selected.set(v);
                                                     modal_open.set(false);
                                                 }
-                                                Some(ConfirmAction::DeleteMany(_ids)) => {
-                                                    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                                    {
-                                                        for id in _ids.iter().copied() {
-                                                            let _ = dao::delete_publisher(id);
-                                                        }
-                                                        if let Ok(items) = dao::list_publishers() {
-                                                            let mut list_sig = list.clone();
-                                                            list_sig.set(items);
-                                                        }
-                                                    }
-                                                    #[cfg(target_arch = "wasm32")]
-                                                    {
-                                                        for id in _ids.iter().copied() {
-                                                            wasm_backend::delete_publisher(id);
-                                                        }
-                                                        let mut list_sig = list.clone();
-                                                        list_sig.set(wasm_backend::list_publishers());
+                                                Some(ConfirmAction::DeleteMany(ids)) => {
+                                                    let store = crate::db::store();
+                                                    for id in ids.iter().copied() {
+                                                        let _ = store.delete_publisher(id);
                                                     }
+                                                    list.set(store.list_publishers().unwrap_or_default());
                                                     selected.set(vec![]);
                                                     modal_open.set(false);
                                                 }
@@ -1,9 +1,7 @@
 use dioxus::prelude::*;
-use crate::i18n::t;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] use crate::db::dao;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))] use crate::db::dao::Schedule as NativeSchedule;
-#[cfg(target_arch = "wasm32")] use crate::db::wasm_store as wasm_backend;
-#[cfg(target_arch = "wasm32")] use wasm_backend::Schedule as WebSchedule;
+use crate::i18n::{format_date_ymd, t};
+use crate::db::store::Schedule;
+use crate::recurrence::{Recurrence, Rule};
 #[cfg(target_arch = "wasm32")] use web_sys::window;
 
 const PAGE_SIZE: usize = 25;
@@ -23,6 +21,64 @@ struct ScheduleForm {
     num_shift_managers: String,
     num_brothers: String,
     num_sisters: String,
+    repeat: String, // "weekly", "every_n_weeks" or "monthly_nth"
+    interval: String,
+    anchor: String, // YYYY-MM-DD
+    nth: Vec<i32>,
+    active_from: String,
+    active_until: String,
+}
+
+impl Default for ScheduleForm {
+    fn default() -> Self {
+        ScheduleForm { id: None, location: String::new(), start_hour: "09:00".into(), end_hour: "12:00".into(), weekday: "Monday".into(), description: String::new(), num_publishers: "4".into(), num_shift_managers: "1".into(), num_brothers: "2".into(), num_sisters: "2".into(), repeat: "weekly".into(), interval: "2".into(), anchor: crate::db::today().to_string(), nth: vec![1], active_from: String::new(), active_until: String::new() }
+    }
+}
+
+impl ScheduleForm {
+    fn from_schedule(s: Schedule) -> Self {
+        let mut f = ScheduleForm { id: Some(s.id), location: s.location, start_hour: s.start_hour, end_hour: s.end_hour, weekday: s.weekday, description: s.description.unwrap_or_default(), num_publishers: s.num_publishers.to_string(), num_shift_managers: s.num_shift_managers.to_string(), num_brothers: s.num_brothers.to_string(), num_sisters: s.num_sisters.to_string(), ..Default::default() };
+        match s.recurrence.rule {
+            Rule::Weekly => {}
+            Rule::EveryNWeeks { interval, anchor } => { f.repeat = "every_n_weeks".into(); f.interval = interval.to_string(); f.anchor = anchor.to_string(); }
+            Rule::MonthlyNth { nth } => { f.repeat = "monthly_nth".into(); f.nth = nth; }
+        }
+        f.active_from = s.recurrence.from.map(|d| d.to_string()).unwrap_or_default();
+        f.active_until = s.recurrence.until.map(|d| d.to_string()).unwrap_or_default();
+        f
+    }
+
+    // None when a date or the interval doesn't parse
+    fn recurrence(&self) -> Option<Recurrence> {
+        let date = |v: &str| if v.trim().is_empty() { Some(None) } else { chrono::NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d").ok().map(Some) };
+        let rule = match self.repeat.as_str() {
+            "every_n_weeks" => Rule::EveryNWeeks { interval: self.interval.trim().parse().ok()?, anchor: date(&self.anchor)?? },
+            "monthly_nth" => { let mut nth = self.nth.clone(); nth.sort_by_key(|n| if *n < 0 { 6 } else { *n }); Rule::MonthlyNth { nth } }
+            _ => Rule::Weekly,
+        };
+        Some(Recurrence { rule, from: date(&self.active_from)?, until: date(&self.active_until)? })
+    }
+}
+
+const NTH: [(i32, &str); 6] = [(1, "schedules.nth_1"), (2, "schedules.nth_2"), (3, "schedules.nth_3"), (4, "schedules.nth_4"), (5, "schedules.nth_5"), (-1, "schedules.nth_last")];
+
+// "every 2 weeks from …", "1st, 3rd of the month", "active … – …"; None for a plain weekly schedule
+fn recurrence_label(r: &Recurrence) -> Option<String> {
+    if r.is_weekly() { return None; }
+    let mut parts = Vec::new();
+    match &r.rule {
+        Rule::Weekly => {}
+        Rule::EveryNWeeks { interval, anchor } => parts.push(format!("{} {} {} {}", t("schedules.every"), interval, t("schedules.weeks_from"), format_date_ymd(&anchor.to_string()))),
+        Rule::MonthlyNth { nth } => {
+            let names: Vec<String> = NTH.iter().filter(|(n, _)| nth.contains(n)).map(|(_, key)| t(key)).collect();
+            parts.push(format!("{} {}", names.join(", "), t("schedules.of_the_month")));
+        }
+    }
+    if r.from.is_some() || r.until.is_some() {
+        let date = |d: Option<chrono::NaiveDate>| d.map(|d| format_date_ymd(&d.to_string())).unwrap_or_else(|| "…".into());
+        parts.push(format!("{} {} – {}", t("schedules.active"), date(r.from), date(r.until)));
+    }
+    Some(parts.join(", "))
 }
 
 #[derive(Clone)]
@@ -34,6 +90,19 @@ struct ScheduleListItem {
 
 fn normalize(s: &str) -> String { s.to_lowercase() }
 
+// Schedules sorted by the configured week start, then start hour
+fn load_items() -> Vec<ScheduleListItem> {
+    let store = crate::db::store();
+    let mut items = store.list_schedules().unwrap_or_default();
+    let order = weekday_order_list(&store.week_start());
+    items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour)));
+    items.into_iter().map(|s| ScheduleListItem {
+        id: s.id,
+        title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour),
+        subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", recurrence_label(&s.recurrence).map_or(s.weekday.clone(), |r| format!("{}, {}", s.weekday, r)), s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")),
+    }).collect()
+}
+
 #[cfg(target_arch = "wasm32")]
 fn locale_prefix() -> String {
     window()
@@ -81,7 +150,7 @@ pub fn Schedules() -> Element {
     let mut query = use_signal(|| String::new());
     let mut loc_suggestions = use_signal(|| Vec::<String>::new());
     let mut modal_open = use_signal(|| false);
-    let mut form = use_signal(|| ScheduleForm { id: None, location: String::new(), start_hour: "09:00".into(), end_hour: "12:00".into(), weekday: "Monday".into(), description: String::new(), num_publishers: "4".into(), num_shift_managers: "1".into(), num_brothers: "2".into(), num_sisters: "2".into() });
+    let mut form = use_signal(ScheduleForm::default);
     let mut error = use_signal(|| Option::<String>::None);
     let mut current_page = use_signal(|| 0usize);
     let mut selected = use_signal(|| Vec::<i64>::new());
@@ -89,40 +158,13 @@ pub fn Schedules() -> Element {
     let mut select_mode = use_signal(|| false);
 
     use_effect(move || {
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        if let Ok(mut items) = dao::list_schedules() {
-            let week_start = dao::get_configuration().ok().map(|c| c.week_start).unwrap_or_else(|| "monday".into());
-            let order = weekday_order_list(&week_start);
-            items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour)));
-            let mapped = items.into_iter().map(|s: NativeSchedule| ScheduleListItem {
-                id: s.id,
-                title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour),
-                subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", s.weekday, s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")),
-            }).collect::<Vec<_>>();
-            raw.set(mapped.clone());
-            // build unique location suggestions from mapped items' titles
-            let mut set = std::collections::BTreeSet::<String>::new();
-            for it in &mapped { if let Some((loc, _)) = it.title.split_once(" • ") { set.insert(loc.to_string()); } }
-            loc_suggestions.set(set.into_iter().collect());
-            list.set(mapped);
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let mut items = wasm_backend::list_schedules();
-            let week_start = wasm_backend::get_configuration().map(|c| c.week_start).unwrap_or_else(|| "monday".into());
-            let order = weekday_order_list(&week_start);
-            items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour)));
-            let mapped = items.into_iter().map(|s: WebSchedule| ScheduleListItem {
-                id: s.id,
-                title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour),
-                subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", s.weekday, s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")),
-            }).collect::<Vec<_>>();
-            raw.set(mapped.clone());
-            let mut set = std::collections::BTreeSet::<String>::new();
-            for it in &mapped { if let Some((loc, _)) = it.title.split_once(" • ") { set.insert(loc.to_string()); } }
-            loc_suggestions.set(set.into_iter().collect());
-            list.set(mapped);
-        }
+        let mapped = load_items();
+        raw.set(mapped.clone());
+        // build unique location suggestions from mapped items' titles
+        let mut set = std::collections::BTreeSet::<String>::new();
+        for it in &mapped { if let Some((loc, _)) = it.title.split_once(" • ") { set.insert(loc.to_string()); } }
+        loc_suggestions.set(set.into_iter().collect());
+        list.set(mapped);
     });
 
     // search
@@ -155,7 +197,7 @@ pub fn Schedules() -> Element {
 
     let open_create = move |_| {
         error.set(None);
-        form.set(ScheduleForm { id: None, location: String::new(), start_hour: "09:00".into(), end_hour: "12:00".into(), weekday: "Monday".into(), description: String::new(), num_publishers: "4".into(), num_shift_managers: "1".into(), num_brothers: "2".into(), num_sisters: "2".into() });
+        form.set(ScheduleForm::default());
         modal_open.set(true);
     };
     let mut open_edit_id = {
@@ -163,19 +205,9 @@ pub fn Schedules() -> Element {
         let mut _modal_open_cl = modal_open.clone();
         move |_id: i64| {
             error.set(None);
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            if let Ok(items) = dao::list_schedules() {
-                if let Some(s) = items.into_iter().find(|x| x.id == _id) {
-                    _form_cl.set(ScheduleForm { id: Some(s.id), location: s.location, start_hour: s.start_hour, end_hour: s.end_hour, weekday: s.weekday, description: s.description.unwrap_or_default(), num_publishers: s.num_publishers.to_string(), num_shift_managers: s.num_shift_managers.to_string(), num_brothers: s.num_brothers.to_string(), num_sisters: s.num_sisters.to_string() });
-                    _modal_open_cl.set(true);
-                }
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                if let Some(s) = wasm_backend::list_schedules().into_iter().find(|x| x.id == _id) {
-                    _form_cl.set(ScheduleForm { id: Some(s.id), location: s.location, start_hour: s.start_hour, end_hour: s.end_hour, weekday: s.weekday, description: s.description.unwrap_or_default(), num_publishers: s.num_publishers.to_string(), num_shift_managers: s.num_shift_managers.to_string(), num_brothers: s.num_brothers.to_string(), num_sisters: s.num_sisters.to_string() });
-                    _modal_open_cl.set(true);
-                }
+            if let Some(s) = crate::db::store().list_schedules().unwrap_or_default().into_iter().find(|x| x.id == _id) {
+                _form_cl.set(ScheduleForm::from_schedule(s));
+                _modal_open_cl.set(true);
             }
         }
     };
@@ -189,46 +221,21 @@ pub fn Schedules() -> Element {
         let nb = f.num_brothers.parse::<i64>().unwrap_or(0);
         let ns = f.num_sisters.parse::<i64>().unwrap_or(0);
         if nm + nb + ns > np { error.set(Some(t("schedules.error_counts_exceed_total"))); return; }
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        {
-            let s = NativeSchedule { id: f.id.unwrap_or_default(), location: f.location, start_hour: f.start_hour, end_hour: f.end_hour, weekday: f.weekday, description: if f.description.trim().is_empty() { None } else { Some(f.description) }, num_publishers: np, num_shift_managers: nm, num_brothers: nb, num_sisters: ns };
-            if s.id > 0 { let _ = dao::update_schedule(&s); } else { let _ = dao::create_schedule(&s); }
-            if let Ok(mut items) = dao::list_schedules() {
-                let week_start = dao::get_configuration().ok().map(|c| c.week_start).unwrap_or_else(|| "monday".into());
-                let order = weekday_order_list(&week_start);
-                items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour)));
-                let mapped = items.into_iter().map(|s: NativeSchedule| ScheduleListItem {
-                    id: s.id,
-                    title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour),
-                    subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", s.weekday, s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")),
-                }).collect::<Vec<_>>();
-                raw.set(mapped.clone()); list.set(mapped);
-            }
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let s = WebSchedule { id: f.id.unwrap_or_default(), location: f.location, start_hour: f.start_hour, end_hour: f.end_hour, weekday: f.weekday, description: if f.description.trim().is_empty() { None } else { Some(f.description) }, num_publishers: np, num_shift_managers: nm, num_brothers: nb, num_sisters: ns };
-            if s.id > 0 { wasm_backend::update_schedule(&s); } else { let _ = wasm_backend::create_schedule(&s); }
-            let mut items = wasm_backend::list_schedules();
-            let week_start = wasm_backend::get_configuration().map(|c| c.week_start).unwrap_or_else(|| "monday".into());
-            let order = weekday_order_list(&week_start);
-            items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour)));
-            let mapped = items.into_iter().map(|s: WebSchedule| ScheduleListItem {
-                id: s.id,
-                title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour),
-                subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", s.weekday, s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")),
-            }).collect::<Vec<_>>();
-            raw.set(mapped.clone()); list.set(mapped);
-        }
+        let Some(recurrence) = f.recurrence() else { error.set(Some(t("schedules.error_recurrence"))); return; };
+        let s = Schedule { id: f.id.unwrap_or_default(), location: f.location, start_hour: f.start_hour, end_hour: f.end_hour, weekday: f.weekday, description: if f.description.trim().is_empty() { None } else { Some(f.description) }, num_publishers: np, num_shift_managers: nm, num_brothers: nb, num_sisters: ns, recurrence };
+        let store = crate::db::store();
+        let res = if s.id > 0 { store.update_schedule(&s) } else { store.create_schedule(&s).map(|_| ()) };
+        if let Err(e) = res { error.set(Some(e.to_string())); return; }
+        let mapped = load_items();
+        raw.set(mapped.clone()); list.set(mapped);
         modal_open.set(false);
         selected.set(vec![]);
     };
 
-    let mut delete_schedule = move |_id: i64| {
-        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-        { let _ = dao::delete_schedule(_id); if let Ok(mut items) = dao::list_schedules() { let week_start = dao::get_configuration().ok().map(|c| c.week_start).unwrap_or_else(|| "monday".into()); let order = weekday_order_list(&week_start); items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour))); let mapped = items.into_iter().map(|s: NativeSchedule| ScheduleListItem { id: s.id, title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour), subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", s.weekday, s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")) }).collect::<Vec<_>>(); raw.set(mapped.clone()); list.set(mapped); } }
-        #[cfg(target_arch = "wasm32")]
-        { wasm_backend::delete_schedule(_id); let mut items = wasm_backend::list_schedules(); let week_start = wasm_backend::get_configuration().map(|c| c.week_start).unwrap_or_else(|| "monday".into()); let order = weekday_order_list(&week_start); items.sort_by(|a, b| weekday_rank(&a.weekday, &order).cmp(&weekday_rank(&b.weekday, &order)).then(a.start_hour.cmp(&b.start_hour))); let mapped = items.into_iter().map(|s: WebSchedule| ScheduleListItem { id: s.id, title: format!("{} • {}–{}", s.location, s.start_hour, s.end_hour), subtitle: format!("{}, {} {}, {} {}, {} {}, {} {}", s.weekday, s.num_publishers, t("schedules.pubs_short"), s.num_shift_managers, t("schedules.managers_short"), s.num_brothers, t("schedules.brothers"), s.num_sisters, t("schedules.sisters")) }).collect::<Vec<_>>(); raw.set(mapped.clone()); list.set(mapped); }
+    let mut delete_schedule = move |id: i64| {
+        let _ = crate::db::store().delete_schedule(id);
+        let mapped = load_items();
+        raw.set(mapped.clone()); list.set(mapped);
     };
 
     rsx! {
@@ -456,6 +463,82 @@ pub fn Schedules() -> Element {
                             oninput: move |e| form.write().end_hour = e.value(),
                         }
                     }
+                    div { class: "space-y-2",
+                        div { class: "flex items-center gap-2",
+                            label { class: "text-sm", {t("schedules.repeat")} }
+                            select {
+                                class: "h-10 flex-1 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: form.read().repeat.clone(),
+                                onchange: move |e| form.write().repeat = e.value(),
+                                option { value: "weekly", {t("schedules.repeat_weekly")} }
+                                option { value: "every_n_weeks", {t("schedules.repeat_every_n_weeks")} }
+                                option { value: "monthly_nth", {t("schedules.repeat_monthly_nth")} }
+                            }
+                        }
+                        {(form.read().repeat == "every_n_weeks").then(|| rsx! {
+                            div { class: "grid grid-cols-2 gap-3",
+                                div { class: "flex flex-col gap-1",
+                                    label { class: "text-xs text-slate-600 dark:text-slate-300", {t("schedules.interval_weeks")} }
+                                    input {
+                                        r#type: "number",
+                                        min: "1",
+                                        class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        value: form.read().interval.clone(),
+                                        oninput: move |e| form.write().interval = e.value(),
+                                    }
+                                }
+                                div { class: "flex flex-col gap-1",
+                                    label { class: "text-xs text-slate-600 dark:text-slate-300", {t("schedules.anchor")} }
+                                    input {
+                                        r#type: "date",
+                                        class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        value: form.read().anchor.clone(),
+                                        oninput: move |e| form.write().anchor = e.value(),
+                                    }
+                                }
+                            }
+                        })}
+                        {(form.read().repeat == "monthly_nth").then(|| rsx! {
+                            div { class: "flex flex-wrap items-center gap-3",
+                                span { class: "text-xs text-slate-600 dark:text-slate-300", {t("schedules.weeks_of_month")} }
+                                for (n, key) in NTH {
+                                    label { class: "inline-flex items-center gap-1 text-sm",
+                                        input {
+                                            r#type: "checkbox",
+                                            checked: form.read().nth.contains(&n),
+                                            onchange: move |e| {
+                                                let mut f = form.write();
+                                                f.nth.retain(|x| *x != n);
+                                                if e.checked() { f.nth.push(n); }
+                                            },
+                                        }
+                                        span { {t(key)} }
+                                    }
+                                }
+                            }
+                        })}
+                        div { class: "grid grid-cols-2 gap-3",
+                            div { class: "flex flex-col gap-1",
+                                label { class: "text-xs text-slate-600 dark:text-slate-300", {t("schedules.active_from")} }
+                                input {
+                                    r#type: "date",
+                                    class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                    value: form.read().active_from.clone(),
+                                    oninput: move |e| form.write().active_from = e.value(),
+                                }
+                            }
+                            div { class: "flex flex-col gap-1",
+                                label { class: "text-xs text-slate-600 dark:text-slate-300", {t("schedules.active_until")} }
+                                input {
+                                    r#type: "date",
+                                    class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                    value: form.read().active_until.clone(),
+                                    oninput: move |e| form.write().active_until = e.value(),
+                                }
+                            }
+                        }
+                        p { class: "text-xs text-slate-500 dark:text-slate-400", {t("schedules.active_hint")} }
+                    }
                     textarea {
                         class: "rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500 w-full h-20",
                         placeholder: t("schedules.description_optional"),
@@ -565,95 +648,13 @@ pub fn Schedules() -> Element {
                                                     v.retain(|x| *x != id);
                                                     selected.set(v);
                                                 }
-                                                Some(ConfirmAction::DeleteMany(_ids)) => {
-                                                    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                                    {
-                                                        for id in _ids.iter().copied() {
-                                                            let _ = dao::delete_schedule(id);
-                                                        }
-                                                        if let Ok(mut items) = dao::list_schedules() {
-                                                            let week_start = dao::get_configuration()
-                                                                .ok()
-                                                                .map(|c| c.week_start)
-                                                                .unwrap_or_else(|| "monday".into());
-                                                            let order = weekday_order_list(&week_start);
-                                                            items
-                                                                .sort_by(|a, b| {
-                                                                    weekday_rank(&a.weekday, &order)
-                                                                        .cmp(&weekday_rank(&b.weekday, &order))
-                                                                        .then(a.start_hour.cmp(&b.start_hour))
-                                                                });
-                                                            let mapped = items
-                                                                .into_iter()
-                                                                .map(|s: NativeSchedule| ScheduleListItem {
-                                                                    id: s.id,
-                                                                    title: format!(
-                                                                        "{} • {}–{}",
-                                                                        s.location,
-                                                                        s.start_hour,
-                                                                        s.end_hour,
-                                                                    ),
-                                                                    subtitle: format!(
-                                                                        "{}, {} {}, {} {}, {} {}, {} {}",
-                                                                        s.weekday,
-                                                                        s.num_publishers,
-                                                                        t("schedules.pubs_short"),
-                                                                        s.num_shift_managers,
-                                                                        t("schedules.managers_short"),
-                                                                        s.num_brothers,
-                                                                        t("schedules.brothers"),
-                                                                        s.num_sisters,
-                                                                        t("schedules.sisters"),
-                                                                    ),
-                                                                })
-                                                                .collect::<Vec<_>>();
-                                                            raw.set(mapped.clone());
-                                                            list.set(mapped);
-                                                        }
-                                                    }
-                                                    #[cfg(target_arch = "wasm32")]
-                                                    {
-                                                        for id in _ids.iter().copied() {
-                                                            wasm_backend::delete_schedule(id);
-                                                        }
-                                                        let mut items = wasm_backend::list_schedules();
-                                                        let week_start = wasm_backend::get_configuration()
-                                                            .map(|c| c.week_start)
-                                                            .unwrap_or_else(|| "monday".into());
-                                                        let order = weekday_order_list(&week_start);
-                                                        items
-                                                            .sort_by(|a, b| {
-                                                                weekday_rank(&a.weekday, &order)
-                                                                    .cmp(&weekday_rank(&b.weekday, &order))
-                                                                    .then(a.start_hour.cmp(&b.start_hour))
-                                                            });
-                                                        let mapped = items
-                                                            .into_iter()
-                                                            .map(|s: WebSchedule| ScheduleListItem {
-                                                                id: s.id,
-                                                                title: format!(
-                                                                    "{} • {}–{}",
-                                                                    s.location,
-                                                                    s.start_hour,
-                                                                    s.end_hour,
-                                                                ),
-                                                                subtitle: format!(
-                                                                    "{}, {} {}, {} {}, {} {}, {} {}",
-                                                                    s.weekday,
-                                                                    s.num_publishers,
-                                                                    t("schedules.pubs_short"),
-                                                                    s.num_shift_managers,
-                                                                    t("schedules.managers_short"),
-                                                                    s.num_brothers,
-                                                                    t("schedules.brothers"),
-                                                                    s.num_sisters,
-                                                                    t("schedules.sisters"),
-                                                                ),
-                                                            })
-                                                            .collect::<Vec<_>>();
-                                                        raw.set(mapped.clone());
-                                                        list.set(mapped);
+                                                Some(ConfirmAction::DeleteMany(ids)) => {
+                                                    for id in ids.iter().copied() {
+                                                        let _ = crate::db::store().delete_schedule(id);
                                                     }
+                                                    let mapped = load_items();
+                                                    raw.set(mapped.clone());
+                                                    list.set(mapped);
                                                     selected.set(vec![]);
                                                 }
                                                 None => {}
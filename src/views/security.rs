@@ -0,0 +1,139 @@
+use dioxus::prelude::*;
+use crate::db::cipher::{self, CipherError};
+use crate::i18n::t;
+
+fn error_text(e: CipherError) -> String {
+    match e {
+        CipherError::WrongPassphrase => t("unlock.wrong"),
+        CipherError::TooShort => t("unlock.too_short"),
+        e => e.to_string(),
+    }
+}
+
+// Configuration section on encrypted builds: change the passphrase, create a recovery code
+#[component]
+pub fn Security() -> Element {
+    let mut current = use_signal(String::new);
+    let mut new_pass = use_signal(String::new);
+    let mut confirm = use_signal(String::new);
+    let mut message = use_signal(|| Option::<String>::None);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut recovery_code = use_signal(|| Option::<String>::None);
+    let mut has_code = use_signal(cipher::has_recovery_code);
+
+    let on_change = move |_| {
+        message.set(None);
+        error.set(None);
+        if *new_pass.read() != *confirm.read() { error.set(Some(t("unlock.mismatch"))); return; }
+        let res = cipher::change_passphrase(&current.read(), &new_pass.read());
+        match res {
+            Ok(()) => {
+                current.set(String::new());
+                new_pass.set(String::new());
+                confirm.set(String::new());
+                message.set(Some(t("security.changed")));
+            }
+            Err(e) => error.set(Some(error_text(e))),
+        }
+    };
+
+    let on_generate = move |_| {
+        message.set(None);
+        error.set(None);
+        let res = cipher::create_recovery_code(&current.read());
+        match res {
+            Ok(code) => {
+                current.set(String::new());
+                recovery_code.set(Some(code));
+                has_code.set(true);
+            }
+            Err(e) => error.set(Some(error_text(e))),
+        }
+    };
+
+    let input_class = "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500";
+
+    rsx! {
+        div { class: "pt-2 border-t border-slate-200 dark:border-slate-700 mt-2 space-y-3",
+            h2 { class: "text-sm font-semibold text-slate-700 dark:text-slate-200",
+                {t("security.title")}
+            }
+            div { class: "flex flex-col gap-2",
+                label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                    {t("security.current")}
+                }
+                input {
+                    r#type: "password",
+                    class: input_class,
+                    value: current.read().clone(),
+                    oninput: move |e| current.set(e.value()),
+                }
+            }
+            div { class: "flex flex-col gap-2",
+                label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                    {t("security.new")}
+                }
+                input {
+                    r#type: "password",
+                    class: input_class,
+                    value: new_pass.read().clone(),
+                    oninput: move |e| new_pass.set(e.value()),
+                }
+            }
+            div { class: "flex flex-col gap-2",
+                label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                    {t("unlock.confirm")}
+                }
+                input {
+                    r#type: "password",
+                    class: input_class,
+                    value: confirm.read().clone(),
+                    oninput: move |e| confirm.set(e.value()),
+                }
+            }
+            div { class: "flex flex-col sm:flex-row gap-3 items-stretch justify-center w-full",
+                button {
+                    class: "inline-flex items-center justify-center gap-2 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium px-4 py-2 transition disabled:opacity-50 disabled:cursor-not-allowed w-full sm:w-56 h-10",
+                    disabled: current.read().is_empty() || new_pass.read().is_empty(),
+                    onclick: on_change,
+                    {t("security.change")}
+                }
+                button {
+                    class: "inline-flex items-center justify-center gap-2 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium px-4 py-2 transition disabled:opacity-50 disabled:cursor-not-allowed w-full sm:w-56 h-10",
+                    disabled: current.read().is_empty(),
+                    onclick: on_generate,
+                    {if has_code() { t("security.regenerate") } else { t("security.generate") }}
+                }
+            }
+            p { class: "text-xs text-slate-500 dark:text-slate-400", {t("security.recovery_help")} }
+            {message.read().as_ref().map(|m| rsx! {
+                p { class: "text-sm text-green-600 text-center", {m.clone()} }
+            })}
+            {error.read().as_ref().map(|e| rsx! {
+                p { class: "text-sm text-red-600 text-center", {e.clone()} }
+            })}
+        }
+
+        {recovery_code.read().as_ref().map(|code| rsx! {
+            div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
+                div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
+                    h2 { class: "text-lg font-semibold", {t("security.code_title")} }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300", {t("security.code_message")} }
+                    p { class: "font-mono text-lg text-center tracking-wider select-all break-all", {code.clone()} }
+                    div { class: "flex items-center justify-end gap-2",
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium transition",
+                            onclick: move |_| { document::eval("window.print()"); },
+                            {t("security.print")}
+                        }
+                        button {
+                            class: "inline-flex items-center h-9 px-3 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium transition",
+                            onclick: move |_| recovery_code.set(None),
+                            {t("security.done")}
+                        }
+                    }
+                }
+            }
+        })}
+    }
+}
@@ -4,22 +4,15 @@ use crate::i18n::{
     weekdays_for_locale,
     weekday_name_for_date,
 };
-#[cfg(any(all(feature = "native-db", not(target_arch = "wasm32")), target_arch = "wasm32"))]
-use crate::i18n::{weekday_index_for_date, weekday_index_from_name};
-
-// Backends
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-use crate::db::dao;
-#[cfg(target_arch = "wasm32")]
-use crate::db::wasm_store as wasm_backend;
+use crate::i18n::weekday_index_from_name;
+use crate::scheduler;
+use super::generations::GenerationHistory;
+use crate::db::store::{Blackout, GenerationBatch, RelationshipKind, Shift};
 
 // Date/time imports per target
 #[cfg(not(target_arch = "wasm32"))]
-use chrono::{Datelike, NaiveDate};
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-use chrono::Duration;
-#[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-use chrono::{NaiveDateTime, NaiveTime};
+use chrono::Datelike;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 #[cfg(target_arch = "wasm32")]
 use web_sys::window;
 #[cfg(target_arch = "wasm32")]
@@ -51,8 +44,17 @@ struct ManualForm {
 struct AutoForm {
     start: String,
     end: String,
+    mode: String, // scheduler::Mode key
+    seed: String,
 }
 
+// Fresh seed for the generate dialog: six digits, short enough to read out or type back in
+#[cfg(target_arch = "wasm32")]
+fn new_seed() -> u64 { (js::Math::random() * 1_000_000.0) as u64 }
+
+#[cfg(not(target_arch = "wasm32"))]
+fn new_seed() -> u64 { u64::from(chrono::Local::now().timestamp_subsec_nanos()) % 1_000_000 }
+
 #[derive(Clone, Default)]
 struct EditForm {
     shift_id: i64,
@@ -61,16 +63,47 @@ struct EditForm {
     end_dt: String,   // YYYY-MM-DDTHH:MM
     selected_pids: Vec<i64>,
     add_pid: String,
+    draft_index: Option<usize>, // editing a shift of the generated draft instead of a saved one
 }
 
 #[derive(Clone)]
 struct PublisherItem { id: i64, label: String }
 
+// A schedule as the month view needs it to show slots that have no shift yet
 #[derive(Clone)]
-#[allow(dead_code)]
-struct ScheduleFull { location: String }
+struct ScheduleFull {
+    location: String,
+    start_hour: String,
+    end_hour: String,
+    weekday: u32,
+    recurrence: crate::recurrence::Recurrence,
+}
 
 // Date helpers used across the view
+// Let the browser save a file from a data: URL
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, href: &str) {
+    let Some(doc) = window().and_then(|w| w.document()) else { return; };
+    if let Ok(a) = doc.create_element("a") {
+        a.set_attribute("href", href).ok();
+        a.set_attribute("download", file_name).ok();
+        if let Ok(ae) = a.dyn_into::<web_sys::HtmlElement>() { ae.click(); }
+    }
+}
+
+// "YYYY-MM-DD (weekday)" for the agenda; anything else is shown as given
+fn date_with_weekday(ymd: &str) -> String {
+    let parts: Vec<&str> = ymd.split('-').collect();
+    if parts.len() == 3 {
+        let y = parts[0].parse::<i32>().unwrap_or(1970);
+        let m = parts[1].parse::<u32>().unwrap_or(1);
+        let d = parts[2].parse::<u32>().unwrap_or(1);
+        format!("{} ({})", ymd, weekday_name_for_date(y, m, d))
+    } else {
+        ymd.to_string()
+    }
+}
+
 fn fmt_date_ymd(ymd: &(i32, u32, u32)) -> String { format!("{:04}-{:02}-{:02}", ymd.0, ymd.1, ymd.2) }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -100,13 +133,131 @@ fn now_year_month() -> (i32, u32) {
     (now.year(), now.month())
 }
 
-#[cfg(target_arch = "wasm32")]
-fn parse_ymd(s: &str) -> (i32, u32, u32) {
-    let parts: Vec<_> = s.split('-').collect();
-    let y = parts.get(0).and_then(|v| v.parse::<i32>().ok()).unwrap_or(1970);
-    let m = parts.get(1).and_then(|v| v.parse::<u32>().ok()).unwrap_or(1);
-    let d = parts.get(2).and_then(|v| v.parse::<u32>().ok()).unwrap_or(1);
-    (y, m, d)
+// Collect everything the generator needs from the active store
+fn scheduler_input(start: NaiveDate, end: NaiveDate, opts: &scheduler::Options) -> scheduler::Input {
+    let store = crate::db::store();
+    let publishers = store.list_publishers().unwrap_or_default();
+    let schedules = store.list_schedules().unwrap_or_default();
+    let mut availability = Vec::new();
+    for s in &schedules {
+        for pid in store.list_publishers_for_schedule(s.id).unwrap_or_default() { availability.push((pid, s.id)); }
+    }
+    let mut relationships = Vec::new();
+    for p in &publishers {
+        for (oid, kind) in store.list_relationships_for_publisher(p.id).unwrap_or_default() {
+            let kind = match kind { RelationshipKind::Mandatory => scheduler::RelationshipKind::Mandatory, RelationshipKind::Recommended => scheduler::RelationshipKind::Recommended };
+            if p.id < oid { relationships.push((p.id, oid, kind)); }
+        }
+    }
+    let absences = store.list_future_absences(start)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| scheduler::Absence { publisher_id: a.publisher_id, start: a.start_date, end: a.end_date })
+        .collect();
+    let blackouts = store.list_blackouts()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|b| b.end_date >= start && b.start_date <= end)
+        .collect();
+    let history = store.list_shifts_between(
+        NaiveDateTime::new(opts.history_start(start), NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+        NaiveDateTime::new(end, NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+    )
+    .unwrap_or_default()
+    .into_iter()
+    .map(|sh| scheduler::ExistingShift { start: sh.start, end: sh.end, location: sh.location, publishers: sh.publishers })
+    .collect();
+    scheduler::Input {
+        publishers: publishers
+            .iter()
+            .map(|p| scheduler::Publisher { id: p.id, gender: scheduler::Gender::from_label(&p.gender), is_shift_manager: p.is_shift_manager, priority: p.priority })
+            .collect(),
+        schedules: schedules
+            .iter()
+            .filter_map(|s| Some(scheduler::Schedule {
+                id: s.id,
+                location: s.location.clone(),
+                start: NaiveTime::parse_from_str(&s.start_hour, "%H:%M").ok()?,
+                end: NaiveTime::parse_from_str(&s.end_hour, "%H:%M").ok()?,
+                weekday: weekday_index_from_name(&s.weekday),
+                recurrence: s.recurrence.clone(),
+                num_publishers: s.num_publishers,
+                num_shift_managers: s.num_shift_managers,
+                num_brothers: s.num_brothers,
+                num_sisters: s.num_sisters,
+            }))
+            .collect(),
+        availability,
+        relationships,
+        absences,
+        blackouts,
+        history,
+        start,
+        end,
+    }
+}
+
+// How a draft shift compares with the saved shifts of its day: nothing there yet, a saved shift at the same
+// place overlaps it, or someone on it already serves elsewhere that day
+#[derive(Clone, Copy, PartialEq)]
+enum DraftDiff { New, Overlaps, Busy }
+
+impl DraftDiff {
+    fn of(sh: &scheduler::ProposedShift, saved: &[ShiftItem]) -> Self {
+        let date = sh.start.date().to_string();
+        let (start, end) = (sh.start.format("%H:%M").to_string(), sh.end.format("%H:%M").to_string());
+        let same_day = || saved.iter().filter(|it| it.date == date);
+        if same_day().any(|it| it.location == sh.location && it.start_hour < end && start < it.end_hour) { return DraftDiff::Overlaps; }
+        if same_day().any(|it| it.publishers.iter().any(|p| sh.publishers.contains(p))) { return DraftDiff::Busy; }
+        DraftDiff::New
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            DraftDiff::New => "shifts.draft_new",
+            DraftDiff::Overlaps => "shifts.draft_overlaps",
+            DraftDiff::Busy => "shifts.draft_busy",
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            DraftDiff::New => "border-emerald-400 bg-emerald-50 dark:bg-emerald-900/20",
+            DraftDiff::Overlaps => "border-amber-400 bg-amber-50 dark:bg-amber-900/20",
+            DraftDiff::Busy => "border-red-400 bg-red-50 dark:bg-red-900/20",
+        }
+    }
+}
+
+// The part of the draft that accept and discard act on: "" for all of it, "day:YYYY-MM-DD" or "loc:<location>"
+fn in_draft_scope(sh: &scheduler::ProposedShift, scope: &str) -> bool {
+    match scope.split_once(':') {
+        Some(("day", d)) => sh.start.date().to_string() == d,
+        Some(("loc", l)) => sh.location == l,
+        _ => true,
+    }
+}
+
+// One line per slot the exact solver left empty: date, place and time, and what blocked it
+fn blocked_report(input: &scheduler::Input, plan: &scheduler::Plan) -> Vec<String> {
+    plan.warnings.iter().filter_map(|w| {
+        let scheduler::WarningKind::Blocked(c) = w.kind else { return None };
+        let s = input.schedules.iter().find(|s| s.id == w.schedule_id)?;
+        Some(format!("{} · {} {}–{} · {}", crate::i18n::format_date_ymd(&w.date.to_string()), s.location, s.start.format("%H:%M"), s.end.format("%H:%M"), t(c.key())))
+    }).collect()
+}
+
+// One generator run over the dialog's range and mode: the plan, the slots the exact solver left empty, and
+// the run to record once the draft is accepted. None when the range doesn't parse.
+fn run_generation(form: &AutoForm, seed: u64) -> Option<(scheduler::Plan, Vec<String>, GenerationBatch)> {
+    let start = NaiveDate::parse_from_str(&form.start, "%Y-%m-%d").ok()?;
+    let end = NaiveDate::parse_from_str(&form.end, "%Y-%m-%d").ok()?;
+    let opts = scheduler::Options { seed, weights: crate::db::store().weights(), mode: scheduler::Mode::from_key(&form.mode) };
+    let input = scheduler_input(start, end, &opts);
+    let plan = scheduler::generate(&input, &opts);
+    let blocked = blocked_report(&input, &plan);
+    let run = GenerationBatch { id: 0, created_at: crate::db::now(), start, end, seed, mode: opts.mode.key().into(), weights: opts.weights };
+    Some((plan, blocked, run))
 }
 
 #[component]
@@ -120,11 +271,28 @@ pub fn Shifts() -> Element {
     let mut auto_open = use_signal(|| false);
     let mut export_open = use_signal(|| false);
     let mut generating = use_signal(|| false);
+    let mut blocked = use_signal(Vec::<String>::new); // exact mode: slots left empty by the last run
+    let mut last_seed = use_signal(|| Option::<u64>::None); // seed of the last run, to reproduce it
+    let mut auto_error = use_signal(|| Option::<String>::None);
+    // Generated shifts under review: nothing is saved until they are accepted
+    let mut draft = use_signal(Vec::<scheduler::ProposedShift>::new);
+    let mut draft_scope = use_signal(String::new);
+    let mut draft_error = use_signal(|| Option::<String>::None);
+    // The run the draft came from; accepted parts are recorded under it for the generation history
+    let mut draft_run = use_signal(|| Option::<GenerationBatch>::None);
+    let mut history_open = use_signal(|| false);
     // forms
     let mut auto_form = use_signal(AutoForm::default);
     #[derive(Clone, Default)]
-    struct ExportForm { start: String, end: String }
+    struct ExportForm {
+        start: String,
+        end: String,
+        ics_split: String, // "all", "publisher" or "location"
+    }
     let mut export_form = use_signal(ExportForm::default);
+    let mut export_error = use_signal(|| Option::<String>::None);
+    #[cfg_attr(not(all(feature = "native-db", not(target_arch = "wasm32"))), allow(unused_mut))]
+    let mut export_notice = use_signal(|| Option::<String>::None); // where a mobile export went when it couldn't be shared
 
     // data/signals required by the view
     let (yy, mm) = now_year_month();
@@ -133,6 +301,7 @@ pub fn Shifts() -> Element {
     let mut list = use_signal(|| Vec::<ShiftItem>::new());
     let mut publishers_all = use_signal(|| Vec::<PublisherItem>::new());
     let mut schedules_full = use_signal(|| Vec::<ScheduleFull>::new());
+    let mut blackouts = use_signal(Vec::<Blackout>::new);
     let mut selected_ids = use_signal(|| std::collections::BTreeSet::<i64>::new());
     let mut select_mode = use_signal(|| false);
     let mut manual_form = use_signal(ManualForm::default);
@@ -150,92 +319,49 @@ pub fn Shifts() -> Element {
         let mut loc_suggestions = loc_suggestions.clone();
         move || {
             let (y, m, last_day) = month_start_end(year(), month());
-            // Load publishers and schedules (full) for both targets
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            {
-                // publishers
-                let pubs = dao::list_publishers().unwrap_or_default();
-                let name_order = dao::get_configuration().ok().map(|c| c.name_order).unwrap_or_else(|| "first_last".into());
-                let mapped: Vec<PublisherItem> = pubs
-                    .iter()
-                    .map(|p| {
-                        let label = if name_order == "last_first" { format!("{} {}", p.last_name, p.first_name) } else { format!("{} {}", p.first_name, p.last_name) };
-                        PublisherItem { id: p.id, label }
-                    })
-                    .collect();
-                publishers_all.set(mapped);
-                // schedules full
-                let sch = dao::list_schedules().unwrap_or_default();
-                let full: Vec<ScheduleFull> = sch.iter().map(|s| ScheduleFull { location: s.location.clone() }).collect();
-                schedules_full_sig.set(full.clone());
-
-                // list items for current month
-                let start = NaiveDateTime::new(chrono::NaiveDate::from_ymd_opt(y, m, 1).unwrap(), chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-                let end = NaiveDateTime::new(chrono::NaiveDate::from_ymd_opt(y, m, last_day).unwrap(), chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap());
-                if let Ok(shifts) = dao::list_shifts_between(start, end) {
-                    let items: Vec<ShiftItem> = shifts
-                        .into_iter()
-                        .map(|s| {
-                            let date = s.start.date().to_string();
-                            let title = format!("{} • {}–{}", s.location, s.start.format("%H:%M"), s.end.format("%H:%M"));
-                            ShiftItem {
-                                id: s.id,
-                                date,
-                                title,
-                                publishers: s.publishers.clone(),
-                                location: s.location.clone(),
-                                start_hour: s.start.format("%H:%M").to_string(),
-                                end_hour: s.end.format("%H:%M").to_string(),
-                            }
-                        })
-                        .collect();
-                    list.set(items.clone());
-                    // suggestions from schedules + items
-                    use std::collections::BTreeSet;
-                    let mut set: BTreeSet<String> = full.iter().map(|s| s.location.clone()).collect();
-                    for it in items { set.insert(it.location); }
-                    loc_suggestions.set(set.into_iter().collect());
-                }
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                // publishers
-                let pubs = wasm_backend::list_publishers();
-                let name_order = wasm_backend::get_name_order();
-                let mapped: Vec<PublisherItem> = pubs
-                    .iter()
-                    .map(|p| {
-                        let label = if name_order == "last_first" { format!("{} {}", p.last_name, p.first_name) } else { format!("{} {}", p.first_name, p.last_name) };
-                        PublisherItem { id: p.id, label }
-                    })
-                    .collect();
-                publishers_all.set(mapped);
-                // schedules full
-                let sch = wasm_backend::list_schedules();
-                let full: Vec<ScheduleFull> = sch.iter().map(|s| ScheduleFull { location: s.location.clone() }).collect();
-                schedules_full_sig.set(full.clone());
+            let store = crate::db::store();
+            // publishers
+            let pubs = store.list_publishers().unwrap_or_default();
+            let name_order = store.name_order();
+            let mapped: Vec<PublisherItem> = pubs
+                .iter()
+                .map(|p| PublisherItem { id: p.id, label: p.display_name(&name_order) })
+                .collect();
+            publishers_all.set(mapped);
+            // schedules full
+            let sch = store.list_schedules().unwrap_or_default();
+            let full: Vec<ScheduleFull> = sch.iter().map(|s| ScheduleFull {
+                location: s.location.clone(),
+                start_hour: s.start_hour.clone(),
+                end_hour: s.end_hour.clone(),
+                weekday: weekday_index_from_name(&s.weekday),
+                recurrence: s.recurrence.clone(),
+            }).collect();
+            schedules_full_sig.set(full.clone());
+            let (first, last) = (NaiveDate::from_ymd_opt(y, m, 1).unwrap(), NaiveDate::from_ymd_opt(y, m, last_day).unwrap());
+            blackouts.set(store.list_blackouts().unwrap_or_default().into_iter().filter(|b| b.end_date >= first && b.start_date <= last).collect());
 
-                // list items for current month
-                let start = format!("{:04}-{:02}-01 00:00:00", y, m);
-                let end = format!("{:04}-{:02}-{:02} 23:59:59", y, m, last_day);
-                let shifts = wasm_backend::list_shifts_between(&start, &end);
+            // list items for current month
+            let start = NaiveDateTime::new(NaiveDate::from_ymd_opt(y, m, 1).unwrap(), NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+            let end = NaiveDateTime::new(NaiveDate::from_ymd_opt(y, m, last_day).unwrap(), NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+            if let Ok(shifts) = store.list_shifts_between(start, end) {
                 let items: Vec<ShiftItem> = shifts
                     .into_iter()
                     .map(|s| {
-                        let date = s.start_datetime[0..10].to_string();
-                        let title = format!("{} • {}–{}", s.location, &s.start_datetime[11..16], &s.end_datetime[11..16]);
+                        let date = s.start.date().to_string();
+                        let title = format!("{} • {}–{}", s.location, s.start.format("%H:%M"), s.end.format("%H:%M"));
                         ShiftItem {
                             id: s.id,
                             date,
                             title,
                             publishers: s.publishers.clone(),
                             location: s.location.clone(),
-                            start_hour: s.start_datetime[11..16].to_string(),
-                            end_hour: s.end_datetime[11..16].to_string(),
+                            start_hour: s.start.format("%H:%M").to_string(),
+                            end_hour: s.end.format("%H:%M").to_string(),
                         }
                     })
                     .collect();
-                // set items and build suggestions
+                // suggestions from schedules + items
                 use std::collections::BTreeSet;
                 let mut set: BTreeSet<String> = full.iter().map(|s| s.location.clone()).collect();
                 for it in &items { set.insert(it.location.clone()); }
@@ -287,10 +413,7 @@ pub fn Shifts() -> Element {
     let delete_one = {
         let mut refresh = refresh_month.clone();
         move |id: i64| {
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            { let _ = dao::delete_shift(id); }
-            #[cfg(target_arch = "wasm32")]
-            { let _ = wasm_backend::delete_shift(id); }
+            let _ = crate::db::store().delete_shift(id);
             refresh();
         }
     };
@@ -299,10 +422,7 @@ pub fn Shifts() -> Element {
         let mut refresh = refresh_month.clone();
         move |_| {
             let ids: Vec<i64> = selected_ids.read().iter().cloned().collect();
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            { for id in ids { let _ = dao::delete_shift(id); } }
-            #[cfg(target_arch = "wasm32")]
-            { for id in ids { let _ = wasm_backend::delete_shift(id); } }
+            for id in ids { let _ = crate::db::store().delete_shift(id); }
             selected_ids.write().clear();
             refresh();
         }
@@ -363,21 +483,10 @@ pub fn Shifts() -> Element {
         move |_| {
             let f = manual_form.read().clone();
             if f.loc.trim().is_empty() || f.start_dt.is_empty() || f.end_dt.is_empty() { return; }
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            {
-                use chrono::NaiveDateTime;
-                let start = f.start_dt.replace('T', " ") + ":00";
-                let end = f.end_dt.replace('T', " ") + ":00";
-                if let (Ok(st), Ok(et)) = (NaiveDateTime::parse_from_str(&start, "%Y-%m-%d %H:%M:%S"), NaiveDateTime::parse_from_str(&end, "%Y-%m-%d %H:%M:%S")) {
-                    let _ = dao::create_shift(st, et, &f.loc, &f.selected_pids, None);
-                    refresh();
-                }
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                let start = f.start_dt.replace('T', " ") + ":00";
-                let end = f.end_dt.replace('T', " ") + ":00";
-                let _ = wasm_backend::create_shift(&start, &end, &f.loc, &f.selected_pids, None);
+            let start = f.start_dt.replace('T', " ") + ":00";
+            let end = f.end_dt.replace('T', " ") + ":00";
+            if let (Ok(st), Ok(et)) = (NaiveDateTime::parse_from_str(&start, "%Y-%m-%d %H:%M:%S"), NaiveDateTime::parse_from_str(&end, "%Y-%m-%d %H:%M:%S")) {
+                let _ = crate::db::store().create_shift(st, et, &f.loc, &f.selected_pids, None);
                 refresh();
             }
             manual_open.set(false);
@@ -391,29 +500,31 @@ pub fn Shifts() -> Element {
         let mut refresh = refresh_month.clone();
         move |_| {
             let f = edit_form.read();
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            {
-                use chrono::NaiveDateTime;
-                // update publishers
-                let _ = dao::update_shift_publishers(f.shift_id, &f.selected_pids, None);
-                // update datetime + location if valid
-                if !f.start_dt.is_empty() && !f.end_dt.is_empty() {
+            if let Some(i) = f.draft_index {
+                // A draft shift only changes in memory until the draft is accepted
+                if let Some(sh) = draft.write().get_mut(i) {
+                    sh.publishers = f.selected_pids.clone();
+                    sh.warning = None;
                     let start = f.start_dt.replace('T', " ") + ":00";
                     let end = f.end_dt.replace('T', " ") + ":00";
                     if let (Ok(st), Ok(et)) = (NaiveDateTime::parse_from_str(&start, "%Y-%m-%d %H:%M:%S"), NaiveDateTime::parse_from_str(&end, "%Y-%m-%d %H:%M:%S")) {
-                        let _ = dao::update_shift_datetime_location(f.shift_id, st, et, &f.loc, None);
+                        sh.start = st;
+                        sh.end = et;
+                        sh.location = f.loc.clone();
                     }
                 }
+                edit_open.set(false);
+                return;
             }
-            #[cfg(target_arch = "wasm32")]
-            {
-                // update publishers
-                let _ = wasm_backend::update_shift_publishers(f.shift_id, &f.selected_pids, None);
-                // update datetime + location
-                if !f.start_dt.is_empty() && !f.end_dt.is_empty() {
-                    let start = f.start_dt.replace('T', " ") + ":00";
-                    let end = f.end_dt.replace('T', " ") + ":00";
-                    let _ = wasm_backend::update_shift_datetime_location(f.shift_id, &start, &end, &f.loc, None);
+            let store = crate::db::store();
+            // update publishers
+            let _ = store.update_shift_publishers(f.shift_id, &f.selected_pids, None);
+            // update datetime + location if valid
+            if !f.start_dt.is_empty() && !f.end_dt.is_empty() {
+                let start = f.start_dt.replace('T', " ") + ":00";
+                let end = f.end_dt.replace('T', " ") + ":00";
+                if let (Ok(st), Ok(et)) = (NaiveDateTime::parse_from_str(&start, "%Y-%m-%d %H:%M:%S"), NaiveDateTime::parse_from_str(&end, "%Y-%m-%d %H:%M:%S")) {
+                    let _ = store.update_shift_datetime_location(f.shift_id, st, et, &f.loc, None);
                 }
             }
             refresh();
@@ -429,583 +540,141 @@ pub fn Shifts() -> Element {
     let refresh_fn = refresh_month.clone();
         move |_| {
             if generating_sig() { return; }
+            // Same seed over the same data gives the same shifts
+            let Ok(seed) = auto_form_sig.read().seed.trim().parse::<u64>() else { auto_error.set(Some(t("shifts.seed_invalid"))); return; };
             // Close the modal first to avoid re-entrant borrows from conditional UI
             auto_open_sig.set(false);
 
-            #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-            {
+            let form = auto_form_sig.read().clone();
+            let mut refresh = refresh_fn.clone();
+            let mut generate = move || {
                 generating_sig.set(true);
-                let form = auto_form_sig.read().clone();
-                use std::collections::{HashMap, HashSet};
-                let start_d = NaiveDate::parse_from_str(&form.start, "%Y-%m-%d").unwrap();
-                let end_d = NaiveDate::parse_from_str(&form.end, "%Y-%m-%d").unwrap();
-                let schedules = dao::list_schedules().unwrap_or_default();
-                let publishers = dao::list_publishers().unwrap_or_default();
-                // relationships map
-                let mut rel_map: HashMap<i64, Vec<(i64, dao::RelationshipKind)>> = HashMap::new();
-                for p in &publishers { if let Ok(rs) = dao::list_relationships_for_publisher(p.id) { rel_map.insert(p.id, rs); } }
-                // fairness window
-                let hist_start = start_d - Duration::days(60);
-                let hist = dao::list_shifts_between(
-                    NaiveDateTime::new(hist_start, NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
-                    NaiveDateTime::new(end_d, NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
-                )
-                .unwrap_or_default();
-                let mut recent_count: HashMap<i64, i32> = HashMap::new();
-                let mut pair_count: HashMap<(i64, i64), i32> = HashMap::new();
-                for sh in &hist {
-                    for &p in &sh.publishers {
-                        *recent_count.entry(p).or_insert(0) += 1;
-                    }
-                    for i in 0..sh.publishers.len() {
-                        for j in (i + 1)..sh.publishers.len() {
-                            let a = sh.publishers[i].min(sh.publishers[j]);
-                            let b = sh.publishers[i].max(sh.publishers[j]);
-                            *pair_count.entry((a, b)).or_insert(0) += 1;
-                        }
-                    }
-                }
-                let mut assigned_on_day: HashMap<NaiveDate, HashSet<i64>> = HashMap::new();
-                let seed = chrono::Local::now().timestamp_nanos_opt().unwrap_or(0) as u64;
-                let mut rand_for = |pid: i64, day: NaiveDate| -> f64 {
-                    let x = (pid as u64)
-                        .wrapping_mul(6364136223846793005)
-                        .wrapping_add(seed ^ (day.num_days_from_ce() as u64));
-                    ((x >> 11) as f64) / (u64::MAX >> 11) as f64
-                };
-
-                let mut d = start_d;
-                while d <= end_d {
-                    let day_idx = weekday_index_for_date(d.year(), d.month(), d.day());
-                    for s in schedules.iter() {
-                        if weekday_index_from_name(&s.weekday) != day_idx { continue; }
-                        let start_dt = NaiveDateTime::new(d, NaiveTime::parse_from_str(&s.start_hour, "%H:%M").unwrap());
-                        let end_dt = NaiveDateTime::new(d, NaiveTime::parse_from_str(&s.end_hour, "%H:%M").unwrap());
-                        // skip existing identical shift
-                        let existing = dao::list_shifts_between(start_dt, end_dt).unwrap_or_default();
-                        if existing.iter().any(|e| e.location == s.location && e.start == start_dt && e.end == end_dt) { continue; }
-                        // candidates
-                        let avail_ids = dao::list_publishers_for_schedule(s.id).unwrap_or_default();
-                        let day_assigned = assigned_on_day.entry(d).or_default().clone();
-                        let candidates: Vec<_> = publishers
-                            .iter()
-                            .filter(|p| avail_ids.contains(&p.id))
-                            .filter(|p| !day_assigned.contains(&p.id))
-                            .filter(|p| !dao::is_absent_on(p.id, d).unwrap_or(false))
-                            .cloned()
-                            .collect();
-
-                        let score = |p_id: i64, selected: &Vec<i64>| -> f64 {
-                            let p = publishers.iter().find(|x| x.id == p_id).unwrap();
-                            let base = (p.priority as f64) * 10.0;
-                            let rec_pen = (*recent_count.get(&p_id).unwrap_or(&0)) as f64 * 2.0;
-                            let pair_pen: f64 = selected
-                                .iter()
-                                .map(|&o| {
-                                    let a = p_id.min(o);
-                                    let b = p_id.max(o);
-                                    (*pair_count.get(&(a, b)).unwrap_or(&0)) as f64 * 1.5
-                                })
-                                .sum();
-                            // relationship bonus for recommended, enforced for mandatory
-                            let mut rel_bonus = 0.0;
-                            if let Some(rs) = rel_map.get(&p_id) {
-                                for &o in selected.iter() {
-                                    if let Some((_, kind)) = rs.iter().find(|(oid, _)| *oid == o) {
-                                        match kind { dao::RelationshipKind::Recommended => rel_bonus += 2.0, dao::RelationshipKind::Mandatory => rel_bonus += 5.0 }
-                                    }
-                                }
-                            }
-                            let jitter = rand_for(p_id, d) * 3.0;
-                            base + jitter + rel_bonus - rec_pen - pair_pen
-                        };
-
-                        let mut selected: Vec<i64> = Vec::new();
-                        let mut warning: Option<String> = None;
-                        // managers first (male)
-                        let mut mgr_pool: Vec<_> = candidates
-                            .iter()
-                            .filter(|p| p.is_shift_manager && p.gender == "Male")
-                            .map(|p| p.id)
-                            .collect();
-                        mgr_pool.sort_by(|a, b| {
-                            use std::cmp::Ordering;
-                            score(*b, &selected)
-                                .partial_cmp(&score(*a, &selected))
-                                .unwrap_or(Ordering::Equal)
-                                .then_with(|| a.cmp(b))
-                        });
-                        for pid in mgr_pool.into_iter().take(s.num_shift_managers as usize) {
-                            if !selected.contains(&pid) { selected.push(pid); }
-                        }
-                        // brothers (male) including managers
-                        let male_have = selected.iter().filter(|pid| publishers.iter().any(|p| p.id == **pid && p.gender == "Male")).count();
-                        let male_needed = (s.num_brothers as usize).saturating_sub(male_have);
-                        let mut male_pool: Vec<_> = candidates.iter().filter(|p| p.gender == "Male" && !selected.contains(&p.id)).map(|p| p.id).collect();
-                        male_pool.sort_by(|a, b| {
-                            use std::cmp::Ordering;
-                            score(*b, &selected)
-                                .partial_cmp(&score(*a, &selected))
-                                .unwrap_or(Ordering::Equal)
-                                .then_with(|| a.cmp(b))
-                        });
-                        for pid in male_pool.into_iter().take(male_needed) { selected.push(pid); }
-                        // sisters
-                        let female_needed = s.num_sisters as usize;
-                        let mut female_pool: Vec<_> = candidates.iter().filter(|p| p.gender == "Female" && !selected.contains(&p.id)).map(|p| p.id).collect();
-                        female_pool.sort_by(|a, b| {
-                            use std::cmp::Ordering;
-                            score(*b, &selected)
-                                .partial_cmp(&score(*a, &selected))
-                                .unwrap_or(Ordering::Equal)
-                                .then_with(|| a.cmp(b))
-                        });
-                        for pid in female_pool.into_iter().take(female_needed) { selected.push(pid); }
-                        // Enforce mandatory relationships: if one selected, ensure its mandatory partners are added if available
-                        {
-                            let mut must_have: Vec<i64> = Vec::new();
-                            for pid in selected.iter().copied() {
-                                if let Some(rs) = rel_map.get(&pid) {
-                                    for (oid, k) in rs.iter() {
-                                        if matches!(k, dao::RelationshipKind::Mandatory) { must_have.push(*oid); }
-                                    }
-                                }
-                            }
-                            for oid in must_have {
-                                if !selected.contains(&oid) && candidates.iter().any(|p| p.id==oid) && !dao::is_absent_on(oid, d).unwrap_or(false) {
-                                    selected.push(oid);
-                                }
-                            }
-                        }
-                        // Rebalance to respect manager and gender minima
-                        {
-                            use std::collections::HashSet;
-                            // Build quick lookups
-                            let is_manager = |pid: i64| publishers.iter().any(|p| p.id==pid && p.is_shift_manager && p.gender=="Male");
-                            let is_male = |pid: i64| publishers.iter().any(|p| p.id==pid && p.gender=="Male");
-                            let is_female = |pid: i64| publishers.iter().any(|p| p.id==pid && p.gender=="Female");
-                            let mut mandatory_set: HashSet<i64> = HashSet::new();
-                            let sel_snapshot = selected.clone();
-                            for &pid in &sel_snapshot { if let Some(rs) = rel_map.get(&pid) { for &(oid, ref k) in rs.iter() { if matches!(k, dao::RelationshipKind::Mandatory) && sel_snapshot.contains(&oid) { mandatory_set.insert(pid); mandatory_set.insert(oid); } } } }
-                            let mut count_mgr = selected.iter().filter(|pid| is_manager(**pid)).count() as i64;
-                            let mut count_male = selected.iter().filter(|pid| is_male(**pid)).count() as i64;
-                            let mut count_female = selected.iter().filter(|pid| is_female(**pid)).count() as i64;
-
-                            // Reduce managers if exceeding required
-                            if count_mgr > s.num_shift_managers {
-                                let excess = (count_mgr - s.num_shift_managers) as usize;
-                                // candidates to remove: managers not in mandatory_set, lowest score first
-                                let mut mgr_removals: Vec<(i64, f64)> = selected.iter().cloned().filter(|pid| is_manager(*pid) && !mandatory_set.contains(pid)).map(|pid| (pid, score(pid, &selected))).collect();
-                                mgr_removals.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                let mut removed = 0usize;
-                                for (pid,_sc) in mgr_removals {
-                                    if removed >= excess { break; }
-                                    if let Some(pos) = selected.iter().position(|x| *x==pid) { selected.remove(pos); removed+=1; count_mgr-=1; count_male-=1; }
-                                }
-                                if removed < excess { warning = Some("Could not reduce extra managers due to mandatory pairs".into()); }
-                            }
-
-                            // Ensure minimum sisters
-                            while count_female < s.num_sisters {
-                                // pick best female candidate not selected
-                                let mut fem_pool: Vec<(i64,f64)> = candidates.iter().filter(|p| p.gender=="Female" && !selected.contains(&p.id)).map(|p| (p.id, score(p.id, &selected))).collect();
-                                if fem_pool.is_empty() { warning = Some("Fewer sisters available than required".into()); break; }
-                                fem_pool.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                                let (add_id, _) = fem_pool[0];
-                                if selected.len() >= s.num_publishers as usize {
-                                    // remove lowest scoring non-mandatory male (prefer non-manager)
-                                    let mut male_candidates: Vec<(i64,f64)> = selected.iter().cloned().filter(|pid| is_male(*pid) && !mandatory_set.contains(pid) && !is_manager(*pid)).map(|pid| (pid, score(pid, &selected))).collect();
-                                    if male_candidates.is_empty() { male_candidates = selected.iter().cloned().filter(|pid| is_male(*pid) && !mandatory_set.contains(pid)).map(|pid| (pid, score(pid, &selected))).collect(); }
-                                    male_candidates.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                    if let Some((rm,_)) = male_candidates.first().cloned() { if let Some(pos)=selected.iter().position(|x| *x==rm){ selected.remove(pos); if is_manager(rm){ count_mgr-=1; } count_male-=1; } } else { warning = Some("Cannot free slot to add required sister".into()); break; }
-                                }
-                                selected.push(add_id); count_female+=1;
-                            }
-
-                            // Ensure minimum brothers
-                            while count_male < s.num_brothers {
-                                // pick best male candidate not selected (prefer non-manager if manager quota met)
-                                let prefer_non_mgr = count_mgr >= s.num_shift_managers;
-                                let mut male_pool: Vec<(i64,f64)> = candidates.iter().filter(|p| p.gender=="Male" && !selected.contains(&p.id) && (!prefer_non_mgr || !p.is_shift_manager)).map(|p| (p.id, score(p.id, &selected))).collect();
-                                if male_pool.is_empty() { // fallback allow managers
-                                    male_pool = candidates.iter().filter(|p| p.gender=="Male" && !selected.contains(&p.id)).map(|p| (p.id, score(p.id, &selected))).collect();
-                                }
-                                if male_pool.is_empty() { warning = Some("Fewer brothers available than required".into()); break; }
-                                male_pool.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                                let (add_id, _) = male_pool[0];
-                                if selected.len() >= s.num_publishers as usize {
-                                    // remove lowest scoring non-mandatory female
-                                    let mut fem_candidates: Vec<(i64,f64)> = selected.iter().cloned().filter(|pid| is_female(*pid) && !mandatory_set.contains(pid)).map(|pid| (pid, score(pid, &selected))).collect();
-                                    fem_candidates.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                    if let Some((rm,_)) = fem_candidates.first().cloned() { if let Some(pos)=selected.iter().position(|x| *x==rm){ selected.remove(pos); count_female-=1; } } else { warning = Some("Cannot free slot to add required brother".into()); break; }
-                                }
-                                if is_manager(add_id) { count_mgr+=1; }
-                                selected.push(add_id); count_male+=1;
-                            }
-                        }
-                        // fill remaining
-                        let remaining_slots = (s.num_publishers as usize).saturating_sub(selected.len());
-                        if remaining_slots > 0 {
-                            let mut rest: Vec<_> = candidates.iter().filter(|p| !selected.contains(&p.id)).map(|p| p.id).collect();
-                            rest.sort_by(|a, b| {
-                                use std::cmp::Ordering;
-                                score(*b, &selected)
-                                    .partial_cmp(&score(*a, &selected))
-                                    .unwrap_or(Ordering::Equal)
-                                    .then_with(|| a.cmp(b))
-                            });
-                            for pid in rest.into_iter().take(remaining_slots) { selected.push(pid); }
-                        }
-                        // Ensure we don't exceed capacity; prefer keeping mandatory pairs
-                        if selected.len() > s.num_publishers as usize {
-                            use std::collections::HashSet;
-                            let limit = s.num_publishers as usize;
-                            let selected_clone = selected.clone();
-                            let mut mandatory_set: HashSet<i64> = HashSet::new();
-                            for &pid in &selected_clone {
-                                if let Some(rs) = rel_map.get(&pid) {
-                                    for &(oid, ref kind) in rs.iter() {
-                                        if matches!(kind, dao::RelationshipKind::Mandatory) && selected_clone.contains(&oid) {
-                                            mandatory_set.insert(pid);
-                                            mandatory_set.insert(oid);
-                                        }
-                                    }
-                                }
-                            }
-                            // removable are those not in mandatory_set
-                            let mut removable: Vec<i64> = selected.iter().copied().filter(|pid| !mandatory_set.contains(pid)).collect();
-                            // Build a score list to avoid borrowing 'selected' in sort comparator
-                            let mut scored: Vec<(i64, f64)> = removable.iter().map(|pid| (*pid, score(*pid, &selected))).collect();
-                            scored.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                            // remove lowest scoring until within limit
-                            for &(pid, _) in scored.iter().rev() { // lowest score last
-                                if selected.len() <= limit { break; }
-                                if let Some(pos) = selected.iter().position(|x| *x == pid) { selected.remove(pos); }
-                            }
-                            if selected.len() > limit {
-                                // As last resort, drop lowest-scoring overall
-                                let mut overall: Vec<(i64,f64)> = selected.iter().map(|pid| (*pid, score(*pid, &selected))).collect();
-                                overall.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                while selected.len() > limit {
-                                    if let Some((pid,_)) = overall.first().copied() {
-                                        if let Some(pos) = selected.iter().position(|x| *x == pid) { selected.remove(pos); }
-                                        overall.remove(0);
-                                    } else { break; }
-                                }
-                                warning = Some("Had to drop some selections due to capacity; mandatory pairs may be affected".into());
-                            }
-                            if warning.is_none() { warning = Some("Trimmed extra selections to fit capacity".into()); }
-                        }
-                        if selected.len() < s.num_publishers as usize { warning = Some("Not enough available publishers".into()); }
-                        let _ = dao::create_shift(start_dt, end_dt, &s.location, &selected, warning.as_deref());
-                        let set = assigned_on_day.entry(d).or_default();
-                        for &pid in &selected {
-                            set.insert(pid);
-                            *recent_count.entry(pid).or_insert(0) += 1;
-                            for &other in &selected { if pid < other { *pair_count.entry((pid, other)).or_insert(0) += 1; } }
-                        }
-                    }
-                    d = d.succ_opt().unwrap();
+                if let Some((plan, report, run)) = run_generation(&form, seed) {
+                    blocked.set(report);
+                    // Nothing is written yet: the plan replaces any previous draft for review in the calendar
+                    draft.set(plan.shifts);
+                    draft_run.set(Some(run));
+                    draft_scope.set(String::new());
+                    draft_error.set(None);
+                    last_seed.set(Some(seed));
                 }
                 generating_sig.set(false);
-                let mut refresh = refresh_fn.clone();
                 refresh();
-            }
+            };
+
+            #[cfg(not(target_arch = "wasm32"))]
+            generate();
 
             #[cfg(target_arch = "wasm32")]
             {
                 // Defer heavy work to the next tick to avoid re-entrant borrows in the click handler
-                let mut generating = generating_sig.clone();
-                let auto_form = auto_form_sig.clone();
-                let mut refresh = refresh_fn.clone();
-                let cb = Closure::wrap(Box::new(move || {
-                    generating.set(true);
-                    let form = auto_form.read().clone();
-                    use std::collections::{HashMap, HashSet};
-                    let start = form.start.clone();
-                    let end = form.end.clone();
-                    let schedules = wasm_backend::list_schedules();
-                    let publishers = wasm_backend::list_publishers();
-                    // relationships map
-                    let mut rel_map: std::collections::HashMap<i64, Vec<(i64, wasm_backend::RelationshipKind)>> = std::collections::HashMap::new();
-                    for p in &publishers { let rs = wasm_backend::list_relationships_for_publisher(p.id); rel_map.insert(p.id, rs); }
-                    // fairness from last 60 days
-                    let (sy, sm, sd) = parse_ymd(&start);
-                    let mut hist_start = js::Date::new_with_year_month_day(sy as u32, (sm as i32) - 1, sd as i32);
-                    hist_start.set_time(hist_start.get_time() - 60.0 * 24.0 * 3600.0 * 1000.0);
-                    let hist = wasm_backend::list_shifts_between(
-                        &format!("{:04}-{:02}-{:02} 00:00:00", hist_start.get_full_year() as i32, hist_start.get_month() as u32 + 1, hist_start.get_date() as u32),
-                        &format!("{} 23:59:59", end),
-                    );
-                    let mut recent_count: HashMap<i64, i32> = HashMap::new();
-                    let mut pair_count: HashMap<(i64, i64), i32> = HashMap::new();
-                    for sh in &hist {
-                        for &p in &sh.publishers { *recent_count.entry(p).or_insert(0) += 1; }
-                        for i in 0..sh.publishers.len() { for j in (i + 1)..sh.publishers.len() { let a = sh.publishers[i].min(sh.publishers[j]); let b = sh.publishers[i].max(sh.publishers[j]); *pair_count.entry((a, b)).or_insert(0) += 1; } }
-                    }
-                    let mut assigned_on_day: HashMap<String, HashSet<i64>> = HashMap::new();
-                    let (ey, em, ed) = parse_ymd(&end);
-                    let mut cur = js::Date::new_with_year_month_day(sy as u32, (sm as i32) - 1, sd as i32);
-                    let end_d = js::Date::new_with_year_month_day(ey as u32, (em as i32) - 1, ed as i32);
-                    while cur.value_of() <= end_d.value_of() {
-                        let y = cur.get_full_year() as i32; let m = cur.get_month() as u32 + 1; let d = cur.get_date() as u32;
-                        let ymd = format!("{:04}-{:02}-{:02}", y, m, d);
-                        let day_idx = weekday_index_for_date(y, m, d);
-                        // Deterministic jitter for this day
-                        let day_seed: u64 = ((y as u64) << 32) ^ ((m as u64) << 16) ^ (d as u64);
-                        let rand_for = |pid: i64| -> f64 {
-                            let mut x = (pid as u64).wrapping_mul(6364136223846793005).wrapping_add(day_seed);
-                            // splitmix64 steps
-                            x ^= x >> 30; x = x.wrapping_mul(0xbf58476d1ce4e5b9);
-                            x ^= x >> 27; x = x.wrapping_mul(0x94d049bb133111eb);
-                            x ^= x >> 31;
-                            (x as f64) / (u64::MAX as f64)
-                        };
-                        for s in schedules.iter() {
-                            if weekday_index_from_name(&s.weekday) != day_idx { continue; }
-                            let start_dt = format!("{} {}:00", ymd, s.start_hour);
-                            let end_dt = format!("{} {}:00", ymd, s.end_hour);
-                            let existing = wasm_backend::list_shifts_between(&start_dt, &end_dt);
-                            if existing.iter().any(|e| e.location == s.location && e.start_datetime == start_dt && e.end_datetime == end_dt) { continue; }
-                            let avail_ids = wasm_backend::list_publishers_for_schedule(s.id);
-                            let day_assigned = assigned_on_day.entry(ymd.clone()).or_default().clone();
-                            let candidates: Vec<_> = publishers.iter().filter(|p| avail_ids.contains(&p.id)).filter(|p| !day_assigned.contains(&p.id)).filter(|p| !wasm_backend::is_absent_on(p.id, &ymd)).cloned().collect();
-                            let score = |p_id: i64, selected: &Vec<i64>| -> f64 {
-                                let p = publishers.iter().find(|x| x.id == p_id).unwrap();
-                                let base = (p.priority as f64) * 10.0;
-                                let rec_pen = (*recent_count.get(&p_id).unwrap_or(&0)) as f64 * 2.0;
-                                let pair_pen: f64 = selected.iter().map(|&o| { let a = p_id.min(o); let b = p_id.max(o); (*pair_count.get(&(a,b)).unwrap_or(&0)) as f64 * 1.5 }).sum();
-                                let mut rel_bonus = 0.0;
-                                if let Some(rs) = rel_map.get(&p_id) {
-                                    for &o in selected.iter() {
-                                        if let Some((_, kind)) = rs.iter().find(|(oid, _)| *oid == o) {
-                                            match kind { wasm_backend::RelationshipKind::Recommended => rel_bonus += 2.0, wasm_backend::RelationshipKind::Mandatory => rel_bonus += 5.0 }
-                                        }
-                                    }
-                                }
-                                let jitter = rand_for(p_id) * 3.0;
-                                base + jitter + rel_bonus - rec_pen - pair_pen
-                            };
-                            let mut selected: Vec<i64> = Vec::new();
-                            let mut warning: Option<String> = None;
-                            let mut mgr_pool: Vec<_> = candidates.iter().filter(|p| p.is_shift_manager && p.gender == "Male").map(|p| p.id).collect();
-                            mgr_pool.sort_by(|a,b| {
-                                use std::cmp::Ordering;
-                                score(*b,&selected)
-                                    .partial_cmp(&score(*a,&selected))
-                                    .unwrap_or(Ordering::Equal)
-                                    .then_with(|| a.cmp(b))
-                            });
-                            for pid in mgr_pool.into_iter().take(s.num_shift_managers as usize) { if !selected.contains(&pid) { selected.push(pid); } }
-                            let male_have = selected.iter().filter(|pid| publishers.iter().any(|p| p.id == **pid && p.gender == "Male")).count();
-                            let male_needed = (s.num_brothers as usize).saturating_sub(male_have);
-                            let mut male_pool: Vec<_> = candidates.iter().filter(|p| p.gender == "Male" && !selected.contains(&p.id)).map(|p| p.id).collect();
-                            male_pool.sort_by(|a,b| {
-                                use std::cmp::Ordering;
-                                score(*b,&selected)
-                                    .partial_cmp(&score(*a,&selected))
-                                    .unwrap_or(Ordering::Equal)
-                                    .then_with(|| a.cmp(b))
-                            });
-                            for pid in male_pool.into_iter().take(male_needed) { selected.push(pid); }
-                            let female_needed = s.num_sisters as usize;
-                            let mut female_pool: Vec<_> = candidates.iter().filter(|p| p.gender == "Female" && !selected.contains(&p.id)).map(|p| p.id).collect();
-                            female_pool.sort_by(|a,b| {
-                                use std::cmp::Ordering;
-                                score(*b,&selected)
-                                    .partial_cmp(&score(*a,&selected))
-                                    .unwrap_or(Ordering::Equal)
-                                    .then_with(|| a.cmp(b))
-                            });
-                            for pid in female_pool.into_iter().take(female_needed) { selected.push(pid); }
-                            // Enforce mandatory relationships for already selected publishers
-                            {
-                                let mut must_have: Vec<i64> = Vec::new();
-                                for pid in selected.iter().copied() {
-                                    if let Some(rs) = rel_map.get(&pid) {
-                                        for (oid, k) in rs.iter() {
-                                            if matches!(k, wasm_backend::RelationshipKind::Mandatory) { must_have.push(*oid); }
-                                        }
-                                    }
-                                }
-                                for oid in must_have {
-                                    if !selected.contains(&oid) && candidates.iter().any(|p| p.id==oid) && !wasm_backend::is_absent_on(oid, &ymd) {
-                                        selected.push(oid);
-                                    }
-                                }
-                            }
-                            // Rebalance to respect manager and gender minima
-                            {
-                                use std::collections::HashSet;
-                                let is_manager = |pid: i64| publishers.iter().any(|p| p.id==pid && p.is_shift_manager && p.gender=="Male");
-                                let is_male = |pid: i64| publishers.iter().any(|p| p.id==pid && p.gender=="Male");
-                                let is_female = |pid: i64| publishers.iter().any(|p| p.id==pid && p.gender=="Female");
-                                let mut mandatory_set: HashSet<i64> = HashSet::new();
-                                let sel_snapshot = selected.clone();
-                                for &pid in &sel_snapshot { if let Some(rs) = rel_map.get(&pid) { for &(oid, ref k) in rs.iter() { if matches!(k, wasm_backend::RelationshipKind::Mandatory) && sel_snapshot.contains(&oid) { mandatory_set.insert(pid); mandatory_set.insert(oid); } } } }
-                                let mut count_mgr = selected.iter().filter(|pid| is_manager(**pid)).count() as i64;
-                                let mut count_male = selected.iter().filter(|pid| is_male(**pid)).count() as i64;
-                                let mut count_female = selected.iter().filter(|pid| is_female(**pid)).count() as i64;
-
-                                // Reduce managers if exceeding required
-                                if count_mgr > s.num_shift_managers {
-                                    let excess = (count_mgr - s.num_shift_managers) as usize;
-                                    let mut mgr_removals: Vec<(i64, f64)> = selected.iter().cloned().filter(|pid| is_manager(*pid) && !mandatory_set.contains(pid)).map(|pid| (pid, score(pid, &selected))).collect();
-                                    mgr_removals.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                    let mut removed = 0usize;
-                                    for (pid, _) in mgr_removals { if removed>=excess { break; } if let Some(pos)=selected.iter().position(|x| *x==pid){ selected.remove(pos); removed+=1; count_mgr-=1; count_male-=1; } }
-                                    if removed < excess { warning = Some("Could not reduce extra managers due to mandatory pairs".into()); }
-                                }
-
-                                // Ensure minimum sisters
-                                while count_female < s.num_sisters {
-                                    let mut fem_pool: Vec<(i64,f64)> = candidates.iter().filter(|p| p.gender=="Female" && !selected.contains(&p.id)).map(|p| (p.id, score(p.id, &selected))).collect();
-                                    if fem_pool.is_empty() { warning = Some("Fewer sisters available than required".into()); break; }
-                                    fem_pool.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                                    let (add_id, _) = fem_pool[0];
-                                    if selected.len() >= s.num_publishers as usize {
-                                        let mut male_candidates: Vec<(i64,f64)> = selected.iter().cloned().filter(|pid| is_male(*pid) && !mandatory_set.contains(pid) && !is_manager(*pid)).map(|pid| (pid, score(pid, &selected))).collect();
-                                        if male_candidates.is_empty() { male_candidates = selected.iter().cloned().filter(|pid| is_male(*pid) && !mandatory_set.contains(pid)).map(|pid| (pid, score(pid, &selected))).collect(); }
-                                        male_candidates.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                        if let Some((rm,_)) = male_candidates.first().cloned() { if let Some(pos)=selected.iter().position(|x| *x==rm){ selected.remove(pos); if is_manager(rm){ count_mgr-=1; } count_male-=1; } } else { warning = Some("Cannot free slot to add required sister".into()); break; }
-                                    }
-                                    selected.push(add_id); count_female+=1;
-                                }
-
-                                // Ensure minimum brothers
-                                while count_male < s.num_brothers {
-                                    let prefer_non_mgr = count_mgr >= s.num_shift_managers;
-                                    let mut male_pool: Vec<(i64,f64)> = candidates.iter().filter(|p| p.gender=="Male" && !selected.contains(&p.id) && (!prefer_non_mgr || !p.is_shift_manager)).map(|p| (p.id, score(p.id, &selected))).collect();
-                                    if male_pool.is_empty() { male_pool = candidates.iter().filter(|p| p.gender=="Male" && !selected.contains(&p.id)).map(|p| (p.id, score(p.id, &selected))).collect(); }
-                                    if male_pool.is_empty() { warning = Some("Fewer brothers available than required".into()); break; }
-                                    male_pool.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                                    let (add_id, _) = male_pool[0];
-                                    if selected.len() >= s.num_publishers as usize {
-                                        let mut fem_candidates: Vec<(i64,f64)> = selected.iter().cloned().filter(|pid| is_female(*pid) && !mandatory_set.contains(pid)).map(|pid| (pid, score(pid, &selected))).collect();
-                                        fem_candidates.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                        if let Some((rm,_)) = fem_candidates.first().cloned() { if let Some(pos)=selected.iter().position(|x| *x==rm){ selected.remove(pos); count_female-=1; } } else { warning = Some("Cannot free slot to add required brother".into()); break; }
-                                    }
-                                    if is_manager(add_id) { count_mgr+=1; }
-                                    selected.push(add_id); count_male+=1;
-                                }
-                            }
-                            let remaining_slots = (s.num_publishers as usize).saturating_sub(selected.len());
-                            if remaining_slots > 0 {
-                                let mut rest: Vec<_> = candidates.iter().filter(|p| !selected.contains(&p.id)).map(|p| p.id).collect();
-                                rest.sort_by(|a,b| {
-                                    use std::cmp::Ordering;
-                                    score(*b,&selected)
-                                        .partial_cmp(&score(*a,&selected))
-                                        .unwrap_or(Ordering::Equal)
-                                        .then_with(|| a.cmp(b))
-                                });
-                                for pid in rest.into_iter().take(remaining_slots) { selected.push(pid); }
-                            }
-                            // Trim if over capacity; prefer keeping mandatory pairs
-                            if selected.len() > s.num_publishers as usize {
-                                use std::collections::HashSet;
-                                let limit = s.num_publishers as usize;
-                                let selected_clone = selected.clone();
-                                let mut mandatory_set: HashSet<i64> = HashSet::new();
-                                for &pid in &selected_clone {
-                                    if let Some(rs) = rel_map.get(&pid) {
-                                        for &(oid, ref kind) in rs.iter() {
-                                            if matches!(kind, wasm_backend::RelationshipKind::Mandatory) && selected_clone.contains(&oid) {
-                                                mandatory_set.insert(pid);
-                                                mandatory_set.insert(oid);
-                                            }
-                                        }
-                                    }
-                                }
-                                let mut removable: Vec<i64> = selected.iter().copied().filter(|pid| !mandatory_set.contains(pid)).collect();
-                                let mut scored: Vec<(i64,f64)> = removable.iter().map(|pid| (*pid, score(*pid, &selected))).collect();
-                                scored.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-                                for &(pid, _) in scored.iter().rev() {
-                                    if selected.len() <= limit { break; }
-                                    if let Some(pos) = selected.iter().position(|x| *x == pid) { selected.remove(pos); }
-                                }
-                                if selected.len() > limit {
-                                    let mut overall: Vec<(i64,f64)> = selected.iter().map(|pid| (*pid, score(*pid, &selected))).collect();
-                                    overall.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-                                    while selected.len() > limit {
-                                        if let Some((pid,_)) = overall.first().copied() {
-                                            if let Some(pos) = selected.iter().position(|x| *x == pid) { selected.remove(pos); }
-                                            overall.remove(0);
-                                        } else { break; }
-                                    }
-                                    warning = Some("Had to drop some selections due to capacity; mandatory pairs may be affected".into());
-                                }
-                                if warning.is_none() { warning = Some("Trimmed extra selections to fit capacity".into()); }
-                            }
-                            if selected.len() < s.num_publishers as usize { warning = Some("Not enough available publishers".into()); }
-                            let _ = wasm_backend::create_shift(&start_dt, &end_dt, &s.location, &selected, warning.as_deref());
-                            let set = assigned_on_day.entry(ymd.clone()).or_default();
-                            for &pid in &selected { set.insert(pid); *recent_count.entry(pid).or_insert(0) += 1; for &other in &selected { if pid < other { *pair_count.entry((pid, other)).or_insert(0) += 1; } } }
-                        }
-                        cur.set_date(cur.get_date() + 1);
-                    }
-                    generating.set(false);
-                    refresh();
-                }) as Box<dyn FnMut()>);
+                let cb = Closure::wrap(Box::new(generate) as Box<dyn FnMut()>);
                 if let Some(w) = window() { let _ = w.set_timeout_with_callback_and_timeout_and_arguments_0(cb.as_ref().unchecked_ref(), 0); }
                 cb.forget();
             }
         }
     };
 
-    // export to PDF (web: open print-friendly window and trigger print)
-    let do_export = {
+    // accept the draft shifts in scope in one transaction, as part of the run they came from; the rest of the
+    // draft stays under review and joins the same run when accepted later
+    let accept_draft = {
+        let mut refresh = refresh_month;
+        move |_| {
+            let Some(run) = draft_run() else { return };
+            let scope = draft_scope();
+            let (chosen, rest): (Vec<_>, Vec<_>) = draft.read().iter().cloned().partition(|sh| in_draft_scope(sh, &scope));
+            let rows: Vec<Shift> = chosen
+                .into_iter()
+                .map(|sh| Shift { id: 0, start: sh.start, end: sh.end, location: sh.location, publishers: sh.publishers, warning: sh.warning, batch_id: None, edited: false })
+                .collect();
+            match crate::db::store().save_generation(&run, &rows) {
+                Ok(id) => {
+                    draft_run.set(Some(GenerationBatch { id, ..run }));
+                    draft.set(rest);
+                    draft_scope.set(String::new());
+                    draft_error.set(None);
+                }
+                Err(e) => draft_error.set(Some(format!("{}: {}", t("shifts.draft_accept_failed"), e))),
+            }
+            refresh();
+        }
+    };
+    let discard_draft = move |_| {
+        let scope = draft_scope();
+        draft.write().retain(|sh| !in_draft_scope(sh, &scope));
+        draft_scope.set(String::new());
+        draft_error.set(None);
+    };
+
+    // export as "pdf", "xlsx" or "ics" (web: PDF through a print-friendly window, the others as downloads; native: write the files)
+    let mut do_export = {
         let export_form = export_form.clone();
-        let publishers_all = publishers_all.clone();
         let mut export_open = export_open.clone();
-        move |_| {
+        move |format: &'static str| {
             let start = export_form.read().start.clone();
             let end = export_form.read().end.clone();
             if start.is_empty() || end.is_empty() { return; }
+            let (Ok(start_d), Ok(end_d)) = (NaiveDate::parse_from_str(&start, "%Y-%m-%d"), NaiveDate::parse_from_str(&end, "%Y-%m-%d")) else { return; };
+            if format == "ics" {
+                use crate::export::ics;
+                let (shifts, name_for) = crate::export::shifts_between(start_d, end_d);
+                if shifts.is_empty() { export_error.set(Some(t("shifts.none_in_range"))); return; }
+                let split = ics::Split::from_key(&export_form.read().ics_split);
+                let calendars = ics::calendars(&shifts, &name_for, split, &crate::export::title(&start, &end));
+                #[cfg(target_arch = "wasm32")]
+                for cal in &calendars {
+                    download(&ics::file_name(&format!("shifts_{}_{}", start, end), cal), &format!("data:text/calendar;charset=utf-8,{}", urlencoding::encode(&cal.body)));
+                }
+                #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
+                {
+                    use crate::dialogs;
+                    // Files go next to the chosen path, named after its stem
+                    spawn(async move {
+                        let Some(path) = dialogs::save_path(&crate::export::default_path(&start, &end, "ics"), &dialogs::ICS).await else { return };
+                        let dir = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+                        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| format!("shifts_{}_{}", start, end));
+                        match ics::save(&dir, &stem, &calendars) {
+                            Ok(paths) => {
+                                export_open.set(false);
+                                if !dialogs::share(&paths, &dialogs::ICS).await { export_notice.set(Some(format!("{} {}", t("shifts.export_saved_to"), dir.display()))); }
+                            }
+                            Err(e) => export_error.set(Some(format!("{}: {}", t("shifts.export_failed"), e))),
+                        }
+                    });
+                }
+                #[cfg(not(any(feature = "native-db", target_arch = "wasm32")))]
+                let _ = calendars;
+                #[cfg(not(all(feature = "native-db", not(target_arch = "wasm32"))))]
+                export_open.set(false);
+                return;
+            }
+            let by_day = crate::export::shifts_by_day(start_d, end_d);
             #[cfg(target_arch = "wasm32")]
-            {
-                use std::collections::BTreeMap;
-                // fetch shifts
-                let shifts = wasm_backend::list_shifts_between(&format!("{} 00:00:00", start), &format!("{} 23:59:59", end));
-                // map publishers
-                let mut name_for: std::collections::HashMap<i64, String> = std::collections::HashMap::new();
-                for p in publishers_all.read().iter() { name_for.insert(p.id, p.label.clone()); }
-                // group by date and sort by start time
-                let mut by_day: BTreeMap<String, Vec<(String, String, Vec<String>)>> = BTreeMap::new();
-                for s in shifts.into_iter() {
-                    let date = s.start_datetime[0..10].to_string();
-                    let start_h = s.start_datetime[11..16].to_string();
-                    let end_h = s.end_datetime[11..16].to_string();
-                    let loc = s.location.clone();
-                    let mut names: Vec<String> = s.publishers.iter().map(|pid| name_for.get(pid).cloned().unwrap_or_else(|| format!("#{}", pid))).collect();
-                    names.sort();
-                    let entry = by_day.entry(date).or_default();
-                    entry.push((format!("{}–{}", start_h, end_h), loc, names));
+            if format == "xlsx" {
+                match crate::export::xlsx::render(&by_day) {
+                    Ok(bytes) => {
+                        let binary: String = bytes.iter().map(|&b| b as char).collect();
+                        if let Some(Ok(b64)) = window().map(|w| w.btoa(&binary)) {
+                            download(&format!("shifts_{}_{}.xlsx", start, end), &format!("data:application/vnd.openxmlformats-officedocument.spreadsheetml.sheet;base64,{}", b64));
+                        }
+                    }
+                    Err(e) => { export_error.set(Some(format!("{}: {}", t("shifts.export_failed"), e))); return; }
                 }
-                for (_d, v) in by_day.iter_mut() { v.sort_by(|a,b| a.0.cmp(&b.0)); }
+            } else {
+                use crate::export::human_date;
                 // helpers
                 fn esc(s: &str) -> String {
                     s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
                 }
-                fn human_date(ymd: &str) -> String {
-                    let parts: Vec<&str> = ymd.split('-').collect();
-                    if parts.len() != 3 { return ymd.to_string(); }
-                    let y = parts[0].parse::<i32>().unwrap_or(1970);
-                    let m = parts[1].parse::<u32>().unwrap_or(1);
-                    let d = parts[2].parse::<u32>().unwrap_or(1);
-                    let wd = weekday_name_for_date(y,m,d);
-                    let names = ["January","February","March","April","May","June","July","August","September","October","November","December"];
-                    format!("{}, {} {} {}", wd, names[(m-1) as usize], d, y)
-                }
                 // build HTML
                 let mut body = String::new();
-                body.push_str(&format!("<h1>Shifts from {} to {}</h1>", esc(&start), esc(&end)));
-                for (day, items) in by_day.iter() {
-                    body.push_str(&format!("<section class=\"day\"><h2>{}</h2>", esc(&human_date(day))));
-                    for (hh, loc, names) in items.iter() {
+                body.push_str(&format!("<h1>{}</h1>", esc(&crate::export::title(&start, &end))));
+                for day in by_day.iter() {
+                    body.push_str(&format!("<section class=\"day\"><h2>{}</h2>", esc(&human_date(day.date))));
+                    for s in day.shifts.iter() {
                         body.push_str(&format!(
                             "<div class=\"card\"><div class=\"hdr\"><span class=\"time\">{}</span><span class=\"loc\">{}</span></div><div class=\"names\">{}</div></div>",
-                            esc(hh), esc(loc), esc(&names.join(", "))
+                            esc(&s.time), esc(&s.location), esc(&s.names.join(", "))
                         ));
                     }
                     body.push_str("</section>");
@@ -1048,8 +717,28 @@ pub fn Shifts() -> Element {
             }
             #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
             {
-                // TODO: Implement native PDF export (e.g., using a PDF crate). For now, no-op.
+                use crate::dialogs;
+                let filter = if format == "xlsx" { &dialogs::XLSX } else { &dialogs::PDF };
+                spawn(async move {
+                    let Some(path) = dialogs::save_path(&crate::export::default_path(&start, &end, format), filter).await else { return };
+                    let res = if format == "xlsx" {
+                        crate::export::xlsx::save(&path, &by_day).map_err(|e| e.to_string())
+                    } else {
+                        crate::export::pdf::save(&path, &crate::export::title(&start, &end), &by_day).map_err(|e| e.to_string())
+                    };
+                    match res {
+                        Ok(()) => {
+                            export_open.set(false);
+                            if !dialogs::share(std::slice::from_ref(&path), filter).await { export_notice.set(Some(format!("{} {}", t("shifts.export_saved_to"), path.display()))); }
+                        }
+                        Err(e) => export_error.set(Some(format!("{}: {}", t("shifts.export_failed"), e))),
+                    }
+                });
             }
+            #[cfg(not(any(feature = "native-db", target_arch = "wasm32")))]
+            let _ = (format, by_day);
+            // Native exports close the dialog once the file is written
+            #[cfg(not(all(feature = "native-db", not(target_arch = "wasm32"))))]
             export_open.set(false);
         }
     };
@@ -1065,15 +754,7 @@ pub fn Shifts() -> Element {
     let month_start = (mstart_y, mstart_m, 1u32);
     let month_end = (mstart_y, mstart_m, m_last);
     // Precompute week start and leading blanks count
-    #[cfg(not(target_arch = "wasm32"))]
-    let week_start: String = {
-        #[cfg(feature = "native-db")]
-        { dao::get_configuration().ok().map(|c| c.week_start).unwrap_or_else(|| "monday".into()) }
-        #[cfg(not(feature = "native-db"))]
-        { "monday".into() }
-    };
-    #[cfg(target_arch = "wasm32")]
-    let week_start: String = { wasm_backend::get_configuration().map(|c| c.week_start).unwrap_or_else(|| "monday".into()) };
+    let week_start: String = crate::db::store().week_start();
     let blanks_count: usize = {
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -1089,6 +770,63 @@ pub fn Shifts() -> Element {
     };
     let month_items = list.read().clone();
     let filtered_items: Vec<ShiftItem> = month_items.clone();
+    // Blackout labels per day of the month: the reason, and the location when it isn't congregation-wide
+    let blackouts_by_day: Vec<Vec<String>> = (1..=month_end.2).map(|day| {
+        let Some(date) = NaiveDate::from_ymd_opt(month_start.0, month_start.1, day) else { return Vec::new() };
+        blackouts.read().iter()
+            .filter(|b| b.start_date <= date && date <= b.end_date)
+            .map(|b| {
+                let reason = b.reason.clone().unwrap_or_else(|| t("blackouts.no_shifts"));
+                match &b.location { Some(l) => format!("{} · {}", reason, l), None => reason }
+            })
+            .collect()
+    }).collect();
+    // Draft shifts in this month with their index in the draft and how they compare with the saved ones
+    let month_prefix = format!("{:04}-{:02}-", month_start.0, month_start.1);
+    let draft_items: Vec<(usize, scheduler::ProposedShift, DraftDiff)> = draft.read().iter().enumerate()
+        .filter(|(_, sh)| sh.start.date().to_string().starts_with(&month_prefix))
+        .map(|(i, sh)| (i, sh.clone(), DraftDiff::of(sh, &month_items)))
+        .collect();
+    // What accept and discard can be narrowed to: each location and each day of the whole draft, with its count
+    let (draft_locations, draft_days) = {
+        use std::collections::BTreeMap;
+        let mut locations: BTreeMap<String, usize> = BTreeMap::new();
+        let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for sh in draft.read().iter() {
+            *locations.entry(sh.location.clone()).or_default() += 1;
+            *days.entry(sh.start.date()).or_default() += 1;
+        }
+        (locations.into_iter().collect::<Vec<_>>(), days.into_iter().collect::<Vec<_>>())
+    };
+    let names_for = move |pids: &[i64]| {
+        let mut names: Vec<String> = pids.iter().filter_map(|pid| publishers_all.read().iter().find(|p| p.id == *pid).map(|p| p.label.clone())).collect();
+        names.sort();
+        if names.is_empty() { t("shifts.no_publishers_assigned") } else { names.join(", ") }
+    };
+    let mut open_draft = move |i: usize| {
+        let Some(sh) = draft.read().get(i).cloned() else { return };
+        let mut w = edit_form.write();
+        w.shift_id = 0;
+        w.draft_index = Some(i);
+        w.selected_pids = sh.publishers;
+        w.add_pid.clear();
+        w.loc = sh.location;
+        w.start_dt = sh.start.format("%Y-%m-%dT%H:%M").to_string();
+        w.end_dt = sh.end.format("%Y-%m-%dT%H:%M").to_string();
+        edit_open.set(true);
+    };
+    // Schedule slots per day of the month that run on that day but have no shift yet (none on blacked-out days)
+    let planned_by_day: Vec<Vec<String>> = (1..=month_end.2).map(|day| {
+        let Some(date) = NaiveDate::from_ymd_opt(month_start.0, month_start.1, day) else { return Vec::new() };
+        let ymd = date.to_string();
+        schedules_full.read().iter()
+            .filter(|s| s.recurrence.runs_on(s.weekday, date))
+            .filter(|s| !blackouts.read().iter().any(|b| b.covers(date, &s.location)))
+            .filter(|s| !month_items.iter().any(|it| it.date == ymd && it.location == s.location && it.start_hour == s.start_hour && it.end_hour == s.end_hour))
+            .filter(|s| !draft_items.iter().any(|(_, sh, _)| sh.start.date() == date && sh.location == s.location && sh.start.format("%H:%M").to_string() == s.start_hour && sh.end.format("%H:%M").to_string() == s.end_hour))
+            .map(|s| format!("{} {}–{}", s.location, s.start_hour, s.end_hour))
+            .collect()
+    }).collect();
 
     rsx! {
         div { class: "min-h-[70vh] flex items-start justify-center",
@@ -1221,20 +959,101 @@ pub fn Shifts() -> Element {
                                     },
                                 ),
                                 disabled: generating(),
-                                onclick: move |_| auto_open.set(true),
+                                onclick: move |_| {
+                                    auto_error.set(None);
+                                    auto_form.write().seed = new_seed().to_string();
+                                    auto_open.set(true);
+                                },
                                 span { {if generating() { "⏳" } else { "⚙️" }} }
                                 span { {t("shifts.auto_generate")} }
                             }
                             button {
                                 class: "h-9 px-3 rounded-md bg-purple-600 hover:bg-purple-500 text-white text-sm font-medium",
-                                onclick: move |_| export_open.set(true),
+                                onclick: move |_| { export_error.set(None); export_notice.set(None); export_open.set(true); },
                                 {t("shifts.export")}
                             }
+                            button {
+                                class: "h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-slate-700 dark:text-slate-200 hover:bg-slate-100 dark:hover:bg-slate-800 text-sm font-medium inline-flex items-center gap-2",
+                                onclick: move |_| history_open.set(true),
+                                span { "🕘" }
+                                span { class: "hidden sm:inline", {t("shifts.generations")} }
+                            }
                         }
                         div { class: "text-sm text-slate-600 dark:text-slate-300",
                             {format!("{}–{}", fmt_date_ymd(&month_start), fmt_date_ymd(&month_end))}
                         }
                     }
+                    {export_notice.read().as_ref().map(|m| rsx! {
+                        p { class: "text-sm text-green-600 break-all", {m.clone()} }
+                    })}
+                    {last_seed().map(|seed| rsx! {
+                        p { class: "text-xs text-slate-500", {format!("{} {}", t("shifts.generated_with_seed"), seed)} }
+                    })}
+                    {(!blocked.read().is_empty()).then(|| rsx! {
+                        div { class: "rounded-md border border-amber-300 bg-amber-50 dark:bg-amber-900/20 p-3 space-y-1",
+                            div { class: "flex items-center justify-between gap-2",
+                                p { class: "text-sm font-medium text-amber-800 dark:text-amber-200", {format!("{} ({})", t("shifts.blocked_title"), blocked.read().len())} }
+                                button {
+                                    class: "text-xs text-amber-800 dark:text-amber-200 hover:underline",
+                                    onclick: move |_| blocked.set(Vec::new()),
+                                    {t("common.done")}
+                                }
+                            }
+                            ul { class: "text-xs text-amber-800 dark:text-amber-200 space-y-0.5",
+                                for line in blocked.read().iter().cloned() {
+                                    li { "{line}" }
+                                }
+                            }
+                        }
+                    })}
+                    {(!draft.read().is_empty()).then(|| rsx! {
+                        div { class: "rounded-md border border-emerald-300 bg-emerald-50/50 dark:bg-emerald-900/10 p-3 space-y-2",
+                            div { class: "flex flex-wrap items-center justify-between gap-2",
+                                p { class: "text-sm font-medium text-emerald-800 dark:text-emerald-200",
+                                    {format!("{} — {} {}", t("shifts.draft_title"), draft.read().len(), t("shifts.draft_count"))}
+                                }
+                                div { class: "flex flex-wrap items-center gap-1",
+                                    for diff in [DraftDiff::New, DraftDiff::Overlaps, DraftDiff::Busy] {
+                                        span { class: "text-[11px] px-2 py-0.5 rounded border border-dashed {diff.class()}", {t(diff.key())} }
+                                    }
+                                }
+                            }
+                            p { class: "text-xs text-slate-600 dark:text-slate-300", {t("shifts.draft_hint")} }
+                            div { class: "flex flex-wrap items-center gap-2",
+                                select {
+                                    class: "h-9 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-2 text-sm",
+                                    value: draft_scope(),
+                                    onchange: move |e| draft_scope.set(e.value()),
+                                    option { value: "", selected: draft_scope().is_empty(), {format!("{} ({})", t("shifts.draft_scope_all"), draft.read().len())} }
+                                    optgroup { label: t("shifts.draft_by_location"),
+                                        for (loc, n) in draft_locations.clone() {
+                                            option { value: "loc:{loc}", selected: draft_scope() == format!("loc:{loc}"), "{loc} ({n})" }
+                                        }
+                                    }
+                                    optgroup { label: t("shifts.draft_by_day"),
+                                        for (day, n) in draft_days.clone() {
+                                            option { value: "day:{day}", selected: draft_scope() == format!("day:{day}"),
+                                                {format!("{} ({})", crate::i18n::format_date_ymd(&day.to_string()), n)}
+                                            }
+                                        }
+                                    }
+                                }
+                                button {
+                                    class: "h-9 px-3 rounded-md bg-emerald-600 hover:bg-emerald-500 text-white text-sm font-medium",
+                                    onclick: accept_draft,
+                                    {t("shifts.draft_accept")}
+                                }
+                                button {
+                                    class: "h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600 text-sm",
+                                    onclick: discard_draft,
+                                    {t("shifts.draft_discard")}
+                                }
+                            }
+                            {draft_error.read().as_ref().map(|e| rsx! {
+                                p { class: "text-sm text-red-600", {e.clone()} }
+                            })}
+                        }
+                    })}
                     {
                         if view() == "agenda" {
                             // Show date and weekday for clarity
@@ -1242,6 +1061,22 @@ pub fn Shifts() -> Element {
                             // color dot per location (better contrast than tinted background)
                             rsx! {
                                 ul { class: "divide-y divide-slate-200 dark:divide-slate-700",
+                                    for (i, sh, diff) in draft_items.clone() {
+                                        li { class: "py-3",
+                                            div {
+                                                class: "min-w-0 w-full cursor-pointer rounded-md border border-dashed px-3 py-2 {diff.class()}",
+                                                title: sh.warning.clone().unwrap_or_default(),
+                                                onclick: move |_| open_draft(i),
+                                                div { class: "text-sm text-slate-500",
+                                                    {format!("{} · {}", date_with_weekday(&sh.start.date().to_string()), t(diff.key()))}
+                                                }
+                                                div { class: "font-medium",
+                                                    {format!("{} • {}–{}", sh.location, sh.start.format("%H:%M"), sh.end.format("%H:%M"))}
+                                                }
+                                                div { class: "text-xs text-slate-600 dark:text-slate-300", {names_for(&sh.publishers)} }
+                                            }
+                                        }
+                                    }
                                     for item in filtered_items.clone() {
                                         li { class: "py-3 flex items-start justify-between gap-3",
                                             div {
@@ -1253,6 +1088,7 @@ pub fn Shifts() -> Element {
                                                     move |_| {
                                                         let mut w = edit_form.write();
                                                         w.shift_id = it.id;
+                                                        w.draft_index = None;
                                                         w.selected_pids = it.publishers.clone();
                                                         w.add_pid.clear();
                                                         w.loc = it.location.clone();
@@ -1262,18 +1098,7 @@ pub fn Shifts() -> Element {
                                                     }
                                                 },
                                                 div { class: "text-sm text-slate-500 flex items-center gap-2",
-                                                    {
-                                                        let parts: Vec<&str> = item.date.split('-').collect();
-                                                        if parts.len() == 3 {
-                                                            let y = parts[0].parse::<i32>().unwrap_or(1970);
-                                                            let m = parts[1].parse::<u32>().unwrap_or(1);
-                                                            let d = parts[2].parse::<u32>().unwrap_or(1);
-                                                            let wd = weekday_name_for_date(y, m, d);
-                                                            format!("{} ({})", item.date.clone(), wd)
-                                                        } else {
-                                                            item.date.clone()
-                                                        }
-                                                    }
+                                                    {date_with_weekday(&item.date)}
                                                     {select_mode().then(|| rsx! {
                                                         input {
                                                             r#type: "checkbox",
@@ -1325,7 +1150,7 @@ pub fn Shifts() -> Element {
                                             }
                                         }
                                     }
-                                    {(filtered_items.is_empty()).then(|| rsx! {
+                                    {(filtered_items.is_empty() && draft_items.is_empty()).then(|| rsx! {
                                         li { class: "py-3 text-sm text-slate-500", {t("shifts.none_in_range")} }
                                     })}
                                 }
@@ -1340,8 +1165,12 @@ pub fn Shifts() -> Element {
                                         div { class: "min-h-24 rounded-md bg-transparent" }
                                     }
                                     for day in 1..=month_end.2 {
-                                        div { class: "rounded-md border border-slate-200 dark:border-slate-700 p-2 space-y-1",
+                                        div {
+                                            class: if blackouts_by_day[day as usize - 1].is_empty() { "rounded-md border border-slate-200 dark:border-slate-700 p-2 space-y-1" } else { "rounded-md border border-slate-200 dark:border-slate-700 bg-slate-100 dark:bg-slate-900 p-2 space-y-1" },
                                             div { class: "text-xs text-slate-500 mb-1", {format!("{}", day)} }
+                                            for label in blackouts_by_day[day as usize - 1].clone() {
+                                                div { class: "text-[11px] font-medium text-slate-600 dark:text-slate-300", {format!("⛔ {}", label)} }
+                                            }
                                             for it in filtered_items
                                                 .iter()
                                                 .filter(|it| {
@@ -1381,6 +1210,7 @@ pub fn Shifts() -> Element {
                                                                 move |_| {
                                                                     let mut w = edit_form.write();
                                                                     w.shift_id = it2.id;
+                                                                    w.draft_index = None;
                                                                     w.selected_pids = it2.publishers.clone();
                                                                     w.add_pid.clear();
                                                                     w.loc = it2.location.clone();
@@ -1409,6 +1239,31 @@ pub fn Shifts() -> Element {
                                                     }
                                                 }
                                             }
+                                            for (i, sh, diff) in draft_items
+                                                .iter()
+                                                .filter(|(_, sh, _)| sh.start.date().to_string() == format!("{:04}-{:02}-{:02}", month_start.0, month_start.1, day))
+                                                .cloned()
+                                            {
+                                                div {
+                                                    class: "text-[12px] flex flex-col gap-1 cursor-pointer border border-dashed rounded p-2 {diff.class()}",
+                                                    title: match &sh.warning { Some(w) => format!("{} · {}", t(diff.key()), w), None => t(diff.key()) },
+                                                    onclick: move |_| open_draft(i),
+                                                    div { class: "flex items-center gap-2 flex-wrap",
+                                                        span { class: "font-semibold text-slate-900 dark:text-slate-100", {sh.location.clone()} }
+                                                        span { class: "text-xs text-slate-600 dark:text-slate-300",
+                                                            {format!("{}–{}", sh.start.format("%H:%M"), sh.end.format("%H:%M"))}
+                                                        }
+                                                    }
+                                                    div { class: "text-[12px] text-slate-800 dark:text-slate-200 whitespace-normal break-words", {names_for(&sh.publishers)} }
+                                                }
+                                            }
+                                            for label in planned_by_day[day as usize - 1].clone() {
+                                                div {
+                                                    class: "text-[11px] rounded border border-dashed border-slate-300 dark:border-slate-600 px-2 py-1 text-slate-500 dark:text-slate-400",
+                                                    title: t("shifts.planned_hint"),
+                                                    {label}
+                                                }
+                                            }
                                         }
                                     }
                                 }
@@ -1535,60 +1390,32 @@ pub fn Shifts() -> Element {
                                         .unwrap_or("")
                                         .to_string();
                                     for pid in manual_form.read().selected_pids.iter() {
-                                        #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                        {
-                                            if let Ok(d) = chrono::NaiveDate::parse_from_str(
-                                                &date_s,
-                                                "%Y-%m-%d",
-                                            ) {
-                                                let name = publishers_all
-                                                    .read()
-                                                    .iter()
-                                                    .find(|pp| pp.id == *pid)
-                                                    .map(|pp| pp.label.clone())
-                                                    .unwrap_or_else(|| format!("#{pid}"));
-                                                if dao::is_absent_on(*pid, d).unwrap_or(false) {
-                                                    warns
-                                                        .push(
-                                                            format!("{} {}", name, t("shifts.warn_absent_generic")),
-                                                        );
-                                                }
-                                                let day_start = chrono::NaiveDateTime::new(
-                                                    d,
-                                                    chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-                                                );
-                                                let day_end = chrono::NaiveDateTime::new(
-                                                    d,
-                                                    chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
-                                                );
-                                                let existing = dao::list_shifts_between(day_start, day_end)
-                                                    .unwrap_or_default();
-                                                if existing.iter().any(|sh| sh.publishers.contains(pid)) {
-                                                    warns
-                                                        .push(
-                                                            format!("{} {}", name, t("shifts.warn_already_has_shift")),
-                                                        );
-                                                }
-                                            }
-                                        }
-                                        #[cfg(target_arch = "wasm32")]
-                                        {
+                                        if let Ok(d) = chrono::NaiveDate::parse_from_str(
+                                            &date_s,
+                                            "%Y-%m-%d",
+                                        ) {
                                             let name = publishers_all
                                                 .read()
                                                 .iter()
                                                 .find(|pp| pp.id == *pid)
                                                 .map(|pp| pp.label.clone())
                                                 .unwrap_or_else(|| format!("#{pid}"));
-                                            if wasm_backend::is_absent_on(*pid, &date_s) {
+                                            if crate::db::store().is_absent_on(*pid, d).unwrap_or(false) {
                                                 warns
                                                     .push(
                                                         format!("{} {}", name, t("shifts.warn_absent_generic")),
                                                     );
                                             }
-                                            let existing = wasm_backend::list_shifts_between(
-                                                &format!("{} 00:00:00", date_s),
-                                                &format!("{} 23:59:59", date_s),
+                                            let day_start = chrono::NaiveDateTime::new(
+                                                d,
+                                                chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+                                            );
+                                            let day_end = chrono::NaiveDateTime::new(
+                                                d,
+                                                chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
                                             );
+                                            let existing = crate::db::store().list_shifts_between(day_start, day_end)
+                                                .unwrap_or_default();
                                             if existing.iter().any(|sh| sh.publishers.contains(pid)) {
                                                 warns
                                                     .push(
@@ -1643,15 +1470,39 @@ pub fn Shifts() -> Element {
                             oninput: move |e| export_form.write().end = e.value(),
                         }
                     }
+                    div { class: "flex flex-col gap-1",
+                        label { class: "text-sm font-medium text-slate-700 dark:text-slate-200", {t("shifts.export_ics_split")} }
+                        select {
+                            class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm",
+                            value: export_form.read().ics_split.clone(),
+                            onchange: move |e| export_form.write().ics_split = e.value(),
+                            option { value: "all", {t("shifts.export_ics_all")} }
+                            option { value: "publisher", {t("shifts.export_ics_publisher")} }
+                            option { value: "location", {t("shifts.export_ics_location")} }
+                        }
+                    }
+                    {export_error.read().as_ref().map(|e| rsx! {
+                        p { class: "text-sm text-red-600", {e.clone()} }
+                    })}
                     div { class: "flex items-center justify-end gap-2",
                         button {
                             class: "h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600",
                             onclick: move |_| export_open.set(false),
                             {t("common.cancel")}
                         }
+                        button {
+                            class: "h-9 px-3 rounded-md border border-purple-600 text-purple-700 dark:text-purple-300 hover:bg-purple-50 dark:hover:bg-purple-900/30",
+                            onclick: move |_| do_export("ics"),
+                            {t("shifts.export_ics")}
+                        }
+                        button {
+                            class: "h-9 px-3 rounded-md border border-purple-600 text-purple-700 dark:text-purple-300 hover:bg-purple-50 dark:hover:bg-purple-900/30",
+                            onclick: move |_| do_export("xlsx"),
+                            {t("shifts.export_excel")}
+                        }
                         button {
                             class: "h-9 px-3 rounded-md bg-purple-600 hover:bg-purple-500 text-white",
-                            onclick: do_export,
+                            onclick: move |_| do_export("pdf"),
                             {t("shifts.export_pdf")}
                         }
                     }
@@ -1665,7 +1516,9 @@ pub fn Shifts() -> Element {
         {edit_open().then(|| rsx! {
             div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4",
                 div { class: "w-full max-w-md rounded-xl border border-slate-200 dark:border-slate-700 bg-white dark:bg-slate-800 shadow-lg p-5 space-y-4",
-                    h2 { class: "text-lg font-semibold", {t("shifts.edit_title")} }
+                    h2 { class: "text-lg font-semibold",
+                        {if edit_form.read().draft_index.is_some() { t("shifts.draft_edit_title") } else { t("shifts.edit_title") }}
+                    }
                     div { class: "space-y-2",
                         div { class: "space-y-1",
                             label { class: "text-xs text-slate-600 dark:text-slate-300", {t("schedules.location")} }
@@ -1778,72 +1631,46 @@ pub fn Shifts() -> Element {
                                     .unwrap_or("")
                                     .to_string();
                                 for pid in edit_form.read().selected_pids.iter() {
-                                    #[cfg(all(feature = "native-db", not(target_arch = "wasm32")))]
-                                    {
-                                        if let Ok(d) = chrono::NaiveDate::parse_from_str(
-                                            &date_s,
-                                            "%Y-%m-%d",
-                                        ) {
-                                            let name = publishers_all
-                                                .read()
-                                                .iter()
-                                                .find(|pp| pp.id == *pid)
-                                                .map(|pp| pp.label.clone())
-                                                .unwrap_or_else(|| format!("#{pid}"));
-                                            if dao::is_absent_on(*pid, d).unwrap_or(false) {
-                                                warns
-                                                    .push(
-                                                        format!("{} {}", name, t("shifts.warn_absent_generic")),
-                                                    );
-                                            }
-                                            let day_start = chrono::NaiveDateTime::new(
-                                                d,
-                                                chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-                                            );
-                                            let day_end = chrono::NaiveDateTime::new(
-                                                d,
-                                                chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
-                                            );
-                                            let existing = dao::list_shifts_between(day_start, day_end)
-                                                .unwrap_or_default();
-                                            if existing
-                                                .iter()
-                                                .any(|sh| {
-                                                    sh.id != edit_form.read().shift_id
-                                                        && sh.publishers.contains(pid)
-                                                })
-                                            {
-                                                warns
-                                                    .push(
-                                                        format!("{} {}", name, t("shifts.warn_already_has_shift")),
-                                                    );
-                                            }
-                                        }
-                                    }
-                                    #[cfg(target_arch = "wasm32")]
-                                    {
+                                    if let Ok(d) = chrono::NaiveDate::parse_from_str(
+                                        &date_s,
+                                        "%Y-%m-%d",
+                                    ) {
                                         let name = publishers_all
                                             .read()
                                             .iter()
                                             .find(|pp| pp.id == *pid)
                                             .map(|pp| pp.label.clone())
                                             .unwrap_or_else(|| format!("#{pid}"));
-                                        if wasm_backend::is_absent_on(*pid, &date_s) {
+                                        if crate::db::store().is_absent_on(*pid, d).unwrap_or(false) {
                                             warns
                                                 .push(
                                                     format!("{} {}", name, t("shifts.warn_absent_generic")),
                                                 );
                                         }
-                                        let existing = wasm_backend::list_shifts_between(
-                                            &format!("{} 00:00:00", date_s),
-                                            &format!("{} 23:59:59", date_s),
+                                        let day_start = chrono::NaiveDateTime::new(
+                                            d,
+                                            chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+                                        );
+                                        let day_end = chrono::NaiveDateTime::new(
+                                            d,
+                                            chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
                                         );
+                                        let existing = crate::db::store().list_shifts_between(day_start, day_end)
+                                            .unwrap_or_default();
+                                        let draft_index = edit_form.read().draft_index;
                                         if existing
                                             .iter()
                                             .any(|sh| {
                                                 sh.id != edit_form.read().shift_id
                                                     && sh.publishers.contains(pid)
                                             })
+                                            || draft_index.is_some_and(|di| {
+                                                draft
+                                                    .read()
+                                                    .iter()
+                                                    .enumerate()
+                                                    .any(|(i, sh)| i != di && sh.start.date() == d && sh.publishers.contains(pid))
+                                            })
                                         {
                                             warns
                                                 .push(
@@ -1868,11 +1695,19 @@ pub fn Shifts() -> Element {
                             onclick: {
                                 let mut confirm_delete_id = confirm_delete_id.clone();
                                 let id = edit_form.read().shift_id;
+                                let draft_index = edit_form.read().draft_index;
                                 move |_| {
-                                    confirm_delete_id.set(Some(id));
+                                    match draft_index {
+                                        // Nothing was saved for a draft shift, so it goes without asking
+                                        Some(i) => {
+                                            if i < draft.read().len() { draft.write().remove(i); }
+                                            edit_open.set(false);
+                                        }
+                                        None => confirm_delete_id.set(Some(id)),
+                                    }
                                 }
                             },
-                            {t("common.delete")}
+                            {if edit_form.read().draft_index.is_some() { t("shifts.draft_remove") } else { t("common.delete") }}
                         }
                         button {
                             class: "h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600",
@@ -1943,6 +1778,44 @@ pub fn Shifts() -> Element {
                             oninput: move |e| auto_form.write().end = e.value(),
                         }
                     }
+                    div { class: "space-y-1",
+                        label { class: "text-xs text-slate-600 dark:text-slate-300", {t("shifts.auto_mode")} }
+                        select {
+                            class: "h-10 w-full rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm",
+                            value: auto_form.read().mode.clone(),
+                            onchange: move |e| auto_form.write().mode = e.value(),
+                            for mode in [scheduler::Mode::Greedy, scheduler::Mode::Exact] {
+                                option {
+                                    value: mode.key(),
+                                    selected: scheduler::Mode::from_key(&auto_form.read().mode) == mode,
+                                    {t(&format!("shifts.auto_mode_{}", mode.key()))}
+                                }
+                            }
+                        }
+                        p { class: "text-xs text-slate-500", {t(&format!("shifts.auto_mode_{}_hint", scheduler::Mode::from_key(&auto_form.read().mode).key()))} }
+                    }
+                    div { class: "space-y-1",
+                        label { class: "text-xs text-slate-600 dark:text-slate-300", {t("shifts.seed")} }
+                        div { class: "flex items-center gap-2",
+                            input {
+                                r#type: "text",
+                                inputmode: "numeric",
+                                class: "h-10 w-full rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-900 px-3 py-2 text-sm font-mono",
+                                value: auto_form.read().seed.clone(),
+                                oninput: move |e| { auto_error.set(None); auto_form.write().seed = e.value(); },
+                            }
+                            button {
+                                class: "h-10 px-3 rounded-md border border-slate-300 dark:border-slate-600",
+                                title: t("shifts.seed_new"),
+                                onclick: move |_| { auto_error.set(None); auto_form.write().seed = new_seed().to_string(); },
+                                "🎲"
+                            }
+                        }
+                        p { class: "text-xs text-slate-500", {t("shifts.seed_hint")} }
+                    }
+                    {auto_error.read().as_ref().map(|err| rsx! {
+                        p { class: "text-red-600 text-sm", {err.clone()} }
+                    })}
                     div { class: "flex items-center justify-end gap-2",
                         button {
                             class: "h-9 px-3 rounded-md border border-slate-300 dark:border-slate-600",
@@ -1968,6 +1841,18 @@ pub fn Shifts() -> Element {
                 }
             }
         })}
+
+        {history_open().then(|| rsx! {
+            GenerationHistory {
+                on_close: {
+                    let mut refresh = refresh_month;
+                    move |reverted: bool| {
+                        history_open.set(false);
+                        if reverted { refresh(); }
+                    }
+                },
+            }
+        })}
     }
 }
 
@@ -0,0 +1,127 @@
+use dioxus::prelude::*;
+use crate::db::cipher::{self, CipherError};
+use crate::i18n::{apply_theme, set_date_format, set_lang, t};
+
+// Shown before Home on encrypted builds: creates the passphrase on first run, asks for it afterwards.
+// A forgotten passphrase can be replaced with the recovery code from Configuration.
+#[component]
+pub fn Unlock() -> Element {
+    let first_run = use_signal(cipher::needs_setup);
+    let mut passphrase = use_signal(String::new);
+    let mut confirm = use_signal(String::new);
+    let mut recovering = use_signal(|| false);
+    let mut code = use_signal(String::new);
+    let mut error = use_signal(|| Option::<String>::None);
+    let mut unlocked: Signal<bool> = use_context();
+    let mut configured: Signal<bool> = use_context();
+
+    let submit = move |_| {
+        error.set(None);
+        let pass = passphrase.read().clone();
+        if (first_run() || recovering()) && pass != *confirm.read() { error.set(Some(t("unlock.mismatch"))); return; }
+        let res = if first_run() {
+            cipher::setup(&pass)
+        } else if recovering() {
+            cipher::recover(&code.read(), &pass)
+        } else {
+            cipher::unlock(&pass)
+        };
+        passphrase.set(String::new());
+        confirm.set(String::new());
+        code.set(String::new());
+        match res {
+            Ok(()) => {
+                // Configuration was unreadable while locked; load it now
+                let store = crate::db::store();
+                if let Ok(cfg) = store.get_configuration() {
+                    set_lang(&cfg.language);
+                    set_date_format(&cfg.date_format);
+                    apply_theme(&cfg.theme);
+                }
+                configured.set(store.configuration_is_set());
+                unlocked.set(true);
+            }
+            Err(CipherError::WrongPassphrase) => error.set(Some(t("unlock.wrong"))),
+            Err(CipherError::TooShort) => error.set(Some(t("unlock.too_short"))),
+            Err(CipherError::WrongRecoveryCode) => error.set(Some(t("unlock.wrong_code"))),
+            Err(CipherError::NoRecoveryCode) => error.set(Some(t("unlock.no_code"))),
+            Err(e) => error.set(Some(e.to_string())),
+        }
+    };
+
+    rsx! {
+        div { class: "min-h-full flex flex-col items-center justify-start pt-10",
+            div { class: "w-full max-w-xl space-y-8",
+                div { class: "space-y-2",
+                    h1 { class: "text-3xl font-bold tracking-tight",
+                        {if first_run() { t("unlock.setup_title") } else if recovering() { t("unlock.recover_title") } else { t("unlock.title") }}
+                    }
+                    p { class: "text-sm text-slate-600 dark:text-slate-300",
+                        {if first_run() { t("unlock.setup_subtitle") } else if recovering() { t("unlock.recover_subtitle") } else { t("unlock.subtitle") }}
+                    }
+                }
+                div { class: "bg-slate-50 dark:bg-slate-900/50 border border-slate-200 dark:border-slate-700 rounded-lg p-6 shadow-sm space-y-5",
+                    {recovering().then(|| rsx! {
+                        div { class: "flex flex-col gap-2",
+                            label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                                {t("unlock.recovery_code")}
+                            }
+                            input {
+                                autofocus: true,
+                                class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-800 px-3 py-2 text-sm font-mono focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: code.read().clone(),
+                                oninput: move |e| code.set(e.value()),
+                            }
+                        }
+                    })}
+                    div { class: "flex flex-col gap-2",
+                        label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                            {if recovering() { t("security.new") } else { t("unlock.passphrase") }}
+                        }
+                        input {
+                            r#type: "password",
+                            autofocus: true,
+                            class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-800 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                            value: passphrase.read().clone(),
+                            oninput: move |e| passphrase.set(e.value()),
+                        }
+                    }
+                    {(first_run() || recovering()).then(|| rsx! {
+                        div { class: "flex flex-col gap-2",
+                            label { class: "text-sm font-medium text-slate-700 dark:text-slate-200",
+                                {t("unlock.confirm")}
+                            }
+                            input {
+                                r#type: "password",
+                                class: "h-10 rounded-md border border-slate-300 dark:border-slate-600 bg-white dark:bg-slate-800 px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                value: confirm.read().clone(),
+                                oninput: move |e| confirm.set(e.value()),
+                            }
+                        }
+                    })}
+                    {first_run().then(|| rsx! {
+                        p { class: "text-xs text-amber-700 dark:text-amber-300", {t("unlock.setup_warning")} }
+                    })}
+                    {error.read().as_ref().map(|e| rsx! {
+                        p { class: "text-sm text-red-600", {e.clone()} }
+                    })}
+                    div { class: "flex items-center justify-between gap-2",
+                        button {
+                            class: "inline-flex items-center gap-2 rounded-md bg-blue-600 hover:bg-blue-500 text-white text-sm font-medium px-4 py-2 transition disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: passphrase.read().is_empty() || (recovering() && code.read().is_empty()),
+                            onclick: submit,
+                            {if first_run() { t("unlock.create") } else if recovering() { t("unlock.recover") } else { t("unlock.unlock") }}
+                        }
+                        {(!first_run()).then(|| rsx! {
+                            button {
+                                class: "text-sm text-blue-600 dark:text-blue-400 hover:underline",
+                                onclick: move |_| { error.set(None); recovering.set(!recovering()); },
+                                {if recovering() { t("unlock.back") } else { t("unlock.forgot") }}
+                            }
+                        })}
+                    }
+                }
+            }
+        }
+    }
+}